@@ -17,6 +17,7 @@ std::string resultToString(PlannerResult result) {
         case PLANNER_PARSE_ERROR: return "Parse Error";
         case PLANNER_FILE_ERROR: return "File Error";
         case PLANNER_INVALID_HANDLE: return "Invalid Handle";
+        case PLANNER_RESOURCE_LIMIT: return "Resource Limit";
         default: return "Unknown";
     }
 }
@@ -27,13 +27,25 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     ) {
         Ok(SearchResult::Solution(plan)) => {
             println!("   ✅ Solution found with {} actions", plan.actions.len());
-            for (i, action) in plan.actions.iter().enumerate() {
-                println!("     {}. {} (time: {:.2})", i+1, action.name, action.start_time);
+            for (i, action_idx) in plan.actions.iter().enumerate() {
+                println!("     {}. Action index {}", i+1, action_idx);
             }
         }
-        Ok(SearchResult::Failure) => {
+        Ok(SearchResult::Failure | SearchResult::Partial { .. }) => {
             println!("   ❌ No solution found");
         }
+        Ok(SearchResult::Unsolvable(reason)) => {
+            println!("   ❌ Unsolvable: {}", reason);
+        }
+        Ok(SearchResult::ResourceLimit(reason)) => {
+            println!("   ⏱️  Stopped early: {}", reason);
+        }
+        Ok(SearchResult::Cancelled) => {
+            println!("   ⏹️  Cancelled");
+        }
+        Ok(SearchResult::InvalidTask(error)) => {
+            println!("   ⚠️  Invalid task: {}", error);
+        }
         Err(e) => {
             println!("   ⚠️  Error: {}", e);
         }
@@ -68,9 +80,21 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("   ✅ Solution found with {} actions", plan.actions.len());
             println!("   Plan cost: {:.2}", plan.cost);
         }
-        SearchResult::Failure => {
+        SearchResult::Failure | SearchResult::Partial { .. } => {
             println!("   ❌ No solution found");
         }
+        SearchResult::Unsolvable(reason) => {
+            println!("   ❌ Unsolvable: {}", reason);
+        }
+        SearchResult::ResourceLimit(reason) => {
+            println!("   ⏱️  Stopped early: {}", reason);
+        }
+        SearchResult::Cancelled => {
+            println!("   ⏹️  Cancelled");
+        }
+        SearchResult::InvalidTask(error) => {
+            println!("   ⚠️  Invalid task: {}", error);
+        }
     }
     println!();
 
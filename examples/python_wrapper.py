@@ -16,6 +16,7 @@ class PlannerResult(IntEnum):
     PARSE_ERROR = 3
     FILE_ERROR = 4
     INVALID_HANDLE = 5
+    RESOURCE_LIMIT = 6
 
 class TemporalPlannerWrapper:
     """Python wrapper for the Rust temporal planner"""
@@ -51,9 +51,21 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     i + 1, action_idx, plan.cost / plan.actions.len() as f64);
             }
         }
-        SearchResult::Failure => {
+        SearchResult::Failure | SearchResult::Partial { .. } => {
             println!("❌ No solution found");
         }
+        SearchResult::Unsolvable(reason) => {
+            println!("❌ Unsolvable: {}", reason);
+        }
+        SearchResult::ResourceLimit(reason) => {
+            println!("⏱️  Stopped early: {}", reason);
+        }
+        SearchResult::Cancelled => {
+            println!("⏹️  Cancelled");
+        }
+        SearchResult::InvalidTask(error) => {
+            println!("⚠️  Invalid task: {}", error);
+        }
     }
 
     println!();
@@ -9,12 +9,17 @@
 #ifndef TEMPORAL_PLANNER_H
 #define TEMPORAL_PLANNER_H
 
+#include <stdint.h>
+
 #ifdef __cplusplus
 extern "C" {
 #endif
 
 /**
  * @brief Opaque handle for the temporal planner instance
+ *
+ * Calls on one handle may come from several threads; they are serialized by a lock inside
+ * the handle. Only temporal_planner_destroy must not run alongside other calls.
  */
 typedef struct PlannerHandle PlannerHandle;
 
@@ -27,9 +32,30 @@ typedef enum {
     PLANNER_NO_SOLUTION = 2,       /**< No solution exists for the problem */
     PLANNER_PARSE_ERROR = 3,       /**< Error parsing PDDL content */
     PLANNER_FILE_ERROR = 4,        /**< Error reading PDDL files */
-    PLANNER_INVALID_HANDLE = 5     /**< Invalid planner handle */
+    PLANNER_INVALID_HANDLE = 5,    /**< Invalid planner handle */
+    PLANNER_RESOURCE_LIMIT = 6,    /**< Search stopped at its expansion or time limit */
+    PLANNER_INVALID_INDEX = 7,     /**< Plan step index out of range */
+    PLANNER_INVALID_CONFIG = 8     /**< Unknown algorithm or heuristic code */
 } PlannerResult;
 
+/**
+ * @brief Search algorithm codes for temporal_planner_set_config
+ */
+typedef enum {
+    PLANNER_ALGORITHM_ASTAR = 0,   /**< Weighted A* */
+    PLANNER_ALGORITHM_GREEDY = 1   /**< Greedy best-first search */
+} PlannerAlgorithm;
+
+/**
+ * @brief Heuristic codes for temporal_planner_set_config
+ */
+typedef enum {
+    PLANNER_HEURISTIC_FF = 0,         /**< Relaxed-plan (FF) heuristic, the default */
+    PLANNER_HEURISTIC_HMAX = 1,       /**< Admissible h^max */
+    PLANNER_HEURISTIC_HADD = 2,       /**< Additive h^add */
+    PLANNER_HEURISTIC_GOAL_COUNT = 3  /**< Number of unmet goal conditions */
+} PlannerHeuristic;
+
 /**
  * @brief Create a new temporal planner instance
  * @return Opaque handle to the planner, or NULL on failure
@@ -72,6 +98,46 @@ PlannerResult temporal_planner_solve_content(
     int* plan_length
 );
 
+/**
+ * @brief Configure the search used by later solves on this handle
+ * @param handle Planner handle
+ * @param algorithm_code A PlannerAlgorithm value
+ * @param heuristic_code A PlannerHeuristic value
+ * @param max_time_ms Time limit per solve in milliseconds, or 0 for none
+ * @return PLANNER_SUCCESS, or PLANNER_INVALID_CONFIG for an unknown code (leaving the planner unchanged)
+ */
+PlannerResult temporal_planner_set_config(
+    PlannerHandle* handle,
+    int algorithm_code,
+    int heuristic_code,
+    uint64_t max_time_ms
+);
+
+/**
+ * @brief Get one step of the plan found by the last successful solve
+ * @param handle Planner handle
+ * @param index Step index, below the plan length reported by the solve
+ * @param out_name Output parameter for the ground action name (caller must free with temporal_planner_free_string, can be NULL)
+ * @param out_start Output parameter for the step's start time (can be NULL)
+ * @param out_duration Output parameter for the step's duration (can be NULL)
+ * @return PLANNER_SUCCESS, or PLANNER_INVALID_INDEX if the last solve found no plan with that many steps
+ */
+PlannerResult temporal_planner_get_plan_action(
+    const PlannerHandle* handle,
+    int index,
+    char** out_name,
+    double* out_start,
+    double* out_duration
+);
+
+/**
+ * @brief Get the reason the last solve on this handle failed
+ * @param handle Planner handle
+ * @return C string with the error message (caller must free with temporal_planner_free_string),
+ *         or NULL if the last solve succeeded or none has run
+ */
+char* temporal_planner_last_error(const PlannerHandle* handle);
+
 /**
  * @brief Get planner version information
  * @return C string with version info (caller must free with temporal_planner_free_string)
@@ -156,6 +222,53 @@ public:
         return {result, planLength};
     }
 
+    /**
+     * @brief Get one step of the plan found by the last successful solve
+     * @param index Step index, below the plan length
+     * @param name Receives the ground action name
+     * @param start Receives the step's start time
+     * @param duration Receives the step's duration
+     * @return false if the index is out of range
+     */
+    bool getPlanAction(int index, std::string& name, double& start, double& duration) {
+        char* name_cstr = nullptr;
+        PlannerResult result = temporal_planner_get_plan_action(
+            handle, index, &name_cstr, &start, &duration);
+        if (result != PLANNER_SUCCESS) {
+            return false;
+        }
+        if (name_cstr) {
+            name = name_cstr;
+            temporal_planner_free_string(name_cstr);
+        }
+        return true;
+    }
+
+    /**
+     * @brief Configure the search used by later solves
+     * @param algorithm Search algorithm
+     * @param heuristic Heuristic guiding the search
+     * @param maxTimeMs Time limit per solve in milliseconds, or 0 for none
+     * @return Result code
+     */
+    PlannerResult setConfig(PlannerAlgorithm algorithm, PlannerHeuristic heuristic, uint64_t maxTimeMs = 0) {
+        return temporal_planner_set_config(handle, algorithm, heuristic, maxTimeMs);
+    }
+
+    /**
+     * @brief Get the reason the last solve failed
+     * @return Error message, empty if the last solve succeeded
+     */
+    std::string lastError() {
+        char* error_cstr = temporal_planner_last_error(handle);
+        if (error_cstr) {
+            std::string error(error_cstr);
+            temporal_planner_free_string(error_cstr);
+            return error;
+        }
+        return "";
+    }
+
     // Disable copy constructor and assignment operator
     TemporalPlannerCpp(const TemporalPlannerCpp&) = delete;
     TemporalPlannerCpp& operator=(const TemporalPlannerCpp&) = delete;
@@ -1,6 +1,8 @@
+use std::collections::HashSet;
 use std::fs;
 use std::time::Instant;
-use temporal_planner::TemporalTask;
+use temporal_planner::state_space::{EnabledActions, StateSpace, TemporalState};
+use temporal_planner::{TemporalAStarSearch, TemporalSearchEngine, TemporalTask};
 
 fn main() {
     println!("⚡ Temporal Planner Performance Benchmarks");
@@ -64,24 +66,28 @@ fn main() {
         
         // Parse once more to analyze the domain
         let task = TemporalTask::from_pddl(&domain_content, &problem_content);
-        let durative_actions = task.actions.iter().filter(|a| a.duration > 1.0).count();
+        let summary = task.summary();
         
         println!("   Domain Analysis:");
         println!("     Actions: {} ({}% durative)", 
-                task.actions.len(),
-                if task.actions.len() > 0 { 
-                    durative_actions * 100 / task.actions.len()
-                } else { 
-                    0 
-                });
+                summary.actions,
+                (summary.durative_actions * 100).checked_div(summary.actions).unwrap_or(0));
         println!("     Initial Facts: {}", task.initial_state.facts.len());
-        println!("     Goal Conditions: {}", task.goal_conditions.len());
+        println!("     Goal Conditions: {}", summary.goal_conditions);
         
         // Throughput calculation
         let throughput = 1_000_000.0 / mean_time; // parses per second
         println!("     Throughput: {:.1} parses/second", throughput);
     }
     
+    // Search benchmark
+    println!("\n🔎 Search: String-Based vs Compiled Task");
+    benchmark_search();
+
+    // Successor generation benchmark
+    println!("\n🌿 Successors: Full Check vs Incremental Applicability");
+    benchmark_successor_generation();
+
     // Memory usage benchmark
     println!("\n🔍 Memory Usage Analysis");
     benchmark_memory_usage();
@@ -90,6 +96,94 @@ fn main() {
     println!("✅ Benchmarking completed!");
 }
 
+fn benchmark_search() {
+    let test_cases = vec![
+        ("Small Domain", "tests/fixtures/domains/simple_robot.pddl", "tests/fixtures/problems/simple_delivery.pddl"),
+        ("Medium Domain", "tests/fixtures/domains/blocks_world.pddl", "tests/fixtures/problems/stack_blocks.pddl"),
+    ];
+    let iterations = 20;
+
+    for (name, domain_file, problem_file) in test_cases {
+        let (Ok(domain_content), Ok(problem_content)) = (fs::read_to_string(domain_file), fs::read_to_string(problem_file)) else {
+            println!("   ❌ Failed to read {}", name);
+            continue;
+        };
+        let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+        let start = Instant::now();
+        for _ in 0..iterations {
+            TemporalAStarSearch::new().search(&task);
+        }
+        let string_time = start.elapsed().as_micros() as f64 / iterations as f64;
+
+        let start = Instant::now();
+        let compiled = task.compile();
+        let compile_time = start.elapsed().as_micros() as f64;
+        let start = Instant::now();
+        for _ in 0..iterations {
+            TemporalAStarSearch::new().search_compiled(&task, &compiled);
+        }
+        let compiled_time = start.elapsed().as_micros() as f64 / iterations as f64;
+
+        println!("   {} ({} iterations):", name, iterations);
+        println!("     String-based: {:.2}μs per search", string_time);
+        println!("     Compiled:     {:.2}μs per search (+{:.2}μs to compile once)", compiled_time, compile_time);
+        println!("     Speedup:      {:.2}x", string_time / compiled_time.max(1.0));
+    }
+}
+
+fn benchmark_successor_generation() {
+    let domain_file = "tests/fixtures/domains/factory_automation.pddl";
+    let problem_file = "tests/fixtures/problems/factory_production.pddl";
+    let (Ok(domain_content), Ok(problem_content)) = (fs::read_to_string(domain_file), fs::read_to_string(problem_file)) else {
+        println!("   ❌ Failed to read the factory domain");
+        return;
+    };
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let state_space = StateSpace::new(task.clone());
+
+    // Breadth-first sample of reachable states, each with its parent's enabled actions
+    let initial = state_space.initial_state();
+    let mut seen = HashSet::from([initial.classical_state.clone()]);
+    let mut samples: Vec<(TemporalState, Option<EnabledActions>)> = vec![(initial, None)];
+    let mut next = 0;
+    while next < samples.len() && samples.len() < 2000 {
+        let state = samples[next].0.clone();
+        next += 1;
+        let enabled = state_space.enabled_actions(&state, None);
+        for (action_idx, _) in state_space.applicable_actions(&state, &enabled) {
+            if let Some(successor) = state_space.apply_action_to_completion(&state, action_idx) {
+                if seen.insert(successor.classical_state.clone()) {
+                    samples.push((successor, Some(enabled.clone())));
+                }
+            }
+        }
+    }
+
+    let rounds = 5;
+    let throughput = |generate: &dyn Fn(&TemporalState, Option<&EnabledActions>) -> usize| {
+        let start = Instant::now();
+        let mut successors = 0;
+        for _ in 0..rounds {
+            for (state, parent) in &samples {
+                successors += generate(state, parent.as_ref());
+            }
+        }
+        (successors, (rounds * samples.len()) as f64 / start.elapsed().as_secs_f64())
+    };
+    let (checked, full_rate) = throughput(&|state, _| state_space.get_applicable_actions(state).len());
+    let (updated, incremental_rate) = throughput(&|state, parent| {
+        let enabled = state_space.enabled_actions(state, parent);
+        state_space.applicable_actions(state, &enabled).len()
+    });
+    assert_eq!(checked, updated, "both ways must find the same successors");
+
+    println!("   {} factory_automation states over {} ground actions:", samples.len(), task.ground_actions.len());
+    println!("     Full check:  {:.0} states/s", full_rate);
+    println!("     Incremental: {:.0} states/s", incremental_rate);
+    println!("     Speedup:     {:.2}x", incremental_rate / full_rate);
+}
+
 fn benchmark_memory_usage() {
     let domain_content = r#"
 (define (domain memory-test)
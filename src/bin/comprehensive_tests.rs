@@ -1,6 +1,5 @@
 use std::fs;
 use std::io::{self, Write};
-use serde_json;
 use serde::{Serialize, Deserialize};
 use temporal_planner::{TemporalTask, SearchResult, TemporalAStarSearch, TemporalSearchEngine};
 
@@ -31,7 +30,7 @@ enum TestStatus {
     Skipped,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct TestMetrics {
     actions_parsed: usize,
     durative_actions: usize,
@@ -186,20 +185,6 @@ fn main() {
     save_test_report(&test_report);
 }
 
-impl Default for TestMetrics {
-    fn default() -> Self {
-        Self {
-            actions_parsed: 0,
-            durative_actions: 0,
-            initial_facts: 0,
-            goal_conditions: 0,
-            parse_time_ms: None,
-            search_time_ms: None,
-            plan_length: None,
-            plan_cost: None,
-        }
-    }
-}
 
 fn run_domain_test(test_name: &str, domain_file: &str, problem_file: &str) -> Result<TestMetrics, String> {
     let domain_path = format!("tests/fixtures/domains/{}", domain_file);
@@ -215,15 +200,13 @@ fn run_domain_test(test_name: &str, domain_file: &str, problem_file: &str) -> Re
     let parse_time = parse_start.elapsed();
     
     // Analyze parsed task
-    let durative_actions = task.actions.iter()
-        .filter(|a| a.duration > 1.0)
-        .count();
+    let summary = task.summary();
     
     let metrics = TestMetrics {
-        actions_parsed: task.actions.len(),
-        durative_actions,
+        actions_parsed: summary.actions,
+        durative_actions: summary.durative_actions,
         initial_facts: task.initial_state.facts.len(),
-        goal_conditions: task.goal_conditions.len(),
+        goal_conditions: summary.goal_conditions,
         parse_time_ms: Some(parse_time.as_millis()),
         search_time_ms: None,
         plan_length: None,
@@ -258,10 +241,8 @@ fn validate_domain_expectations(test_name: &str, task: &TemporalTask) -> Result<
                 return Err(format!("Stack-slow action should have duration 3.0, got {}", stack_action.duration));
             }
         }
-        "Factory Automation" => {
-            if task.actions.len() != 4 {
-                return Err(format!("Expected 4 actions, got {}", task.actions.len()));
-            }
+        "Factory Automation" if task.actions.len() != 4 => {
+            return Err(format!("Expected 4 actions, got {}", task.actions.len()));
         }
         _ => {} // No specific validation for other domains
     }
@@ -310,9 +291,21 @@ fn test_planning_integration() -> Result<TestMetrics, String> {
                 plan_cost: Some(plan.cost),
             })
         }
-        SearchResult::Failure => {
+        SearchResult::Failure | SearchResult::Partial { .. } => {
             Err("Planning system incomplete - search returned failure".to_string())
         }
+        SearchResult::Unsolvable(reason) => {
+            Err(format!("Task reported unsolvable: {}", reason))
+        }
+        SearchResult::ResourceLimit(reason) => {
+            Err(format!("Search hit its limits: {}", reason))
+        }
+        SearchResult::Cancelled => {
+            Err("Search was cancelled".to_string())
+        }
+        SearchResult::InvalidTask(error) => {
+            Err(format!("Task could not be read: {}", error))
+        }
     }
 }
 
@@ -164,7 +164,7 @@ fn test_factory_automation_domain() -> Result<TestResult, String> {
 
     let action_names: Vec<&str> = task.actions.iter().map(|a| a.name.as_str()).collect();
     let durative_actions: Vec<&str> = task.actions.iter()
-        .filter(|a| a.duration > 1.0)
+        .filter(|a| a.is_durative)
         .map(|a| a.name.as_str())
         .collect();
 
@@ -283,7 +283,7 @@ fn test_planning_integration() -> Result<TestResult, String> {
                 ],
             })
         }
-        SearchResult::Failure => {
+        SearchResult::Failure | SearchResult::Partial { .. } => {
             // This is expected if planning is not fully implemented
             Ok(TestResult {
                 summary: "Planning system instantiated correctly (no solution found)".to_string(),
@@ -294,5 +294,17 @@ fn test_planning_integration() -> Result<TestResult, String> {
                 ],
             })
         }
+        SearchResult::Unsolvable(reason) => {
+            Err(format!("Task reported unsolvable: {}", reason))
+        }
+        SearchResult::ResourceLimit(reason) => {
+            Err(format!("Search hit its limits: {}", reason))
+        }
+        SearchResult::Cancelled => {
+            Err("Search was cancelled".to_string())
+        }
+        SearchResult::InvalidTask(error) => {
+            Err(format!("Task could not be read: {}", error))
+        }
     }
 }
@@ -0,0 +1,201 @@
+// f:\common\Source_Code\TemporalFastDownward\rust\src\temporal_planner\compiled_task.rs
+use super::temporal_task::{Condition, ConditionalEffect, Effect, FactSet, NumericCondition, State, TemporalTask};
+use std::collections::HashMap;
+
+/// A ground condition or effect resolved to its index in `State::facts`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompiledLiteral {
+    /// `None` for atoms outside the task's fact table, which never hold
+    pub fact: Option<u32>,
+    /// A negated condition, or a delete effect
+    pub negative: bool,
+}
+
+impl CompiledLiteral {
+    pub fn holds(&self, facts: &FactSet) -> bool {
+        self.fact.is_some_and(|fact| facts[fact as usize]) != self.negative
+    }
+}
+
+/// A ground atom as interned predicate and argument ids
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompiledAtom {
+    pub predicate: u32,
+    pub args: Box<[u32]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompiledConditionalEffect {
+    pub conditions: Box<[CompiledLiteral]>,
+    pub effects: Box<[CompiledLiteral]>,
+}
+
+/// Boolean conditions and effects of one ground action. Numeric conditions, numeric effects,
+/// and the duration stay on the matching `TemporalAction`.
+#[derive(Debug, Clone)]
+pub struct CompiledAction {
+    pub conditions_start: Box<[CompiledLiteral]>,
+    pub conditions_over_all: Box<[CompiledLiteral]>,
+    pub conditions_end: Box<[CompiledLiteral]>,
+    pub effects_start: Box<[CompiledLiteral]>,
+    pub effects_end: Box<[CompiledLiteral]>,
+    pub conditional_effects_start: Box<[CompiledConditionalEffect]>,
+    pub conditional_effects_end: Box<[CompiledConditionalEffect]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompiledAxiom {
+    /// Fact index of the derived atom
+    pub head: u32,
+    pub conditions: Box<[CompiledLiteral]>,
+    pub numeric_conditions: Box<[NumericCondition]>,
+}
+
+/// Immutable, index-based form of a grounded `TemporalTask`, built by `TemporalTask::compile`.
+///
+/// Every atom is resolved to its fact index once, so checking a condition or applying an effect
+/// no longer formats and hashes strings. `actions` runs parallel to `TemporalTask::ground_actions`.
+#[derive(Debug, Clone)]
+pub struct CompiledTask {
+    /// Interned predicate names
+    pub predicates: Box<[String]>,
+    /// Interned objects and constants appearing as atom arguments
+    pub symbols: Box<[String]>,
+    /// The atom behind each index of `State::facts`
+    pub facts: Box<[CompiledAtom]>,
+    pub actions: Box<[CompiledAction]>,
+    pub goal_conditions: Box<[CompiledLiteral]>,
+    pub always_constraints: Box<[CompiledLiteral]>,
+    pub axioms: Box<[CompiledAxiom]>,
+}
+
+impl CompiledTask {
+    pub fn new(task: &TemporalTask) -> Self {
+        let mut predicates = Interner::default();
+        let mut symbols = Interner::default();
+        let facts = task.fact_names.iter()
+            .map(|name| {
+                let mut parts = name.split_whitespace();
+                CompiledAtom {
+                    predicate: predicates.intern(parts.next().unwrap_or_default()),
+                    args: parts.map(|arg| symbols.intern(arg)).collect(),
+                }
+            })
+            .collect();
+
+        let conditions = |conditions: &[Condition]| -> Box<[CompiledLiteral]> {
+            conditions.iter().map(|condition| resolve_condition(task, condition)).collect()
+        };
+        let effects = |effects: &[Effect]| -> Box<[CompiledLiteral]> {
+            effects.iter().map(|effect| resolve_effect(task, effect)).collect()
+        };
+        let actions = task.ground_actions.iter()
+            .map(|action| {
+                let conditional = |list: &[ConditionalEffect]| -> Box<[CompiledConditionalEffect]> {
+                    list.iter()
+                        .map(|conditional| CompiledConditionalEffect {
+                            conditions: conditions(&conditional.conditions),
+                            effects: effects(&conditional.effects),
+                        })
+                        .collect()
+                };
+                CompiledAction {
+                    conditions_start: conditions(&action.conditions_start),
+                    conditions_over_all: conditions(&action.conditions_over_all),
+                    conditions_end: conditions(&action.conditions_end),
+                    effects_start: effects(&action.effects_start),
+                    effects_end: effects(&action.effects_end),
+                    conditional_effects_start: conditional(&action.conditional_effects_start),
+                    conditional_effects_end: conditional(&action.conditional_effects_end),
+                }
+            })
+            .collect();
+        let axioms = task.axioms.iter()
+            .map(|axiom| CompiledAxiom {
+                head: task.fact_ids[&axiom.key()] as u32,
+                conditions: conditions(&axiom.conditions),
+                numeric_conditions: axiom.numeric_conditions.clone().into_boxed_slice(),
+            })
+            .collect();
+
+        Self {
+            predicates: predicates.names.into_boxed_slice(),
+            symbols: symbols.names.into_boxed_slice(),
+            facts,
+            actions,
+            goal_conditions: conditions(&task.goal_conditions),
+            always_constraints: conditions(&task.always_constraints),
+            axioms,
+        }
+    }
+
+    /// The ground atom behind fact `fact`, written like `TemporalTask::fact_names`
+    pub fn atom_name(&self, fact: u32) -> String {
+        let atom = &self.facts[fact as usize];
+        std::iter::once(&self.predicates[atom.predicate as usize])
+            .chain(atom.args.iter().map(|&arg| &self.symbols[arg as usize]))
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Same fixpoint as `TemporalTask::apply_axioms`, over fact indices
+    pub fn apply_axioms(&self, state: &mut State) {
+        for axiom in self.axioms.iter() {
+            state.facts.set(axiom.head as usize, false);
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for axiom in self.axioms.iter() {
+                let head = axiom.head as usize;
+                if state.facts[head] {
+                    continue;
+                }
+                let holds = axiom.conditions.iter().all(|literal| literal.holds(&state.facts))
+                    && axiom.numeric_conditions.iter().all(|condition| condition.is_satisfied(&state.numeric_values));
+                if holds {
+                    state.facts.set(head, true);
+                    changed = true;
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn resolve_condition(task: &TemporalTask, condition: &Condition) -> CompiledLiteral {
+    // Equality has no fact; a literal without one holds exactly when it is negative
+    if let Some(equal) = condition.equality() {
+        return CompiledLiteral { fact: None, negative: equal != condition.is_negative };
+    }
+    CompiledLiteral {
+        fact: task.fact_id(&condition.predicate, &condition.args).map(|id| id as u32),
+        negative: condition.is_negative,
+    }
+}
+
+pub(crate) fn resolve_effect(task: &TemporalTask, effect: &Effect) -> CompiledLiteral {
+    CompiledLiteral {
+        fact: task.fact_id(&effect.predicate, &effect.args).map(|id| id as u32),
+        negative: effect.is_delete,
+    }
+}
+
+#[derive(Default)]
+struct Interner {
+    names: Vec<String>,
+    ids: HashMap<String, u32>,
+}
+
+impl Interner {
+    fn intern(&mut self, name: &str) -> u32 {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len() as u32;
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        id
+    }
+}
@@ -2,14 +2,79 @@
 //! Foreign Function Interface (FFI) for external application integration
 
 use std::ffi::{CStr, CString};
-use std::os::raw::{c_char, c_int};
+use std::os::raw::{c_char, c_double, c_int};
 use std::ptr;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
 
-use crate::{TemporalPlanner, SearchResult};
+use crate::heuristics::{GoalCountHeuristic, TemporalAddHeuristic, TemporalAdmissibleHeuristic, TemporalFFHeuristic, TemporalHeuristic};
+use crate::{EngineKind, PddlParseError, Plan, TemporalPlanner, TemporalTask, SearchResult};
 
-/// Opaque handle for the temporal planner
+/// Opaque handle for the temporal planner. Every call locks the handle, so hosts may share
+/// one across threads; concurrent solves run one after another.
 pub struct PlannerHandle {
+    state: Mutex<HandleState>,
+}
+
+impl PlannerHandle {
+    fn lock(&self) -> MutexGuard<'_, HandleState> {
+        // A panic mid-solve leaves nothing half-updated that later calls rely on
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+struct HandleState {
     planner: TemporalPlanner,
+    /// Steps of the plan found by the last successful solve
+    plan: Vec<PlanStep>,
+    /// Why the last solve failed, for `temporal_planner_last_error`
+    last_error: Option<String>,
+}
+
+struct PlanStep {
+    name: String,
+    start: f64,
+    duration: f64,
+}
+
+impl HandleState {
+    /// Solve `task`, caching the plan's steps for `temporal_planner_get_plan_action`
+    fn solve(&mut self, task: &TemporalTask) -> PlannerResult {
+        self.plan.clear();
+        self.last_error = None;
+        let (result, error) = match self.planner.solve(task) {
+            SearchResult::Solution(plan) => {
+                self.plan = plan_steps(task, &plan);
+                return PlannerResult::SolutionFound;
+            }
+            SearchResult::Failure | SearchResult::Partial { .. } => {
+                (PlannerResult::NoSolutionFound, "search exhausted without finding a plan".to_string())
+            }
+            SearchResult::Unsolvable(reason) => (PlannerResult::NoSolutionFound, format!("task is unsolvable: {}", reason)),
+            SearchResult::ResourceLimit(reason) => (PlannerResult::ResourceLimit, format!("search stopped: {}", reason)),
+            SearchResult::Cancelled => (PlannerResult::ResourceLimit, "search was cancelled".to_string()),
+            SearchResult::InvalidTask(error) => (PlannerResult::ParseError, error),
+        };
+        self.last_error = Some(error);
+        result
+    }
+
+    fn fail(&mut self, result: PlannerResult, error: String) -> PlannerResult {
+        self.plan.clear();
+        self.last_error = Some(error);
+        result
+    }
+}
+
+fn plan_steps(task: &TemporalTask, plan: &Plan) -> Vec<PlanStep> {
+    plan.actions.iter()
+        .zip(plan.start_times.iter().zip(&plan.end_times))
+        .map(|(&action, (&start, &end))| PlanStep {
+            name: task.ground_actions[action].name.clone(),
+            start,
+            duration: end - start,
+        })
+        .collect()
 }
 
 /// C-compatible result codes
@@ -21,6 +86,25 @@ pub enum PlannerResult {
     ParseError = 3,
     FileError = 4,
     InvalidHandle = 5,
+    ResourceLimit = 6,
+    InvalidIndex = 7,
+    InvalidConfig = 8,
+}
+
+/// Search algorithm codes for `temporal_planner_set_config`
+#[repr(C)]
+pub enum PlannerAlgorithm {
+    AStar = 0,
+    Greedy = 1,
+}
+
+/// Heuristic codes for `temporal_planner_set_config`
+#[repr(C)]
+pub enum PlannerHeuristic {
+    FF = 0,
+    HMax = 1,
+    HAdd = 2,
+    GoalCount = 3,
 }
 
 /// Create a new temporal planner instance
@@ -28,17 +112,20 @@ pub enum PlannerResult {
 #[no_mangle]
 pub extern "C" fn temporal_planner_create() -> *mut PlannerHandle {
     let planner = TemporalPlanner::new();
-    Box::into_raw(Box::new(PlannerHandle { planner }))
+    let state = HandleState { planner, plan: Vec::new(), last_error: None };
+    Box::into_raw(Box::new(PlannerHandle { state: Mutex::new(state) }))
 }
 
 /// Destroy a temporal planner instance
 /// Parameters: handle - Planner handle to destroy
+///
+/// # Safety
+/// `handle` must be null or a pointer returned by `temporal_planner_create`
+/// that has not already been destroyed, and no other thread may be using it.
 #[no_mangle]
-pub extern "C" fn temporal_planner_destroy(handle: *mut PlannerHandle) {
+pub unsafe extern "C" fn temporal_planner_destroy(handle: *mut PlannerHandle) {
     if !handle.is_null() {
-        unsafe {
-            drop(Box::from_raw(handle));
-        }
+        drop(Box::from_raw(handle));
     }
 }
 
@@ -49,8 +136,13 @@ pub extern "C" fn temporal_planner_destroy(handle: *mut PlannerHandle) {
 ///   problem_path - Path to PDDL problem file
 ///   plan_length - Output parameter for plan length (can be null)
 /// Returns: Result code indicating success/failure
+///
+/// # Safety
+/// `handle` must be a live pointer from `temporal_planner_create`, the path
+/// arguments must be valid NUL-terminated strings, and `plan_length` must be
+/// null or valid for writes.
 #[no_mangle]
-pub extern "C" fn temporal_planner_solve_files(
+pub unsafe extern "C" fn temporal_planner_solve_files(
     handle: *mut PlannerHandle,
     domain_path: *const c_char,
     problem_path: *const c_char,
@@ -61,7 +153,7 @@ pub extern "C" fn temporal_planner_solve_files(
     }
 
     unsafe {
-        let planner_handle = &mut *handle;
+        let mut state = (*handle).lock();
         
         let domain_path_str = match CStr::from_ptr(domain_path).to_str() {
             Ok(s) => s,
@@ -73,15 +165,16 @@ pub extern "C" fn temporal_planner_solve_files(
             Err(_) => return PlannerResult::InvalidHandle,
         };
 
-        match planner_handle.planner.solve_from_files(domain_path_str, problem_path_str) {
-            Ok(SearchResult::Solution(plan)) => {
-                if !plan_length.is_null() {
-                    *plan_length = plan.actions.len() as c_int;
+        match state.planner.load_pddl_files(domain_path_str, problem_path_str) {
+            Ok(task) => {
+                let result = state.solve(&task);
+                if matches!(result, PlannerResult::SolutionFound) && !plan_length.is_null() {
+                    *plan_length = state.plan.len() as c_int;
                 }
-                PlannerResult::SolutionFound
+                result
             }
-            Ok(SearchResult::Failure) => PlannerResult::NoSolutionFound,
-            Err(_) => PlannerResult::FileError,
+            Err(error) if error.is::<PddlParseError>() => state.fail(PlannerResult::ParseError, error.to_string()),
+            Err(error) => state.fail(PlannerResult::FileError, error.to_string()),
         }
     }
 }
@@ -93,8 +186,13 @@ pub extern "C" fn temporal_planner_solve_files(
 ///   problem_content - PDDL problem content as C string
 ///   plan_length - Output parameter for plan length (can be null)
 /// Returns: Result code indicating success/failure
+///
+/// # Safety
+/// `handle` must be a live pointer from `temporal_planner_create`, the content
+/// arguments must be valid NUL-terminated strings, and `plan_length` must be
+/// null or valid for writes.
 #[no_mangle]
-pub extern "C" fn temporal_planner_solve_content(
+pub unsafe extern "C" fn temporal_planner_solve_content(
     handle: *mut PlannerHandle,
     domain_content: *const c_char,
     problem_content: *const c_char,
@@ -105,7 +203,7 @@ pub extern "C" fn temporal_planner_solve_content(
     }
 
     unsafe {
-        let planner_handle = &mut *handle;
+        let mut state = (*handle).lock();
         
         let domain_str = match CStr::from_ptr(domain_content).to_str() {
             Ok(s) => s,
@@ -117,14 +215,125 @@ pub extern "C" fn temporal_planner_solve_content(
             Err(_) => return PlannerResult::InvalidHandle,
         };
 
-        match planner_handle.planner.solve_from_content(domain_str, problem_str) {
-            SearchResult::Solution(plan) => {
-                if !plan_length.is_null() {
-                    *plan_length = plan.actions.len() as c_int;
-                }
-                PlannerResult::SolutionFound
-            }
-            SearchResult::Failure => PlannerResult::NoSolutionFound,
+        let task = match TemporalTask::try_from_pddl(domain_str, problem_str) {
+            Ok(task) => task,
+            Err(error) => return state.fail(PlannerResult::ParseError, error.to_string()),
+        };
+        let result = state.solve(&task);
+        if matches!(result, PlannerResult::SolutionFound) && !plan_length.is_null() {
+            *plan_length = state.plan.len() as c_int;
+        }
+        result
+    }
+}
+
+/// Configure the search used by later solves on this handle
+/// Parameters:
+///   handle - Planner handle
+///   algorithm_code - A `PlannerAlgorithm` value
+///   heuristic_code - A `PlannerHeuristic` value
+///   max_time_ms - Time limit per solve in milliseconds, or 0 for none
+/// Returns: Success, or InvalidConfig for an unknown code (leaving the planner unchanged)
+///
+/// # Safety
+/// `handle` must be a live pointer from `temporal_planner_create`.
+#[no_mangle]
+pub unsafe extern "C" fn temporal_planner_set_config(
+    handle: *mut PlannerHandle,
+    algorithm_code: c_int,
+    heuristic_code: c_int,
+    max_time_ms: u64,
+) -> PlannerResult {
+    if handle.is_null() {
+        return PlannerResult::InvalidHandle;
+    }
+
+    let engine = match algorithm_code {
+        code if code == PlannerAlgorithm::AStar as c_int => EngineKind::AStar,
+        code if code == PlannerAlgorithm::Greedy as c_int => EngineKind::Greedy,
+        _ => return PlannerResult::InvalidConfig,
+    };
+    let heuristic: Box<dyn TemporalHeuristic> = match heuristic_code {
+        code if code == PlannerHeuristic::FF as c_int => Box::new(TemporalFFHeuristic::new()),
+        code if code == PlannerHeuristic::HMax as c_int => Box::new(TemporalAdmissibleHeuristic::new()),
+        code if code == PlannerHeuristic::HAdd as c_int => Box::new(TemporalAddHeuristic::new()),
+        code if code == PlannerHeuristic::GoalCount as c_int => Box::new(GoalCountHeuristic::new()),
+        _ => return PlannerResult::InvalidConfig,
+    };
+
+    let mut builder = TemporalPlanner::builder().engine(engine).heuristic(heuristic);
+    if max_time_ms > 0 {
+        builder = builder.max_time(Duration::from_millis(max_time_ms));
+    }
+    unsafe {
+        (*handle).lock().planner = builder.build();
+    }
+    PlannerResult::Success
+}
+
+/// Get one step of the plan found by the last successful solve
+/// Parameters:
+///   handle - Planner handle
+///   index - Step index, below the `plan_length` reported by the solve
+///   out_name - Output parameter for the ground action name (caller must free, can be null)
+///   out_start - Output parameter for the step's start time (can be null)
+///   out_duration - Output parameter for the step's duration (can be null)
+/// Returns: Success, or InvalidIndex if the last solve found no plan with that many steps
+///
+/// # Safety
+/// `handle` must be a live pointer from `temporal_planner_create`, and each
+/// output parameter must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn temporal_planner_get_plan_action(
+    handle: *const PlannerHandle,
+    index: c_int,
+    out_name: *mut *mut c_char,
+    out_start: *mut c_double,
+    out_duration: *mut c_double,
+) -> PlannerResult {
+    if handle.is_null() {
+        return PlannerResult::InvalidHandle;
+    }
+
+    unsafe {
+        let state = (*handle).lock();
+        let Some(step) = usize::try_from(index).ok().and_then(|index| state.plan.get(index)) else {
+            return PlannerResult::InvalidIndex;
+        };
+
+        if !out_name.is_null() {
+            *out_name = match CString::new(step.name.as_str()) {
+                Ok(c_string) => c_string.into_raw(),
+                Err(_) => ptr::null_mut(),
+            };
+        }
+        if !out_start.is_null() {
+            *out_start = step.start;
+        }
+        if !out_duration.is_null() {
+            *out_duration = step.duration;
+        }
+        PlannerResult::Success
+    }
+}
+
+/// Get the reason the last solve on this handle failed
+/// Parameters: handle - Planner handle
+/// Returns: C string with the error message (caller must free), or null if the
+/// last solve succeeded or none has run
+///
+/// # Safety
+/// `handle` must be a live pointer from `temporal_planner_create`.
+#[no_mangle]
+pub unsafe extern "C" fn temporal_planner_last_error(handle: *const PlannerHandle) -> *mut c_char {
+    if handle.is_null() {
+        return ptr::null_mut();
+    }
+
+    unsafe {
+        match (*handle).lock().last_error.as_deref().map(CString::new) {
+            Some(Ok(c_string)) => c_string.into_raw(),
+            _ => ptr::null_mut(),
         }
     }
 }
@@ -142,19 +351,20 @@ pub extern "C" fn temporal_planner_get_version() -> *mut c_char {
 
 /// Free a C string returned by the planner
 /// Parameters: str_ptr - Pointer to C string to free
+///
+/// # Safety
+/// `str_ptr` must be null or a string returned by this library that has not
+/// already been freed.
 #[no_mangle]
-pub extern "C" fn temporal_planner_free_string(str_ptr: *mut c_char) {
+pub unsafe extern "C" fn temporal_planner_free_string(str_ptr: *mut c_char) {
     if !str_ptr.is_null() {
-        unsafe {
-            drop(CString::from_raw(str_ptr));
-        }
+        drop(CString::from_raw(str_ptr));
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::ffi::CString;
 
     #[test]
     fn test_ffi_lifecycle() {
@@ -162,7 +372,119 @@ mod tests {
         let handle = temporal_planner_create();
         assert!(!handle.is_null());
         
-        temporal_planner_destroy(handle);
+        unsafe { temporal_planner_destroy(handle) };
+    }
+
+    #[test]
+    fn test_ffi_plan_steps() {
+        let handle = temporal_planner_create();
+        let domain = CString::new("tests/fixtures/domains/simple_robot.pddl").unwrap();
+        let problem = CString::new("tests/fixtures/problems/simple_delivery.pddl").unwrap();
+        let mut plan_length = 0;
+
+        unsafe {
+            let result = temporal_planner_solve_files(handle, domain.as_ptr(), problem.as_ptr(), &mut plan_length);
+            assert!(matches!(result, PlannerResult::SolutionFound));
+            assert!(plan_length > 0);
+
+            let mut name = ptr::null_mut();
+            let mut start = -1.0;
+            let mut duration = -1.0;
+            let result = temporal_planner_get_plan_action(handle, 0, &mut name, &mut start, &mut duration);
+            assert!(matches!(result, PlannerResult::Success));
+            assert!(!name.is_null());
+            assert!(!CStr::from_ptr(name).to_str().unwrap().is_empty());
+            assert_eq!(start, 0.0);
+            assert!(duration > 0.0);
+            temporal_planner_free_string(name);
+
+            let result = temporal_planner_get_plan_action(handle, plan_length, ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
+            assert!(matches!(result, PlannerResult::InvalidIndex));
+
+            temporal_planner_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_ffi_reports_parse_errors() {
+        let handle = temporal_planner_create();
+        let domain = CString::new("(define (domain broken) (:predicates (at ?x))").unwrap();
+        let problem = CString::new("(define (problem p) (:domain broken) (:init) (:goal (and)))").unwrap();
+
+        unsafe {
+            assert!(temporal_planner_last_error(handle).is_null());
+
+            let result = temporal_planner_solve_content(handle, domain.as_ptr(), problem.as_ptr(), ptr::null_mut());
+            assert!(matches!(result, PlannerResult::ParseError));
+
+            let error = temporal_planner_last_error(handle);
+            assert!(!error.is_null());
+            let message = CStr::from_ptr(error).to_str().unwrap();
+            assert!(message.contains("domain line 1"), "unexpected message: {}", message);
+            temporal_planner_free_string(error);
+
+            temporal_planner_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_ffi_set_config() {
+        let handle = temporal_planner_create();
+        let domain = CString::new("tests/fixtures/domains/simple_robot.pddl").unwrap();
+        let problem = CString::new("tests/fixtures/problems/simple_delivery.pddl").unwrap();
+        let mut plan_length = 0;
+
+        unsafe {
+            let result = temporal_planner_set_config(handle, PlannerAlgorithm::Greedy as c_int, PlannerHeuristic::GoalCount as c_int, 10_000);
+            assert!(matches!(result, PlannerResult::Success));
+            assert_eq!((*handle).lock().planner.get_info().search_algorithm, "Temporal greedy best-first (goal count)");
+
+            let result = temporal_planner_solve_files(handle, domain.as_ptr(), problem.as_ptr(), &mut plan_length);
+            assert!(matches!(result, PlannerResult::SolutionFound));
+            assert!(plan_length > 0);
+
+            let result = temporal_planner_set_config(handle, PlannerAlgorithm::AStar as c_int, 42, 0);
+            assert!(matches!(result, PlannerResult::InvalidConfig));
+
+            temporal_planner_destroy(handle);
+        }
+    }
+
+    #[test]
+    fn test_ffi_handle_is_shared_across_threads() {
+        let handle = temporal_planner_create();
+        // Raw pointers are not `Send`; a C host would share the handle the same way
+        let address = handle as usize;
+
+        let workers: Vec<_> = (0..2)
+            .map(|_| {
+                std::thread::spawn(move || {
+                    let handle = address as *mut PlannerHandle;
+                    let domain = CString::new("tests/fixtures/domains/simple_robot.pddl").unwrap();
+                    let problem = CString::new("tests/fixtures/problems/simple_delivery.pddl").unwrap();
+                    (0..3)
+                        .map(|_| {
+                            let mut plan_length = 0;
+                            let result = unsafe {
+                                temporal_planner_solve_files(handle, domain.as_ptr(), problem.as_ptr(), &mut plan_length)
+                            };
+                            assert!(matches!(result, PlannerResult::SolutionFound));
+                            plan_length
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let lengths: Vec<c_int> = workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect();
+
+        assert!(lengths.iter().all(|&length| length == lengths[0]));
+        unsafe {
+            let mut name = ptr::null_mut();
+            let result = temporal_planner_get_plan_action(handle, lengths[0] - 1, &mut name, ptr::null_mut(), ptr::null_mut());
+            assert!(matches!(result, PlannerResult::Success));
+            temporal_planner_free_string(name);
+            temporal_planner_destroy(handle);
+        }
     }
 
     #[test]
@@ -173,8 +495,7 @@ mod tests {
         unsafe {
             let version_str = CStr::from_ptr(version_ptr).to_str().unwrap();
             assert!(!version_str.is_empty());
+            temporal_planner_free_string(version_ptr);
         }
-        
-        temporal_planner_free_string(version_ptr);
     }
 }
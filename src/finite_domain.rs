@@ -0,0 +1,147 @@
+// f:\common\Source_Code\TemporalFastDownward\rust\src\temporal_planner\finite_domain.rs
+use super::temporal_task::{Effect, FactSet, State, TemporalTask};
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// A finite-domain variable over some facts of which at most one holds: value `i` means
+/// `facts[i]` holds, and the extra value `facts.len()`, present when `has_none`, means none does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiniteDomainVariable {
+    pub facts: Box<[usize]>,
+    pub has_none: bool,
+}
+
+impl FiniteDomainVariable {
+    pub fn domain_size(&self) -> usize {
+        self.facts.len() + usize::from(self.has_none)
+    }
+
+    /// The value meaning that none of `facts` holds, if the variable has one
+    pub fn none_value(&self) -> Option<u32> {
+        self.has_none.then_some(self.facts.len() as u32)
+    }
+}
+
+/// A `State` with one value per variable of a `FiniteDomainEncoding` instead of one boolean per fact
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiValuedState {
+    pub values: Box<[u32]>,
+    pub numeric_values: HashMap<String, f64>,
+}
+
+impl Eq for MultiValuedState {}
+
+impl Hash for MultiValuedState {
+    /// Only the variable values; states differing only in their fluents share a bucket
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.values.hash(state);
+    }
+}
+
+/// SAS+ view of a grounded `TemporalTask`, built by `TemporalTask::finite_domain_encoding`.
+///
+/// Mutex groups are taken largest first, each becoming one variable over its facts not already
+/// covered by an earlier group; every remaining fact becomes a binary variable. A variable needs
+/// no "none" value when its group is intact and exactly one of its facts always holds.
+#[derive(Debug, Clone)]
+pub struct FiniteDomainEncoding {
+    pub variables: Box<[FiniteDomainVariable]>,
+    /// Variable and value standing for each index of `State::facts`
+    pub fact_values: Box<[(u32, u32)]>,
+}
+
+impl FiniteDomainEncoding {
+    pub fn new(task: &TemporalTask) -> Self {
+        let mut groups: Vec<&[usize]> = task.mutex_groups.iter().map(|group| group.facts.as_slice()).collect();
+        groups.sort_by_key(|facts| std::cmp::Reverse(facts.len()));
+
+        let mut covered = vec![false; task.fact_names.len()];
+        let mut variables = Vec::new();
+        for group in groups {
+            let facts: Box<[usize]> = group.iter().copied().filter(|&fact| !covered[fact]).collect();
+            if facts.len() < 2 {
+                continue;
+            }
+            for &fact in facts.iter() {
+                covered[fact] = true;
+            }
+            let intact = facts.len() == group.len();
+            variables.push(FiniteDomainVariable {
+                has_none: !(intact && exactly_one_holds(task, &facts)),
+                facts,
+            });
+        }
+        for fact in (0..covered.len()).filter(|&fact| !covered[fact]) {
+            variables.push(FiniteDomainVariable { facts: Box::new([fact]), has_none: true });
+        }
+
+        let mut fact_values = vec![(0, 0); task.fact_names.len()].into_boxed_slice();
+        for (variable, domain) in variables.iter().enumerate() {
+            for (value, &fact) in domain.facts.iter().enumerate() {
+                fact_values[fact] = (variable as u32, value as u32);
+            }
+        }
+        Self {
+            variables: variables.into_boxed_slice(),
+            fact_values,
+        }
+    }
+
+    /// `None` if `state` breaks a mutex group, with two facts of one variable holding or none
+    /// holding for a variable without a "none" value
+    pub fn encode(&self, state: &State) -> Option<MultiValuedState> {
+        let values = self.variables.iter()
+            .map(|variable| {
+                let mut holding = variable.facts.iter().enumerate().filter(|&(_, &fact)| state.facts[fact]);
+                match (holding.next(), holding.next()) {
+                    (Some((value, _)), None) => Some(value as u32),
+                    (None, _) => variable.none_value(),
+                    (Some(_), Some(_)) => None,
+                }
+            })
+            .collect::<Option<Box<[u32]>>>()?;
+        Some(MultiValuedState {
+            values,
+            numeric_values: state.numeric_values.clone(),
+        })
+    }
+
+    pub fn decode(&self, state: &MultiValuedState) -> State {
+        let mut facts = FactSet::new(self.fact_values.len());
+        for (variable, &value) in self.variables.iter().zip(state.values.iter()) {
+            if let Some(&fact) = variable.facts.get(value as usize) {
+                facts.set(fact, true);
+            }
+        }
+        State {
+            facts,
+            numeric_values: state.numeric_values.clone(),
+        }
+    }
+}
+
+/// Exactly one of `facts` holds initially, no timed literal touches them, and every action
+/// deleting one of them adds another at the same time point, so some fact of the group holds
+/// in every reachable state
+fn exactly_one_holds(task: &TemporalTask, facts: &[usize]) -> bool {
+    if facts.iter().filter(|&&fact| task.initial_state.facts[fact]).count() != 1 {
+        return false;
+    }
+    let members: HashSet<String> = facts.iter().map(|&fact| task.fact_names[fact].clone()).collect();
+    let touches = |effects: &[Effect], delete: bool| {
+        effects.iter().any(|effect| effect.is_delete == delete && members.contains(&effect.key()))
+    };
+    if task.timed_literals.iter().any(|literal| members.contains(&literal.effect.key())) {
+        return false;
+    }
+
+    task.ground_actions.iter().all(|action| {
+        let conditionally_deletes = action.conditional_effects_start.iter()
+            .chain(&action.conditional_effects_end)
+            .any(|conditional| touches(&conditional.effects, true));
+        !conditionally_deletes
+            && [&action.effects_start, &action.effects_end]
+                .into_iter()
+                .all(|effects| !touches(effects, true) || touches(effects, false))
+    })
+}
@@ -1,48 +1,400 @@
 // f:\common\Source_Code\TemporalFastDownward\rust\src\temporal_planner\heuristics.rs
+use super::compiled_task::{resolve_condition, CompiledLiteral};
+use super::search::CostModel;
 use super::state_space::TemporalState;
-use super::temporal_task::TemporalTask;
+use super::temporal_task::{Condition, Effect, TemporalTask};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
 pub trait TemporalHeuristic: Send + Sync {
     fn compute(&self, state: &TemporalState, task: &TemporalTask) -> f64;
+
+    /// Precompute whatever depends on `task` alone; the search engines call this once before
+    /// each search. Until it is called again, `compute` may assume it is given this same task,
+    /// unchanged.
+    fn prepare(&self, _task: &TemporalTask) {}
+
+    /// Whether `compute` never overestimates the remaining cost under `cost_model`; A* only
+    /// reports its plans as optimal with heuristics that say so
+    fn is_admissible(&self, _cost_model: CostModel) -> bool {
+        false
+    }
+
+    /// Short name shown in `PlannerInfo::search_algorithm` when the heuristic is not the default
+    fn name(&self) -> String {
+        "custom heuristic".to_string()
+    }
 }
 
+/// FF heuristic: the total duration of a relaxed plan extracted from a layered planning graph
+/// that ignores delete effects. Durative actions are collapsed into one relaxed action with
+/// both their start and end effects. Informative but not admissible.
 pub struct TemporalFFHeuristic {
-    // Temporal relaxed planning graph
+    relaxation: Prepared<Relaxation>,
 }
 
 impl TemporalFFHeuristic {
     pub fn new() -> Self {
-        Self {}
+        Self { relaxation: Prepared::default() }
+    }
+
+    /// `f64::INFINITY` when the goal never appears in the graph
+    fn build_relaxed_planning_graph(&self, state: &TemporalState, task: &TemporalTask) -> f64 {
+        let relaxation = self.relaxation.get(|| Relaxation::new(task));
+        let Some(goals) = relaxation.goals.clone() else {
+            return f64::INFINITY;
+        };
+        let actions = &relaxation.actions;
+
+        // Expand fact and action layers until every goal fact has appeared
+        let mut fact_level: Vec<Option<usize>> = state.classical_state.facts.iter()
+            .map(|holds| holds.then_some(0))
+            .collect();
+        for scheduled in state.scheduled_effects.iter().filter(|scheduled| !scheduled.literal.negative) {
+            if let Some(fact) = scheduled.literal.fact {
+                fact_level[fact as usize] = Some(0);
+            }
+        }
+        let mut action_level: Vec<Option<usize>> = vec![None; actions.len()];
+        let mut level = 0;
+        while !goals.iter().all(|&fact| fact_level[fact].is_some()) {
+            let mut reached: Vec<usize> = Vec::new();
+            for (index, action) in actions.iter().enumerate() {
+                if action_level[index].is_none() && action.preconditions.iter().all(|&fact| fact_level[fact].is_some()) {
+                    action_level[index] = Some(level);
+                    reached.extend(action.adds.iter().filter(|&&fact| fact_level[fact].is_none()));
+                }
+            }
+            if reached.is_empty() {
+                return f64::INFINITY;
+            }
+            level += 1;
+            for fact in reached {
+                fact_level[fact] = Some(level);
+            }
+        }
+
+        // Extract a relaxed plan backwards, supporting each open fact with the achiever from the
+        // layer just below it whose preconditions appear earliest
+        let mut selected: Vec<usize> = Vec::new();
+        let mut supported = vec![false; fact_level.len()];
+        let mut agenda = goals;
+        while let Some(fact) = agenda.pop() {
+            let Some(fact_layer) = fact_level[fact].filter(|&layer| layer > 0) else {
+                continue;
+            };
+            if supported[fact] {
+                continue;
+            }
+            supported[fact] = true;
+            let difficulty = |index: usize| -> usize {
+                actions[index].preconditions.iter().filter_map(|&fact| fact_level[fact]).sum()
+            };
+            let achiever = (0..actions.len())
+                .filter(|&index| action_level[index] == Some(fact_layer - 1) && actions[index].adds.contains(&fact))
+                .min_by_key(|&index| difficulty(index));
+            if let Some(index) = achiever.filter(|index| !selected.contains(index)) {
+                selected.push(index);
+                for &added in &actions[index].adds {
+                    if fact_level[added].is_some_and(|layer| layer <= fact_layer) {
+                        supported[added] = true;
+                    }
+                }
+                agenda.extend(&actions[index].preconditions);
+            }
+        }
+
+        let pending = state.actions_finish() - state.time;
+        selected.iter().map(|&index| actions[index].duration).sum::<f64>().max(pending)
     }
+}
 
-    fn build_relaxed_planning_graph(&self, _state: &TemporalState, _task: &TemporalTask) -> f64 {
-        // Build temporal RPG similar to CRIKEY/COLIN planners
-        // Consider temporal constraints but ignore delete effects
-        // TODO: Implement temporal relaxed planning graph
-        0.0  // Return zero heuristic for now
+impl Default for TemporalFFHeuristic {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl TemporalHeuristic for TemporalFFHeuristic {
-    fn compute(&self, _state: &TemporalState, _task: &TemporalTask) -> f64 {
-        self.build_relaxed_planning_graph(_state, _task)
+    fn compute(&self, state: &TemporalState, task: &TemporalTask) -> f64 {
+        self.build_relaxed_planning_graph(state, task)
+    }
+
+    fn prepare(&self, task: &TemporalTask) {
+        self.relaxation.fill(Relaxation::new(task));
+    }
+
+    fn name(&self) -> String {
+        "FF".to_string()
     }
 }
 
+/// h^max over the delete relaxation, measured in time: each fact costs the cheapest way to
+/// reach it where an action costs its duration plus the most expensive of its preconditions.
+/// The estimate is the most expensive goal fact, a lower bound on the remaining makespan.
 pub struct TemporalAdmissibleHeuristic {
-    // Admissible temporal heuristic (e.g., h^max)
+    relaxation: Prepared<Relaxation>,
 }
 
 impl TemporalAdmissibleHeuristic {
     pub fn new() -> Self {
-        Self {}
+        Self { relaxation: Prepared::default() }
+    }
+}
+
+impl Default for TemporalAdmissibleHeuristic {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl TemporalHeuristic for TemporalAdmissibleHeuristic {
-    fn compute(&self, _state: &TemporalState, _task: &TemporalTask) -> f64 {
-        // Compute admissible estimate considering temporal constraints
-        // TODO: Implement admissible temporal heuristic
-        0.0  // Return zero heuristic for now
+    /// `f64::INFINITY` when some goal fact is unreachable even under the relaxation
+    fn compute(&self, state: &TemporalState, task: &TemporalTask) -> f64 {
+        relaxed_estimate(state, &self.relaxation.get(|| Relaxation::new(task)), f64::max)
+    }
+
+    fn prepare(&self, task: &TemporalTask) {
+        self.relaxation.fill(Relaxation::new(task));
+    }
+
+    fn is_admissible(&self, cost_model: CostModel) -> bool {
+        cost_model == CostModel::Makespan
+    }
+
+    fn name(&self) -> String {
+        "h^max".to_string()
+    }
+}
+
+/// h^add over the same delete relaxation as `TemporalAdmissibleHeuristic`, summing the costs
+/// of preconditions and goal facts instead of taking their maximum. Inadmissible, since shared
+/// subgoals are counted repeatedly, but usually a better guide for greedy search.
+pub struct TemporalAddHeuristic {
+    relaxation: Prepared<Relaxation>,
+}
+
+impl TemporalAddHeuristic {
+    pub fn new() -> Self {
+        Self { relaxation: Prepared::default() }
+    }
+}
+
+impl Default for TemporalAddHeuristic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TemporalHeuristic for TemporalAddHeuristic {
+    /// `f64::INFINITY` when some goal fact is unreachable even under the relaxation
+    fn compute(&self, state: &TemporalState, task: &TemporalTask) -> f64 {
+        relaxed_estimate(state, &self.relaxation.get(|| Relaxation::new(task)), |a, b| a + b)
+    }
+
+    fn prepare(&self, task: &TemporalTask) {
+        self.relaxation.fill(Relaxation::new(task));
+    }
+
+    fn name(&self) -> String {
+        "h^add".to_string()
+    }
+}
+
+/// Baseline heuristic: the number of goal conditions that do not hold in the classical state
+pub struct GoalCountHeuristic {
+    goals: Prepared<Vec<CompiledLiteral>>,
+}
+
+impl GoalCountHeuristic {
+    pub fn new() -> Self {
+        Self { goals: Prepared::default() }
+    }
+
+    fn goal_literals(task: &TemporalTask) -> Vec<CompiledLiteral> {
+        task.goal_conditions.iter().map(|condition| resolve_condition(task, condition)).collect()
+    }
+}
+
+impl Default for GoalCountHeuristic {
+    fn default() -> Self {
+        Self::new()
     }
-}
\ No newline at end of file
+}
+
+impl TemporalHeuristic for GoalCountHeuristic {
+    fn compute(&self, state: &TemporalState, task: &TemporalTask) -> f64 {
+        self.goals.get(|| Self::goal_literals(task)).iter()
+            .filter(|literal| !literal.holds(&state.classical_state.facts))
+            .count() as f64
+    }
+
+    fn prepare(&self, task: &TemporalTask) {
+        self.goals.fill(Self::goal_literals(task));
+    }
+
+    fn name(&self) -> String {
+        "goal count".to_string()
+    }
+}
+
+/// A ground action with its delete effects, negative and numeric conditions dropped
+#[derive(Debug, Clone)]
+struct RelaxedAction {
+    preconditions: Vec<usize>,
+    adds: Vec<usize>,
+    duration: f64,
+}
+
+/// The delete relaxation of a task, with the goal resolved to fact indices
+#[derive(Debug)]
+struct Relaxation {
+    actions: Vec<RelaxedAction>,
+    /// See `goal_facts`
+    goals: Option<Vec<usize>>,
+}
+
+impl Relaxation {
+    fn new(task: &TemporalTask) -> Self {
+        Self { actions: build_relaxed_actions(task), goals: goal_facts(task) }
+    }
+}
+
+/// What a heuristic derived from the task it was last prepared for, so that a search builds it
+/// once rather than on every evaluation
+#[derive(Debug)]
+struct Prepared<T>(RwLock<Option<Arc<T>>>);
+
+impl<T> Default for Prepared<T> {
+    fn default() -> Self {
+        Self(RwLock::new(None))
+    }
+}
+
+impl<T> Prepared<T> {
+    fn fill(&self, value: T) {
+        *self.0.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(value));
+    }
+
+    /// The prepared value, or one from `build` if none was prepared
+    fn get(&self, build: impl FnOnce() -> T) -> Arc<T> {
+        let prepared = self.0.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+        prepared.unwrap_or_else(|| Arc::new(build()))
+    }
+}
+
+/// Relaxed versions of the task's ground actions, with one extra action per conditional
+/// effect and a zero-duration one per derived-predicate rule
+fn build_relaxed_actions(task: &TemporalTask) -> Vec<RelaxedAction> {
+    let facts = |conditions: &mut dyn Iterator<Item = &Condition>| -> Vec<usize> {
+        conditions
+            .filter(|condition| !condition.is_negative)
+            .filter_map(|condition| task.fact_id(&condition.predicate, &condition.args))
+            .collect()
+    };
+    let adds = |effects: &[Effect]| -> Vec<usize> {
+        effects.iter()
+            .filter(|effect| !effect.is_delete)
+            .filter_map(|effect| task.fact_id(&effect.predicate, &effect.args))
+            .collect()
+    };
+
+    let derived: Vec<usize> = task.axioms.iter()
+        .filter_map(|axiom| task.fact_id(&axiom.predicate, &axiom.args))
+        .collect();
+
+    let mut relaxed = Vec::new();
+    for action in &task.ground_actions {
+        // Fluent-dependent durations are only known in the state where the action starts; bounded
+        // ones count their lower end, the least the search may give them
+        let duration = action.duration_spec.bounds(&HashMap::new()).map_or(0.0, |(min, _)| min.max(0.0));
+        // Over-all and end conditions must already hold at the start unless the action's own
+        // start effects provide them, directly or through an axiom; nothing else runs alongside it
+        let started = adds(&action.effects_start);
+        let mut preconditions = facts(&mut action.conditions_start.iter());
+        preconditions.extend(
+            facts(&mut action.conditions_over_all.iter().chain(&action.conditions_end))
+                .into_iter()
+                .filter(|fact| !started.contains(fact) && !derived.contains(fact)),
+        );
+        // h^add would count a repeated precondition twice
+        preconditions.sort_unstable();
+        preconditions.dedup();
+
+        for conditional in action.conditional_effects_start.iter().chain(&action.conditional_effects_end) {
+            let mut guarded = preconditions.clone();
+            guarded.extend(facts(&mut conditional.conditions.iter()));
+            guarded.sort_unstable();
+            guarded.dedup();
+            relaxed.push(RelaxedAction {
+                preconditions: guarded,
+                adds: adds(&conditional.effects),
+                duration,
+            });
+        }
+        let mut effects = started;
+        effects.extend(adds(&action.effects_end));
+        relaxed.push(RelaxedAction {
+            preconditions,
+            adds: effects,
+            duration,
+        });
+    }
+    for axiom in &task.axioms {
+        if let Some(head) = task.fact_id(&axiom.predicate, &axiom.args) {
+            relaxed.push(RelaxedAction {
+                preconditions: facts(&mut axiom.conditions.iter()),
+                adds: vec![head],
+                duration: 0.0,
+            });
+        }
+    }
+    relaxed
+}
+
+/// Fact indices of the positive goal conditions other than equality tests; `None` if one is
+/// outside the fact table and so can never hold
+fn goal_facts(task: &TemporalTask) -> Option<Vec<usize>> {
+    task.goal_conditions.iter()
+        .filter(|condition| !condition.is_negative && condition.equality().is_none())
+        .map(|condition| task.fact_id(&condition.predicate, &condition.args))
+        .collect()
+}
+
+/// Cost of reaching the goal from `state` in the delete relaxation, where `combine` folds the
+/// costs of an action's preconditions and of the goal facts: `f64::max` gives h^max, addition h^add
+fn relaxed_estimate(state: &TemporalState, relaxation: &Relaxation, combine: fn(f64, f64) -> f64) -> f64 {
+    let mut cost: Vec<f64> = state.classical_state.facts.iter()
+        .map(|holds| if holds { 0.0 } else { f64::INFINITY })
+        .collect();
+    // Pending add effects arrive once they are due
+    for scheduled in state.scheduled_effects.iter().filter(|scheduled| !scheduled.literal.negative) {
+        if let Some(fact) = scheduled.literal.fact.map(|fact| fact as usize) {
+            cost[fact] = cost[fact].min(scheduled.time - state.time);
+        }
+    }
+
+    // Bellman-Ford style fixpoint; costs only decrease, so this terminates
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for action in &relaxation.actions {
+            let ready = action.preconditions.iter().map(|&fact| cost[fact]).fold(0.0, combine);
+            if ready == f64::INFINITY {
+                continue;
+            }
+            for &fact in &action.adds {
+                if ready + action.duration < cost[fact] {
+                    cost[fact] = ready + action.duration;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    let pending = state.actions_finish() - state.time;
+    let Some(goals) = &relaxation.goals else {
+        return f64::INFINITY;
+    };
+    goals.iter().map(|&fact| cost[fact]).fold(0.0, combine).max(pending)
+}
@@ -2,15 +2,29 @@ pub mod search;
 pub mod heuristics;
 pub mod state_space;
 pub mod temporal_task;
+pub mod compiled_task;
+pub mod finite_domain;
 pub mod scheduler;
+pub mod strips;
 pub mod ffi;
+mod sexpr;
 
-pub use temporal_task::{TemporalTask, TemporalAction, Condition, Effect, State};
-pub use search::{SearchResult, TemporalAStarSearch, TemporalSearchEngine, Plan};
+pub use temporal_task::{TemporalTask, TemporalAction, Condition, Effect, FactSet, State, PddlParseError, TimedLiteral, Metric, MetricDirection, ParsedDomain, PreprocessOptions, TaskSummary};
+pub use compiled_task::CompiledTask;
+pub use finite_domain::{FiniteDomainEncoding, MultiValuedState};
+pub use search::{create_engine, greedy_rollout, CancellationToken, CostModel, EngineKind, NodeInfo, Optimality, PlannerConfig, SearchLimits, SearchResult, SearchStats, TemporalAStarSearch, TemporalGreedySearch, TemporalSearchEngine, Plan};
+pub use scheduler::PartialOrderPlan;
+pub use strips::{StripsAction, StripsError, StripsTask};
+
+use heuristics::TemporalHeuristic;
+use std::time::Duration;
 
 /// Main API for external applications to interact with the temporal planner
 pub struct TemporalPlanner {
     search_engine: Box<dyn TemporalSearchEngine>,
+    /// How `search_engine` was built, when it is a bundled engine with its default heuristic,
+    /// so `solve_batch` can build one per thread
+    engine_settings: Option<(EngineKind, PlannerConfig)>,
 }
 
 impl TemporalPlanner {
@@ -18,14 +32,35 @@ impl TemporalPlanner {
     pub fn new() -> Self {
         Self {
             search_engine: Box::new(TemporalAStarSearch::new()),
+            engine_settings: Some((EngineKind::AStar, PlannerConfig::default())),
         }
     }
 
+    /// Configure a planner step by step instead of starting from the defaults of `new`
+    pub fn builder() -> TemporalPlannerBuilder {
+        TemporalPlannerBuilder::new()
+    }
+
+    /// Use `engine` instead of the default A* search, e.g. `TemporalGreedySearch` for
+    /// satisficing planning
+    pub fn with_search_engine(mut self, engine: impl TemporalSearchEngine + 'static) -> Self {
+        self.search_engine = Box::new(engine);
+        self.engine_settings = None;
+        self
+    }
+
+    /// Use the bundled engine of the given kind, configured by `config`
+    pub fn with_engine_kind(mut self, kind: EngineKind, config: &PlannerConfig) -> Self {
+        self.search_engine = create_engine(kind, config);
+        self.engine_settings = Some((kind, config.clone()));
+        self
+    }
+
     /// Parse PDDL domain and problem files from file paths
     pub fn load_pddl_files(&self, domain_path: &str, problem_path: &str) -> Result<TemporalTask, Box<dyn std::error::Error>> {
         let domain_content = std::fs::read_to_string(domain_path)?;
         let problem_content = std::fs::read_to_string(problem_path)?;
-        Ok(TemporalTask::from_pddl(&domain_content, &problem_content))
+        Ok(TemporalTask::try_from_pddl(&domain_content, &problem_content)?)
     }
 
     /// Parse PDDL domain and problem from string content
@@ -33,11 +68,42 @@ impl TemporalPlanner {
         TemporalTask::from_pddl(domain_content, problem_content)
     }
 
+    /// Parse a domain and problem as `load_pddl_files` would and report their sizes, without
+    /// searching; for checking that files load as intended
+    pub fn dry_run(&self, domain_content: &str, problem_content: &str) -> Result<TaskSummary, PddlParseError> {
+        Ok(TemporalTask::try_from_pddl(domain_content, problem_content)?.summary())
+    }
+
     /// Solve a temporal planning task
     pub fn solve(&mut self, task: &TemporalTask) -> SearchResult {
         self.search_engine.search(task)
     }
 
+    /// Up to `limit` plans of the optimal cost, none of them a reordering of another that gives
+    /// the same schedule. With the default A* engine this needs an admissible heuristic for the
+    /// plans to be optimal; other engines return their single plan.
+    pub fn solve_all_optimal(&mut self, task: &TemporalTask, limit: usize) -> Vec<Plan> {
+        self.search_engine.search_all_optimal(task, limit)
+    }
+
+    /// Up to `k` plans in order of cost, no two with the same schedule, e.g. alternatives to
+    /// offer an operator. Only the first carries an optimality guarantee.
+    pub fn solve_top_k(&mut self, task: &TemporalTask, k: usize) -> Vec<Plan> {
+        self.search_engine.search_top_k(task, k, false)
+    }
+
+    /// `solve_top_k` where each plan differs from every other by at least one action
+    pub fn solve_top_k_diverse(&mut self, task: &TemporalTask, k: usize) -> Vec<Plan> {
+        self.search_engine.search_top_k(task, k, true)
+    }
+
+    /// Keep solving for cheaper plans, calling `improved` with each one as it is found, until it
+    /// returns `false` or the engine's search is exhausted; returns the best plan found. The
+    /// A* engine reruns with decreasing weights, other engines report a single plan.
+    pub fn solve_anytime(&mut self, task: &TemporalTask, mut improved: impl FnMut(&Plan) -> bool) -> SearchResult {
+        self.search_engine.search_anytime(task, &mut improved)
+    }
+
     /// Complete pipeline: load PDDL files and solve
     pub fn solve_from_files(&mut self, domain_path: &str, problem_path: &str) -> Result<SearchResult, Box<dyn std::error::Error>> {
         let task = self.load_pddl_files(domain_path, problem_path)?;
@@ -50,13 +116,71 @@ impl TemporalPlanner {
         self.solve(&task)
     }
 
-    /// Get planner statistics and information
+    /// Solve every problem in `problem_paths` over the domain in `domain_path`, which is read and
+    /// parsed only once. Files are parsed strictly, as by `load_pddl_files`. Results come back in
+    /// the order of `problem_paths`, each with the path it was read from; a problem that cannot be
+    /// read or parsed gets `SearchResult::InvalidTask` with the error and the others are still
+    /// solved. If the domain cannot be read or parsed, every problem gets its error.
+    ///
+    /// With the `parallel` feature, a planner built from an `EngineKind` (by `new`,
+    /// `with_engine_kind`, or a builder without a custom heuristic) solves the problems on rayon
+    /// threads, each with its own engine of that kind and configuration. A planner given its own
+    /// engine or heuristic solves them one after another on it.
+    pub fn solve_batch(&mut self, domain_path: &str, problem_paths: &[&str]) -> Vec<(String, SearchResult)> {
+        let domain = std::fs::read_to_string(domain_path)
+            .map_err(|error| format!("cannot read {}: {}", domain_path, error))
+            .and_then(|content| ParsedDomain::try_parse(&content).map_err(|error| error.to_string()));
+        let load = |path: &str| -> Result<TemporalTask, String> {
+            let domain = domain.as_ref().map_err(Clone::clone)?;
+            let content = std::fs::read_to_string(path).map_err(|error| format!("cannot read {}: {}", path, error))?;
+            TemporalTask::try_from_domain_and_problem(domain, &content).map_err(|error| error.to_string())
+        };
+        let solve = |engine: &mut dyn TemporalSearchEngine, path: &str| match load(path) {
+            Ok(task) => engine.search(&task),
+            Err(error) => SearchResult::InvalidTask(error),
+        };
+
+        #[cfg(feature = "parallel")]
+        if let Some((kind, config)) = &self.engine_settings {
+            use rayon::prelude::*;
+            return problem_paths.par_iter()
+                .map(|path| (path.to_string(), solve(create_engine(*kind, config).as_mut(), path)))
+                .collect();
+        }
+        problem_paths.iter()
+            .map(|path| (path.to_string(), solve(self.search_engine.as_mut(), path)))
+            .collect()
+    }
+
+    /// Cheap, possibly suboptimal plan from a `greedy_rollout` guided by `TemporalFFHeuristic`,
+    /// e.g. as an initial upper bound; `None` if the rollout hits a dead end
+    pub fn greedy_rollout(&self, task: &TemporalTask) -> Option<Plan> {
+        greedy_rollout(task, &heuristics::TemporalFFHeuristic::new())
+    }
+
+    /// Which PDDL requirements `task` uses and which of them this planner cannot handle, e.g. a
+    /// domain needing `:continuous-effects` or the object fluents of `:fluents`
+    pub fn check_support(&self, task: &TemporalTask) -> SupportReport {
+        let requirements = task.used_requirements();
+        let unsupported = requirements.iter()
+            .filter(|requirement| !SUPPORTED_REQUIREMENTS.contains(&requirement.as_str()))
+            .cloned()
+            .collect();
+        SupportReport { requirements, unsupported }
+    }
+
+    /// Number of goal conditions, boolean and numeric, that hold in `state`
+    pub fn goals_achieved(&self, task: &TemporalTask, state: &State) -> usize {
+        task.satisfied_goal_count(state)
+    }
+
+    /// Get planner statistics and information; the capability flags follow `SUPPORTED_REQUIREMENTS`
     pub fn get_info(&self) -> PlannerInfo {
         PlannerInfo {
             version: env!("CARGO_PKG_VERSION").to_string(),
-            search_algorithm: "Temporal A*".to_string(),
-            supports_durative_actions: true,
-            supports_numeric_fluents: true,
+            search_algorithm: self.search_engine.algorithm_name(),
+            supports_durative_actions: SUPPORTED_REQUIREMENTS.contains(&":durative-actions"),
+            supports_numeric_fluents: SUPPORTED_REQUIREMENTS.contains(&":numeric-fluents"),
         }
     }
 }
@@ -67,6 +191,106 @@ impl Default for TemporalPlanner {
     }
 }
 
+/// Builds a `TemporalPlanner` around one of the bundled engines. Anything left unset keeps the
+/// default of `TemporalPlanner::new`: A* guided by the FF heuristic, unweighted and unlimited.
+pub struct TemporalPlannerBuilder {
+    engine: EngineKind,
+    heuristic: Option<Box<dyn TemporalHeuristic>>,
+    config: PlannerConfig,
+}
+
+impl TemporalPlannerBuilder {
+    pub fn new() -> Self {
+        Self {
+            engine: EngineKind::AStar,
+            heuristic: None,
+            config: PlannerConfig::default(),
+        }
+    }
+
+    pub fn engine(mut self, engine: EngineKind) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Guide the search with `heuristic` instead of the engine's default; any implementation of
+    /// `TemporalHeuristic` works, including one defined outside this crate
+    pub fn heuristic(mut self, heuristic: Box<dyn TemporalHeuristic>) -> Self {
+        self.heuristic = Some(heuristic);
+        self
+    }
+
+    /// Weight on h for A*; greedy search ignores it
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.config.weight = weight;
+        self
+    }
+
+    pub fn max_time(mut self, max_time: Duration) -> Self {
+        self.config.limits.max_time = Some(max_time);
+        self
+    }
+
+    pub fn max_expansions(mut self, max_expansions: usize) -> Self {
+        self.config.limits.max_expansions = Some(max_expansions);
+        self
+    }
+
+    /// Let another thread abort `solve` through `token`
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.config.cancellation = Some(token);
+        self
+    }
+
+    pub fn build(self) -> TemporalPlanner {
+        let engine_settings = self.heuristic.is_none().then(|| (self.engine, self.config.clone()));
+        TemporalPlanner {
+            search_engine: search::create_engine_with_heuristic(self.engine, &self.config, self.heuristic),
+            engine_settings,
+        }
+    }
+}
+
+impl Default for TemporalPlannerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// PDDL requirement flags the parser and every bundled engine handle. Disjunctive action
+/// conditions split the action into one per disjunct and derived predicates into one rule per
+/// disjunct; disjunctive goals and `imply` are not supported, and quantifiers are only expanded
+/// in derived-predicate bodies, so `:adl` and `:disjunctive-preconditions` are missing.
+pub const SUPPORTED_REQUIREMENTS: &[&str] = &[
+    ":strips",
+    ":typing",
+    ":negative-preconditions",
+    ":equality",
+    ":conditional-effects",
+    ":durative-actions",
+    ":duration-inequalities",
+    ":numeric-fluents",
+    ":derived-predicates",
+    ":action-costs",
+    ":constraints",
+    ":timed-initial-literals",
+];
+
+/// Result of `TemporalPlanner::check_support` for one task
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupportReport {
+    /// Requirements the task declares or visibly needs, sorted
+    pub requirements: Vec<String>,
+    /// Those of `requirements` outside `SUPPORTED_REQUIREMENTS`
+    pub unsupported: Vec<String>,
+}
+
+impl SupportReport {
+    pub fn is_supported(&self) -> bool {
+        self.unsupported.is_empty()
+    }
+}
+
 /// Information about the planner capabilities
 #[derive(Debug, Clone)]
 pub struct PlannerInfo {
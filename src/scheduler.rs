@@ -1,5 +1,7 @@
 // f:\common\Source_Code\TemporalFastDownward\rust\src\temporal_planner\scheduler.rs
-use super::temporal_task::TemporalAction;
+use super::search::Plan;
+use super::temporal_task::{DurationSpec, TemporalAction, TemporalTask};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone)]
 pub struct ScheduledAction {
@@ -8,9 +10,79 @@ pub struct ScheduledAction {
     pub end_time: f64,
 }
 
+/// Timed actions produced by the scheduler
+#[derive(Debug, Clone, Default)]
+pub struct Schedule {
+    pub actions: Vec<ScheduledAction>,
+    /// Sum of the actions' `total-cost` increases; `None` unless some action has a cost
+    pub total_cost: Option<f64>,
+}
+
+impl Schedule {
+    /// Indices of the actions running at time `t`, i.e. whose `[start, end)` interval contains it
+    pub fn active_at(&self, t: f64) -> Vec<usize> {
+        self.actions.iter()
+            .filter(|action| action.start_time <= t && t < action.end_time)
+            .map(|action| action.action_idx)
+            .collect()
+    }
+
+    /// Latest end time, with the plan starting at time 0
+    pub fn makespan(&self) -> f64 {
+        self.actions.iter().map(|action| action.end_time).fold(0.0, f64::max)
+    }
+
+    /// Largest number of actions running at the same time
+    pub fn max_concurrency(&self) -> usize {
+        // Concurrency only rises when an action starts
+        self.actions.iter()
+            .map(|action| self.active_at(action.start_time).len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Unused capacity over the plan, `makespan * max_concurrency - sum_of_durations`: the time
+    /// each of `max_concurrency` parallel slots spends without an action
+    pub fn idle_time(&self) -> f64 {
+        let busy: f64 = self.actions.iter().map(|action| action.end_time - action.start_time).sum();
+        self.makespan() * self.max_concurrency() as f64 - busy
+    }
+}
+
+/// A time point of a `SimpleTemporalNetwork`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimePoint {
+    /// Time 0, when the plan starts
+    Origin,
+    /// Start of the action with this index, counting actions in the order they were added
+    Start(usize),
+    /// End of the action with this index
+    End(usize),
+}
+
+impl TimePoint {
+    /// Node of this time point in the distance graph
+    fn node(self) -> usize {
+        match self {
+            TimePoint::Origin => 0,
+            TimePoint::Start(action) => 1 + 2 * action,
+            TimePoint::End(action) => 2 + 2 * action,
+        }
+    }
+}
+
+/// Simple temporal network over the start and end points of the added actions.
+///
+/// Each constraint bounds the distance between two time points, `lower <= to - from <= upper`.
+/// The network is consistent when some assignment of times satisfies all of them, which holds
+/// exactly when its distance graph has no negative cycle.
 pub struct SimpleTemporalNetwork {
-    // STN for managing temporal constraints
     constraints: Vec<TemporalConstraint>,
+    /// Actions in the order they were added, with their requested timing
+    actions: Vec<ScheduledAction>,
+    action_costs: Vec<Option<f64>>,
+    /// All-pairs shortest distances from the last successful `propagate`, cleared by any change
+    distances: Option<Vec<Vec<f64>>>,
 }
 
 #[derive(Debug)]
@@ -25,23 +97,613 @@ impl SimpleTemporalNetwork {
     pub fn new() -> Self {
         Self {
             constraints: Vec::new(),
+            actions: Vec::new(),
+            action_costs: Vec::new(),
+            distances: None,
         }
     }
 
-    pub fn add_action(&mut self, _action: &TemporalAction, _start_time: f64) -> Result<(), String> {
-        // Add temporal constraints for action
-        // Check consistency with existing constraints
-        // TODO: Implement STN constraint addition
+    /// Add `action` as the next action index, starting no earlier than `start_time` and ending
+    /// after a duration its `DurationSpec` allows. The network is left unchanged and an error
+    /// returned if the action cannot be fitted into the existing constraints.
+    pub fn add_action(&mut self, action: &TemporalAction, start_time: f64) -> Result<(), String> {
+        let (min_duration, max_duration) = action.duration_spec.bounds(&HashMap::new())
+            .unwrap_or((action.duration, action.duration));
+        self.add_action_with_bounds(action, start_time, min_duration, max_duration)
+    }
+
+    /// Like `add_action`, but with the duration the action actually took, e.g. one that depends
+    /// on fluents
+    pub fn add_action_with_duration(&mut self, action: &TemporalAction, start_time: f64, duration: f64) -> Result<(), String> {
+        self.add_action_with_bounds(action, start_time, duration, duration)
+    }
+
+    /// Like `add_action`, with the duration anywhere in `[min_duration, max_duration]`
+    pub fn add_action_with_bounds(&mut self, action: &TemporalAction, start_time: f64, min_duration: f64, max_duration: f64) -> Result<(), String> {
+        let index = self.actions.len();
+        if min_duration < 0.0 {
+            return Err(format!("Action '{}' has a negative duration", action.name));
+        }
+        self.actions.push(ScheduledAction {
+            action_idx: index,
+            start_time,
+            end_time: start_time + min_duration,
+        });
+        self.action_costs.push(action.cost());
+
+        let previous_constraints = self.constraints.len();
+        self.add_constraint(TimePoint::Origin, TimePoint::Start(index), start_time, f64::INFINITY);
+        self.add_constraint(TimePoint::Start(index), TimePoint::End(index), min_duration, max_duration);
+        if !self.is_consistent() {
+            self.constraints.truncate(previous_constraints);
+            self.actions.pop();
+            self.action_costs.pop();
+            return Err(format!("Action '{}' cannot start at {} under the existing constraints", action.name, start_time));
+        }
         Ok(())
     }
 
+    /// Number of constraints, two per added action plus those from `add_constraint`
+    pub fn constraint_count(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// Require `lower_bound <= to - from <= upper_bound`; either bound may be infinite. The time
+    /// points may belong to actions not added yet.
+    pub fn add_constraint(&mut self, from: TimePoint, to: TimePoint, lower_bound: f64, upper_bound: f64) {
+        self.distances = None;
+        self.constraints.push(TemporalConstraint {
+            from: from.node(),
+            to: to.node(),
+            lower_bound,
+            upper_bound,
+        });
+    }
+
+    /// Whether some timing satisfies every constraint, checked with Bellman-Ford for a negative
+    /// cycle in the distance graph
     pub fn is_consistent(&self) -> bool {
-        // Check if STN is consistent using Bellman-Ford or similar
-        todo!("Implement STN consistency checking")
+        let edges = self.distance_graph();
+        // Start every node at distance 0, as if from a virtual source linked to all of them
+        let mut distance = vec![0.0; self.node_count()];
+        for _ in 0..distance.len() {
+            let mut changed = false;
+            for &(from, to, weight) in &edges {
+                if distance[from] + weight < distance[to] {
+                    distance[to] = distance[from] + weight;
+                    changed = true;
+                }
+            }
+            if !changed {
+                return true;
+            }
+        }
+        // Still relaxing after |V| rounds: some cycle has negative total weight
+        false
+    }
+
+    /// Tighten every pair of time points with Floyd-Warshall and keep the resulting distances
+    /// for `earliest_time` and `latest_time`. Returns `false`, keeping nothing, when some time
+    /// point ends up at a negative distance from itself, i.e. the network is inconsistent.
+    pub fn propagate(&mut self) -> bool {
+        let nodes = self.node_count();
+        let mut distance = vec![vec![f64::INFINITY; nodes]; nodes];
+        for (node, row) in distance.iter_mut().enumerate() {
+            row[node] = 0.0;
+        }
+        for (from, to, weight) in self.distance_graph() {
+            distance[from][to] = distance[from][to].min(weight);
+        }
+        for via in 0..nodes {
+            for from in 0..nodes {
+                for to in 0..nodes {
+                    let through = distance[from][via] + distance[via][to];
+                    if through < distance[from][to] {
+                        distance[from][to] = through;
+                    }
+                }
+            }
+        }
+
+        let consistent = (0..nodes).all(|node| distance[node][node] >= 0.0);
+        self.distances = consistent.then_some(distance);
+        consistent
     }
 
-    pub fn get_schedule(&self) -> Vec<ScheduledAction> {
-        // Extract consistent schedule from STN
-        todo!("Implement schedule extraction")
+    /// Earliest time `point` can occur, read from the last `propagate`; `None` if the network
+    /// changed since or was never propagated
+    pub fn earliest_time(&self, point: TimePoint) -> Option<f64> {
+        let row = self.distances.as_ref()?.get(point.node())?;
+        Some(0.0 - row[TimePoint::Origin.node()])
     }
-}
\ No newline at end of file
+
+    /// Latest time `point` can occur, `f64::INFINITY` if unbounded, read from the last
+    /// `propagate`; `None` if the network changed since or was never propagated
+    pub fn latest_time(&self, point: TimePoint) -> Option<f64> {
+        self.distances.as_ref()?[TimePoint::Origin.node()].get(point.node()).copied()
+    }
+
+    /// Added actions at the earliest times the constraints allow, with their total cost when
+    /// costs are in use; an error if the network is inconsistent
+    pub fn get_schedule(&self) -> Result<Schedule, String> {
+        if !self.is_consistent() {
+            return Err("Temporal constraints are inconsistent".to_string());
+        }
+
+        // The earliest time of a point is minus its shortest distance to the origin, found by
+        // Bellman-Ford from the origin over the reversed edges
+        let edges = self.distance_graph();
+        let mut distance = vec![f64::INFINITY; self.node_count()];
+        distance[TimePoint::Origin.node()] = 0.0;
+        for _ in 1..distance.len() {
+            let mut changed = false;
+            for &(from, to, weight) in &edges {
+                if distance[to] + weight < distance[from] {
+                    distance[from] = distance[to] + weight;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        let earliest = |point: TimePoint| 0.0 - distance[point.node()];
+
+        let costs: Vec<f64> = self.action_costs.iter().flatten().copied().collect();
+        Ok(Schedule {
+            actions: self.actions.iter()
+                .map(|action| ScheduledAction {
+                    action_idx: action.action_idx,
+                    start_time: earliest(TimePoint::Start(action.action_idx)),
+                    end_time: earliest(TimePoint::End(action.action_idx)),
+                })
+                .collect(),
+            total_cost: (!costs.is_empty()).then(|| costs.iter().sum()),
+        })
+    }
+
+    fn node_count(&self) -> usize {
+        let constrained = self.constraints.iter().map(|constraint| constraint.from.max(constraint.to) + 1);
+        constrained.fold(1 + 2 * self.actions.len(), usize::max)
+    }
+
+    /// Weighted edges `(from, to, weight)` meaning `t_to - t_from <= weight`; infinite bounds
+    /// give no edge
+    fn distance_graph(&self) -> Vec<(usize, usize, f64)> {
+        let mut edges = Vec::new();
+        for constraint in &self.constraints {
+            if constraint.upper_bound.is_finite() {
+                edges.push((constraint.from, constraint.to, constraint.upper_bound));
+            }
+            if constraint.lower_bound.is_finite() {
+                edges.push((constraint.to, constraint.from, -constraint.lower_bound));
+            }
+        }
+        edges
+    }
+}
+
+/// Lift a sequential plan, as found by the search, into the earliest schedule that keeps every
+/// pair of interfering steps in their plan order; other steps may overlap. A step with a bounded
+/// duration over no fluents takes the shortest duration its range and the ordering allow; other
+/// steps keep the duration they had in `plan`, `end_times` minus `start_times`.
+///
+/// Two steps interfere when one changes an atom the other reads or changes, or a fluent the
+/// other uses. Reading a derived atom counts as reading every atom. Under `always` constraints
+/// the plan stays sequential, since overlapping steps could pass through a forbidden state.
+/// With timed initial literals the plan keeps its own timing, which is tied to when they fire.
+pub fn schedule_plan(task: &TemporalTask, plan: &Plan) -> Plan {
+    schedule_plan_with_separation(task, plan, 0.0)
+}
+
+/// As `schedule_plan`, with each step that interferes with an earlier one starting at least
+/// `separation` after that one ends, so the end effects of the first never coincide with the
+/// start of the second. Steps that do not interfere may still start and end together. A range
+/// open at 0 keeps its step at least `separation` long, as the search does. A plan with a step of
+/// negative duration is returned unscheduled.
+pub fn schedule_plan_with_separation(task: &TemporalTask, plan: &Plan, separation: f64) -> Plan {
+    if !task.timed_literals.is_empty() {
+        return plan.clone();
+    }
+    let footprints = step_footprints(task, plan);
+
+    let mut stn = SimpleTemporalNetwork::new();
+    for (step, &action_idx) in plan.actions.iter().enumerate() {
+        let action = &task.ground_actions[action_idx];
+        let duration = plan.end_times[step] - plan.start_times[step];
+        let fixed = HashMap::new();
+        let (min_duration, max_duration) = match (&action.duration_spec, action.duration_spec.bounds(&fixed)) {
+            // The shortest of the search's choices, so a range open at 0 gets the same least duration
+            (DurationSpec::Bounded { .. }, Some((_, max))) => (action.duration_spec.choices(&fixed, separation)[0], max),
+            _ => (duration, duration),
+        };
+        // Plans built by hand, or read back from elsewhere, may hold a step no schedule fits
+        if stn.add_action_with_bounds(action, 0.0, min_duration, max_duration).is_err() {
+            return plan.clone();
+        }
+    }
+    for (earlier, later) in interfering_steps(&footprints) {
+        stn.add_constraint(TimePoint::End(earlier), TimePoint::Start(later), separation, f64::INFINITY);
+    }
+
+    // Precedences only point forward in the plan, so they cannot form a cycle
+    let schedule = stn.get_schedule().expect("Plan-order precedences are consistent");
+    Plan {
+        start_times: schedule.actions.iter().map(|action| action.start_time).collect(),
+        end_times: schedule.actions.iter().map(|action| action.end_time).collect(),
+        ..plan.clone()
+    }
+}
+
+/// Steps of a plan ordered only where `schedule_plan` has to order them, so an executive can run
+/// the others in parallel
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialOrderPlan {
+    /// Ground action of each step, numbered as in the sequential plan
+    pub actions: Vec<usize>,
+    /// How long each step takes
+    pub durations: Vec<f64>,
+    /// `(earlier, later)` pairs of steps where `later` may only start once `earlier` has ended.
+    /// Orderings implied by others through a chain of steps are left out.
+    pub orderings: Vec<(usize, usize)>,
+}
+
+impl PartialOrderPlan {
+    /// Steps that must end before `step` starts, directly or through other steps
+    pub fn predecessors(&self, step: usize) -> Vec<usize> {
+        let mut found = vec![false; self.actions.len()];
+        let mut agenda = vec![step];
+        while let Some(current) = agenda.pop() {
+            for &(earlier, _) in self.orderings.iter().filter(|&&(_, later)| later == current) {
+                if !found[earlier] {
+                    found[earlier] = true;
+                    agenda.push(earlier);
+                }
+            }
+        }
+        (0..self.actions.len()).filter(|&other| found[other]).collect()
+    }
+
+    /// Whether neither step has to wait for the other
+    pub fn is_unordered(&self, a: usize, b: usize) -> bool {
+        !self.predecessors(a).contains(&b) && !self.predecessors(b).contains(&a)
+    }
+}
+
+/// The precedences `schedule_plan` puts between the steps of `plan`, as a `PartialOrderPlan`.
+/// Where the schedule keeps the plan's own timing, with timed initial literals, every step
+/// follows the one before it.
+pub fn partial_order_plan(task: &TemporalTask, plan: &Plan) -> PartialOrderPlan {
+    let steps = plan.actions.len();
+    let mut ordered = vec![vec![false; steps]; steps];
+    if task.timed_literals.is_empty() {
+        for (earlier, later) in interfering_steps(&step_footprints(task, plan)) {
+            ordered[earlier][later] = true;
+        }
+    } else {
+        for step in 1..steps {
+            ordered[step - 1][step] = true;
+        }
+    }
+
+    // Close transitively, Floyd-Warshall style with every edge pointing forward in the plan,
+    // then keep the edges no intermediate step implies
+    let mut reaches = ordered.clone();
+    for middle in 0..steps {
+        let from_middle = reaches[middle].clone();
+        for row in reaches.iter_mut().take(middle).filter(|row| row[middle]) {
+            row.iter_mut().zip(&from_middle).for_each(|(reached, &through)| *reached |= through);
+        }
+    }
+    let orderings = (0..steps)
+        .flat_map(|earlier| (earlier + 1..steps).map(move |later| (earlier, later)))
+        .filter(|&(earlier, later)| {
+            ordered[earlier][later] && !(earlier + 1..later).any(|middle| reaches[earlier][middle] && reaches[middle][later])
+        })
+        .collect();
+
+    PartialOrderPlan {
+        actions: plan.actions.clone(),
+        durations: plan.end_times.iter().zip(&plan.start_times).map(|(end, start)| end - start).collect(),
+        orderings,
+    }
+}
+
+fn step_footprints(task: &TemporalTask, plan: &Plan) -> Vec<Footprint> {
+    plan.actions.iter()
+        .map(|&action_idx| Footprint::new(task, &task.ground_actions[action_idx]))
+        .collect()
+}
+
+/// `(earlier, later)` pairs of steps whose footprints interfere, which must keep their order
+fn interfering_steps(footprints: &[Footprint]) -> Vec<(usize, usize)> {
+    (0..footprints.len())
+        .flat_map(|later| (0..later).map(move |earlier| (earlier, later)))
+        .filter(|&(earlier, later)| footprints[earlier].interferes(&footprints[later]))
+        .collect()
+}
+
+/// Facts and fluents one ground action reads and writes, deciding which steps of a plan may
+/// overlap in `schedule_plan`
+pub(crate) struct Footprint {
+    reads: HashSet<usize>,
+    writes: HashSet<usize>,
+    reads_derived: bool,
+    reads_fluents: bool,
+    writes_fluents: bool,
+    /// The task has `always` constraints, so no steps may overlap
+    sequential: bool,
+}
+
+impl Footprint {
+    pub(crate) fn new(task: &TemporalTask, action: &TemporalAction) -> Self {
+        let derived: HashSet<usize> = task.axioms.iter()
+            .filter_map(|axiom| task.fact_id(&axiom.predicate, &axiom.args))
+            .collect();
+        let conditional = || action.conditional_effects_start.iter().chain(&action.conditional_effects_end);
+
+        let reads: HashSet<usize> = action.conditions_start.iter()
+            .chain(&action.conditions_over_all)
+            .chain(&action.conditions_end)
+            .chain(conditional().flat_map(|conditional| &conditional.conditions))
+            .filter_map(|condition| task.fact_id(&condition.predicate, &condition.args))
+            .collect();
+        let writes = action.effects_start.iter()
+            .chain(&action.effects_end)
+            .chain(conditional().flat_map(|conditional| &conditional.effects))
+            .filter_map(|effect| task.fact_id(&effect.predicate, &effect.args))
+            .collect();
+        let writes_fluents = !action.numeric_effects_start.is_empty()
+            || !action.numeric_effects_end.is_empty()
+            || conditional().any(|conditional| !conditional.numeric_effects.is_empty());
+        // Numeric effects read their own right-hand sides
+        let reads_fluents = writes_fluents
+            || !action.numeric_conditions_start.is_empty()
+            || !action.numeric_conditions_over_all.is_empty()
+            || !action.numeric_conditions_end.is_empty()
+            || conditional().any(|conditional| !conditional.numeric_conditions.is_empty())
+            || action.duration_spec.evaluate(&HashMap::new()).is_none();
+
+        Self {
+            reads_derived: reads.iter().any(|fact| derived.contains(fact)),
+            reads,
+            writes,
+            reads_fluents,
+            writes_fluents,
+            sequential: !task.always_constraints.is_empty(),
+        }
+    }
+
+    /// Whether a step with this footprint and one with `other` must not overlap
+    pub(crate) fn interferes(&self, other: &Footprint) -> bool {
+        if self.sequential || other.sequential {
+            return true;
+        }
+        let changes_what_other_uses = |a: &Footprint, b: &Footprint| {
+            a.writes.iter().any(|fact| b.reads.contains(fact) || b.writes.contains(fact))
+                || (b.reads_derived && !a.writes.is_empty())
+                || (a.writes_fluents && b.reads_fluents)
+        };
+        changes_what_other_uses(self, other) || changes_what_other_uses(other, self)
+    }
+}
+
+impl Default for SimpleTemporalNetwork {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduled(action_idx: usize, start_time: f64, end_time: f64) -> ScheduledAction {
+        ScheduledAction { action_idx, start_time, end_time }
+    }
+
+    #[test]
+    fn test_active_at_finds_overlapping_actions() {
+        let schedule = Schedule {
+            actions: vec![scheduled(0, 1.0, 4.0), scheduled(1, 2.0, 6.0), scheduled(2, 4.0, 5.0)],
+            total_cost: None,
+        };
+
+        assert_eq!(schedule.active_at(0.5), Vec::<usize>::new(), "Nothing has started yet");
+        assert_eq!(schedule.active_at(3.0), vec![0, 1]);
+        assert_eq!(schedule.active_at(4.0), vec![1, 2], "Intervals are half-open");
+    }
+
+    #[test]
+    fn test_idle_time_counts_gaps_in_every_slot() {
+        let sequential = Schedule {
+            actions: vec![scheduled(0, 0.0, 2.0), scheduled(1, 3.0, 5.0)],
+            total_cost: None,
+        };
+        assert_eq!(sequential.makespan(), 5.0);
+        assert_eq!(sequential.max_concurrency(), 1);
+        assert_eq!(sequential.idle_time(), 1.0, "Nothing runs during [2, 3)");
+
+        // Two slots over [0, 6) with 2 + 3 + 1 time units of work
+        let parallel = Schedule {
+            actions: vec![scheduled(0, 0.0, 2.0), scheduled(1, 1.0, 4.0), scheduled(2, 5.0, 6.0)],
+            total_cost: None,
+        };
+        assert_eq!(parallel.max_concurrency(), 2);
+        assert_eq!(parallel.idle_time(), 6.0);
+        assert_eq!(Schedule::default().idle_time(), 0.0);
+    }
+
+    const TWO_STEPS: &str = r#"
+(define (domain steps)
+  (:requirements :durative-actions)
+  (:predicates (ready))
+  (:durative-action short
+    :parameters ()
+    :duration (= ?duration 2)
+    :condition (at start (ready))
+    :effect (at end (ready)))
+  (:durative-action long
+    :parameters ()
+    :duration (= ?duration 3)
+    :condition (at start (ready))
+    :effect (at end (ready))))
+"#;
+
+    fn two_steps() -> SimpleTemporalNetwork {
+        let problem = "(define (problem p) (:domain steps) (:init (ready)) (:goal (ready)))";
+        let task = crate::TemporalTask::from_pddl(TWO_STEPS, problem);
+        let mut stn = SimpleTemporalNetwork::new();
+        stn.add_action(&task.ground_actions[0], 0.0).unwrap();
+        stn.add_action(&task.ground_actions[1], 0.0).unwrap();
+        // The long step starts once the short one has ended
+        stn.add_constraint(TimePoint::End(0), TimePoint::Start(1), 0.0, f64::INFINITY);
+        stn
+    }
+
+    #[test]
+    fn test_partial_order_drops_implied_orderings() {
+        let problem = "(define (problem p) (:domain steps) (:init (ready)) (:goal (ready)))";
+        let task = crate::TemporalTask::from_pddl(TWO_STEPS, problem);
+        // Every step reads and writes (ready), so each pair interferes
+        let plan = Plan {
+            actions: vec![0, 1, 0],
+            start_times: vec![0.0, 2.0, 5.0],
+            end_times: vec![2.0, 5.0, 7.0],
+            cost: 7.0,
+            sequential_cost: 7.0,
+            optimality: Default::default(),
+        };
+        let order = partial_order_plan(&task, &plan);
+        assert_eq!(order.orderings, vec![(0, 1), (1, 2)], "0 before 2 follows through 1");
+        assert_eq!(order.durations, vec![2.0, 3.0, 2.0]);
+        assert_eq!(order.predecessors(2), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_consistent_chain_of_constraints() {
+        let mut stn = two_steps();
+        assert!(stn.is_consistent());
+
+        // 2 + 3 time units fit exactly into a deadline of 5
+        stn.add_constraint(TimePoint::Origin, TimePoint::End(1), 0.0, 5.0);
+        assert!(stn.is_consistent());
+    }
+
+    #[test]
+    fn test_over_constrained_cycle_is_inconsistent() {
+        let mut stn = two_steps();
+        stn.add_constraint(TimePoint::Origin, TimePoint::End(1), 0.0, 4.0);
+        assert!(!stn.is_consistent(), "The chain needs at least 5 time units");
+
+        let mut stn = two_steps();
+        stn.add_constraint(TimePoint::Start(1), TimePoint::End(0), 1.0, f64::INFINITY);
+        assert!(!stn.is_consistent(), "The short step cannot end both before and after the long one starts");
+    }
+
+    #[test]
+    fn test_add_action_constrains_start_and_end() {
+        let problem = "(define (problem p) (:domain steps) (:init (ready)) (:goal (ready)))";
+        let task = crate::TemporalTask::from_pddl(TWO_STEPS, problem);
+        let mut stn = SimpleTemporalNetwork::new();
+        stn.add_action(&task.ground_actions[0], 1.0).unwrap();
+        assert_eq!(stn.constraint_count(), 2, "A release time and a duration");
+
+        // The long step must be over by time 4 but cannot start before time 2
+        stn.add_constraint(TimePoint::Origin, TimePoint::End(1), 0.0, 4.0);
+        assert!(stn.add_action(&task.ground_actions[1], 2.0).is_err());
+        assert_eq!(stn.constraint_count(), 3, "The rejected action leaves no constraints behind");
+        assert!(stn.is_consistent());
+
+        stn.add_action(&task.ground_actions[1], 1.0).unwrap();
+        assert_eq!(stn.constraint_count(), 5);
+        let schedule = stn.get_schedule().unwrap();
+        assert_eq!((schedule.actions[1].start_time, schedule.actions[1].end_time), (1.0, 4.0));
+    }
+
+    #[test]
+    fn test_propagate_tightens_bounds() {
+        let problem = "(define (problem p) (:domain steps) (:init (ready)) (:goal (ready)))";
+        let task = crate::TemporalTask::from_pddl(TWO_STEPS, problem);
+        let mut stn = SimpleTemporalNetwork::new();
+        assert_eq!(stn.earliest_time(TimePoint::Origin), None, "Not propagated yet");
+
+        // Origin, start, and end of a 2-unit step released at 1 with a deadline of 5
+        stn.add_action(&task.ground_actions[0], 1.0).unwrap();
+        stn.add_constraint(TimePoint::Origin, TimePoint::End(0), 0.0, 5.0);
+        assert!(stn.propagate());
+        assert_eq!(stn.earliest_time(TimePoint::Origin), Some(0.0));
+        assert_eq!(stn.latest_time(TimePoint::Origin), Some(0.0));
+        assert_eq!(stn.earliest_time(TimePoint::Start(0)), Some(1.0));
+        assert_eq!(stn.latest_time(TimePoint::Start(0)), Some(3.0), "The deadline minus the duration");
+        assert_eq!(stn.earliest_time(TimePoint::End(0)), Some(3.0));
+        assert_eq!(stn.latest_time(TimePoint::End(0)), Some(5.0));
+
+        stn.add_constraint(TimePoint::Origin, TimePoint::Start(0), 0.0, 0.5);
+        assert_eq!(stn.latest_time(TimePoint::Start(0)), None, "Stale bounds are dropped");
+        assert!(!stn.propagate(), "The step cannot start before its release time");
+        assert_eq!(stn.earliest_time(TimePoint::End(0)), None);
+    }
+
+    #[test]
+    fn test_schedule_respects_precedence() {
+        let schedule = two_steps().get_schedule().unwrap();
+        let (short, long) = (&schedule.actions[0], &schedule.actions[1]);
+        assert_eq!((short.start_time, short.end_time), (0.0, 2.0));
+        assert!(long.start_time >= short.end_time, "The long step must wait for the short one");
+        assert_eq!((long.start_time, long.end_time), (2.0, 5.0), "Earliest-start schedule");
+
+        let mut stn = two_steps();
+        stn.add_constraint(TimePoint::Origin, TimePoint::End(1), 0.0, 4.0);
+        assert!(stn.get_schedule().is_err());
+    }
+
+    #[test]
+    fn test_schedule_reports_total_cost() {
+        let domain = r#"
+(define (domain deliveries)
+  (:requirements :durative-actions :action-costs)
+  (:predicates (ready))
+  (:functions (total-cost))
+
+  (:durative-action ship
+    :parameters ()
+    :duration (= ?duration 3)
+    :condition (at start (ready))
+    :effect (at end (increase (total-cost) 5)))
+
+  (:durative-action fly
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (at start (ready))
+    :effect (and (at start (increase (total-cost) 7.5))
+                 (at end (increase (total-cost) 2))))
+
+  (:durative-action wait
+    :parameters ()
+    :duration (= ?duration 2)
+    :condition (at start (ready))
+    :effect (at end (ready))))
+"#;
+        let problem = "(define (problem p) (:domain deliveries) (:init (ready)) (:goal (ready)) (:metric minimize (total-cost)))";
+        let task = crate::TemporalTask::from_pddl(domain, problem);
+        let actions = &task.ground_actions;
+        assert_eq!(actions[0].cost(), Some(5.0));
+        assert_eq!(actions[1].cost(), Some(9.5));
+        assert_eq!(actions[2].cost(), None);
+
+        let mut stn = SimpleTemporalNetwork::new();
+        stn.add_action(&actions[0], 0.0).unwrap();
+        stn.add_action(&actions[1], 3.0).unwrap();
+        stn.add_action(&actions[2], 4.0).unwrap();
+        let schedule = stn.get_schedule().unwrap();
+
+        assert_eq!(schedule.actions.len(), 3);
+        assert_eq!(schedule.actions[1].end_time, 4.0);
+        assert_eq!(schedule.total_cost, Some(14.5));
+
+        let mut uncosted = SimpleTemporalNetwork::new();
+        uncosted.add_action(&actions[2], 0.0).unwrap();
+        assert_eq!(uncosted.get_schedule().unwrap().total_cost, None, "No cost metric in use");
+    }
+}
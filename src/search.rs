@@ -1,187 +1,1656 @@
 // f:\common\Source_Code\TemporalFastDownward\rust\src\temporal_planner\search.rs
-use super::state_space::{StateSpace, TemporalState};
-use super::temporal_task::TemporalTask;
-use std::collections::{BinaryHeap, HashMap};
+use super::compiled_task::CompiledTask;
+use super::heuristics::{TemporalFFHeuristic, TemporalHeuristic};
+use super::scheduler::{partial_order_plan, schedule_plan, schedule_plan_with_separation, Footprint, PartialOrderPlan};
+use super::state_space::{EnabledActions, StateId, StateRegistry, StateSpace, TemporalState};
+use super::temporal_task::{Condition, State, TemporalTask};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::cmp::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Start times closer than this count as simultaneous
+const TIME_EPSILON: f64 = 1e-6;
 
 #[derive(Debug, Clone)]
 pub struct Plan {
     pub actions: Vec<usize>,
+    /// When each entry of `actions` starts
+    pub start_times: Vec<f64>,
+    /// When each entry of `actions` ends
+    pub end_times: Vec<f64>,
+    /// The plan's g value under the search's `CostModel`
     pub cost: f64,
+    /// Total duration of the steps, i.e. the makespan if they ran one after another, plus any
+    /// time spent waiting for timed initial literals
+    pub sequential_cost: f64,
+    /// What the search that found the plan guarantees about it
+    pub optimality: Optimality,
+}
+
+/// Optimality guarantees of a plan, following from the search's `CostModel`, weight, and
+/// heuristic. Both are relative to plans that run one action at a time, as the search does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Optimality {
+    /// No plan has a lower sum of action costs, under `CostModel::ActionCount`
+    pub cost_optimal: bool,
+    /// No plan run one action at a time finishes sooner, under `CostModel::Makespan`: the plan's
+    /// `sequential_cost` is minimal. Says nothing about `Plan::makespan`, which a plan that lets
+    /// more steps overlap can beat.
+    pub sequential_makespan_optimal: bool,
+}
+
+impl Plan {
+    /// When the last step ends, 0 for an empty plan; below `sequential_cost` when steps overlap
+    pub fn makespan(&self) -> f64 {
+        self.end_times.iter().copied().fold(0.0, f64::max)
+    }
+
+    /// Whether both plans start the same actions at the same times, up to `tolerance`, in
+    /// whatever order their steps are listed
+    pub fn schedule_equals(&self, other: &Plan, tolerance: f64) -> bool {
+        if self.actions.len() != other.actions.len() {
+            return false;
+        }
+        // Sorting by action then time pairs up repeated uses of an action in time order
+        let sorted_steps = |plan: &Plan| {
+            let mut steps: Vec<(usize, f64)> = plan.actions.iter().copied().zip(plan.start_times.iter().copied()).collect();
+            steps.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+            steps
+        };
+        sorted_steps(self).into_iter()
+            .zip(sorted_steps(other))
+            .all(|((action, time), (other_action, other_time))| {
+                action == other_action && (time - other_time).abs() <= tolerance
+            })
+    }
+
+    /// Copy of the plan with every start time rounded to the nearest multiple of `grid`, for
+    /// executors running on a fixed tick. Returns `None` if `grid` is not finite and positive,
+    /// or if rounding would change the temporal order of two steps: swap them, or make steps
+    /// that started apart start together. The cost is kept as it was.
+    pub fn snap_times(&self, grid: f64) -> Option<Plan> {
+        if !(grid.is_finite() && grid > 0.0) {
+            return None;
+        }
+        let snapped: Vec<f64> = self.start_times.iter().map(|&time| (time / grid).round() * grid).collect();
+        let order = |a: f64, b: f64| {
+            if (a - b).abs() <= TIME_EPSILON { Ordering::Equal } else { a.total_cmp(&b) }
+        };
+        let consistent = (0..snapped.len()).all(|i| {
+            (i + 1..snapped.len()).all(|j| {
+                order(self.start_times[i], self.start_times[j]) == order(snapped[i], snapped[j])
+            })
+        });
+        // Steps keep their durations
+        let end_times = self.end_times.iter()
+            .zip(self.start_times.iter().zip(&snapped))
+            .map(|(&end, (&start, &snapped_start))| end - start + snapped_start)
+            .collect();
+        consistent.then(|| Plan {
+            start_times: snapped,
+            end_times,
+            ..self.clone()
+        })
+    }
+
+    /// The plan's steps with only the orderings its schedule depends on; `task` is the task the
+    /// plan was found for
+    pub fn partial_order(&self, task: &TemporalTask) -> PartialOrderPlan {
+        partial_order_plan(task, self)
+    }
+
+    /// The plan as JSON for downstream tools, with each step named by its ground action in
+    /// `task`, the task the plan was found for:
+    /// `{"makespan", "cost", "sequential_cost", "steps": [{"action", "start", "end", "duration"}]}`
+    pub fn to_json(&self, task: &TemporalTask) -> String {
+        let steps: Vec<serde_json::Value> = self.actions.iter()
+            .zip(self.start_times.iter().zip(&self.end_times))
+            .map(|(&action, (&start, &end))| {
+                serde_json::json!({
+                    "action": task.ground_actions[action].name,
+                    "start": start,
+                    "end": end,
+                    "duration": end - start,
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "makespan": self.makespan(),
+            "cost": self.cost,
+            "sequential_cost": self.sequential_cost,
+            "steps": steps,
+        })
+        .to_string()
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum SearchResult {
     Solution(Plan),
     Failure,
+    /// The task provably has no plan; the string explains why
+    Unsolvable(String),
+    /// The search stopped at a `SearchLimits` bound before finding a plan; the string says which
+    ResourceLimit(String),
+    /// The search's `CancellationToken` was cancelled before a plan was found
+    Cancelled,
+    /// The domain or problem could not be read or parsed, so nothing was searched; the string is
+    /// the error. Only `TemporalPlanner::solve_batch`, which reads the files itself, reports this.
+    InvalidTask(String),
+    /// Progress report passed to a `TemporalAStarSearch::with_progress_callback` callback: a plan
+    /// reaching a state where `goals_met` goal conditions hold, more than any earlier state
+    Partial { plan: Plan, goals_met: usize },
+}
+
+/// Flag for aborting a running search from another thread, e.g. a GUI's stop button. Clones
+/// share the flag; the search checks it before every expansion.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+/// Tokens are equal when they share a flag
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, AtomicOrdering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(AtomicOrdering::Relaxed)
+    }
+}
+
+/// What the g value of a search node, and so a plan's cost, measures
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum CostModel {
+    /// Whatever the task's `:metric` asks for: `Makespan` for `(total-time)` or no metric,
+    /// `ActionCount` for `(total-cost)`, and `MetricValue` for any other expression
+    #[default]
+    TaskMetric,
+    /// Time elapsed since the initial state with one action running at a time, so A* minimizes
+    /// the plan's `sequential_cost`
+    Makespan,
+    /// Makespan of the plan so far once `scheduler::schedule_plan` lets steps that do not
+    /// interfere overlap. The closed list compares states by this g alone, although paths to a
+    /// state differ in how early later steps can start, so optimality is not guaranteed.
+    ParallelMakespan,
+    /// Sum of action costs: the `total-cost` increase of actions that declare one, evaluated in
+    /// the state they start in, and the search's default action cost for the rest, regardless
+    /// of duration
+    ActionCount,
+    /// Change in the value of the task's `:metric` expression since the initial state, negated
+    /// when it is maximized. Steps may then have negative cost, and A* guarantees nothing.
+    MetricValue,
+}
+
+impl CostModel {
+    /// The model a search of `task` uses, with `TaskMetric` replaced by the one it stands for
+    pub fn resolve(self, task: &TemporalTask) -> CostModel {
+        if self != CostModel::TaskMetric {
+            return self;
+        }
+        match &task.metric {
+            Some(metric) if metric.is_total_cost() => CostModel::ActionCount,
+            Some(metric) if !metric.is_total_time() => CostModel::MetricValue,
+            _ => CostModel::Makespan,
+        }
+    }
 }
 
-pub trait TemporalSearchEngine {
+/// Bounds on how much work a search may do; `None` means unbounded
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SearchLimits {
+    pub max_expansions: Option<usize>,
+    pub max_time: Option<Duration>,
+}
+
+/// Weights `TemporalAStarSearch::search_anytime` steps through, ending with plain A*
+pub const ANYTIME_WEIGHTS: [f64; 5] = [5.0, 3.0, 2.0, 1.5, 1.0];
+
+/// Least time between interfering steps of a returned plan unless configured otherwise, the
+/// epsilon validators such as VAL expect
+pub const DEFAULT_SEPARATION: f64 = 0.001;
+
+/// Counters from the most recent `TemporalAStarSearch` run, read with `TemporalAStarSearch::stats`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchStats {
+    pub nodes_expanded: usize,
+    /// Nodes pushed onto the open list, the initial node included
+    pub nodes_generated: usize,
+    pub peak_open_size: usize,
+    pub search_time: Duration,
+    /// Heuristic value of the goal node, if a plan was found
+    pub goal_h: Option<f64>,
+    /// Number of actions in the plan found, if any
+    pub solution_depth: Option<usize>,
+    /// Heuristic lookups answered by the cache enabled with `with_heuristic_cache`
+    pub heuristic_cache_hits: usize,
+    pub heuristic_cache_misses: usize,
+    /// States in the closed list when the search stopped; below `nodes_expanded` when cheaper
+    /// paths reopened some of them
+    pub distinct_states: usize,
+    /// States interned by the search, each stored once however many generated nodes reach it
+    pub interned_states: usize,
+}
+
+/// A search over temporal tasks. Engines are `Send` so that a planner can be handed to another
+/// thread, as the C API does with its handles.
+pub trait TemporalSearchEngine: Send {
     fn search(&mut self, task: &TemporalTask) -> SearchResult;
+
+    /// Up to `limit` plans as cheap as the best one, no two with the same schedule. Engines
+    /// without a way to enumerate ties report their single plan.
+    fn search_all_optimal(&mut self, task: &TemporalTask, limit: usize) -> Vec<Plan> {
+        match self.search(task) {
+            SearchResult::Solution(plan) if limit > 0 => vec![plan],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Up to `k` plans in order of cost, no two with the same schedule or, if `diverse`, with
+    /// the same actions. Engines without a way to enumerate plans report their single plan.
+    fn search_top_k(&mut self, task: &TemporalTask, k: usize, _diverse: bool) -> Vec<Plan> {
+        self.search_all_optimal(task, k)
+    }
+
+    /// Search for successively cheaper plans, passing each to `improved` as it is found, until
+    /// `improved` returns `false` or the engine has nothing better to offer. Returns the best
+    /// plan found, or the first search's result if there was none. Engines without an anytime
+    /// mode report their single plan.
+    fn search_anytime(&mut self, task: &TemporalTask, improved: &mut dyn FnMut(&Plan) -> bool) -> SearchResult {
+        let result = self.search(task);
+        if let SearchResult::Solution(plan) = &result {
+            improved(plan);
+        }
+        result
+    }
+
+    /// Name reported in `PlannerInfo::search_algorithm`
+    fn algorithm_name(&self) -> String {
+        "Custom search".to_string()
+    }
+}
+
+/// What a tie-breaker sees of an open node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeInfo {
+    pub g: f64,
+    pub h: f64,
+    /// Number of actions on the path from the initial state
+    pub depth: usize,
+    pub time: f64,
 }
 
-#[derive(Clone)]
+/// Orders two open nodes with equal f; `Ordering::Less` expands the first one earlier
+pub type TieBreaker = Box<dyn Fn(&NodeInfo, &NodeInfo) -> Ordering + Send>;
+
+/// Default tie-breaking: prefer the node with the higher g, i.e. the one closer to a goal
+fn prefer_higher_g(a: &NodeInfo, b: &NodeInfo) -> Ordering {
+    b.g.total_cmp(&a.g)
+}
+
+/// Index of a `SearchNode` in the arena of the search that generated it
+type NodeId = usize;
+
+/// A generated node, stored once in the search's arena and referring to its parent by index
+#[derive(Debug, Clone, Copy)]
 struct SearchNode {
-    state: TemporalState,
+    state: StateId,
+    /// Clock of `state`; interned states are stored shifted to time zero
+    time: f64,
     g_value: f64,
     h_value: f64,
-    parent: Option<Box<SearchNode>>,
+    depth: usize,
+    parent: Option<NodeId>,
     action_idx: Option<usize>,
+    /// When `action_idx` ends once the path is scheduled in parallel; only tracked under
+    /// `CostModel::ParallelMakespan`
+    step_end: f64,
+}
+
+impl SearchNode {
+    fn info(&self) -> NodeInfo {
+        NodeInfo {
+            g: self.g_value,
+            h: self.h_value,
+            depth: self.depth,
+            time: self.time,
+        }
+    }
+}
+
+/// Ranks an open node; lower values are expanded first
+type Priority<'p> = &'p dyn Fn(&SearchNode) -> f64;
+
+/// Open-list entry: lower priority first, ties resolved by the search's tie-breaker
+struct OpenEntry<'a> {
+    id: NodeId,
+    info: NodeInfo,
+    /// `g + w * h` for A*, `h` for greedy best-first search
+    priority: f64,
+    tie_breaker: &'a dyn Fn(&NodeInfo, &NodeInfo) -> Ordering,
 }
 
-impl PartialEq for SearchNode {
+impl PartialEq for OpenEntry<'_> {
     fn eq(&self, other: &Self) -> bool {
-        self.f_value() == other.f_value()
+        self.cmp(other) == Ordering::Equal
     }
 }
 
-impl Eq for SearchNode {}
+impl Eq for OpenEntry<'_> {}
 
-impl Ord for SearchNode {
+impl Ord for OpenEntry<'_> {
     fn cmp(&self, other: &Self) -> Ordering {
-        other.f_value().partial_cmp(&self.f_value()).unwrap()
+        // BinaryHeap pops the greatest entry, so both comparisons are reversed
+        other.priority.total_cmp(&self.priority)
+            .then_with(|| (self.tie_breaker)(&other.info, &self.info))
     }
 }
 
-impl PartialOrd for SearchNode {
+impl PartialOrd for OpenEntry<'_> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl SearchNode {
-    fn f_value(&self) -> f64 {
-        self.g_value + self.h_value
+pub struct TemporalAStarSearch {
+    heuristic: Box<dyn TemporalHeuristic>,
+    /// Plans whose makespan would exceed this horizon are pruned
+    max_makespan: Option<f64>,
+    /// Replaces the default higher-g-first rule among nodes with equal f
+    tie_breaker: Option<TieBreaker>,
+    limits: SearchLimits,
+    /// Stops the search with `SearchResult::Cancelled` once cancelled
+    cancellation: Option<CancellationToken>,
+    /// Weight on h in the open-list priority; 1.0 is plain A*
+    weight: f64,
+    /// Reuse heuristic values for recurring classical states
+    cache_heuristic: bool,
+    cost_model: CostModel,
+    /// Cost of an action without a `total-cost` effect under `CostModel::ActionCount`
+    default_action_cost: f64,
+    /// Receives a `SearchResult::Partial` whenever more goals hold than ever before
+    progress_callback: Option<Box<dyn FnMut(SearchResult) + Send>>,
+    stats: SearchStats,
+    /// Where the last search that found a plan stopped, for `add_goal_and_resume`
+    frontier: Option<Frontier>,
+    /// Gathers plans while `search_all_optimal` or `search_top_k` runs
+    collector: Option<PlanCollector>,
+    /// Least time between the end of a step and the start of a later one it interferes with
+    separation: f64,
+}
+
+/// Goal nodes gathered by a search that keeps going after its first plan
+struct PlanCollector {
+    limit: usize,
+    /// Only plans as cheap as the first, as in `search_all_optimal`
+    ties_only: bool,
+    /// Require plans to differ in their actions, not just in their schedules
+    diverse: bool,
+    plans: Vec<Plan>,
+}
+
+impl PlanCollector {
+    /// Whether `plan` is not ruled out by one already found
+    fn admits(&self, plan: &Plan) -> bool {
+        let sorted_actions = |plan: &Plan| {
+            let mut actions = plan.actions.clone();
+            actions.sort_unstable();
+            actions
+        };
+        self.plans.iter().all(|found| {
+            if self.diverse {
+                sorted_actions(found) != sorted_actions(plan)
+            } else {
+                !found.schedule_equals(plan, TIME_EPSILON)
+            }
+        })
     }
 }
 
-pub struct TemporalAStarSearch {
-    heuristic: Box<dyn super::heuristics::TemporalHeuristic>,
+/// Open nodes, the returned goal node among them, and closed states of a search that found a
+/// plan, along with the task it searched, its node arena, and the states its nodes refer to
+struct Frontier {
+    task: TemporalTask,
+    nodes: Vec<SearchNode>,
+    open: Vec<NodeId>,
+    closed: HashMap<ClosedKey, f64>,
+    registry: StateRegistry,
 }
 
+/// Closed-list identity of a node: its interned state, and its time in microseconds when the
+/// search cannot treat time-shifted copies of a state as one
+type ClosedKey = (StateId, Option<i64>);
+
 impl TemporalAStarSearch {
     pub fn new() -> Self {
         Self {
-            heuristic: Box::new(super::heuristics::TemporalFFHeuristic::new()),
+            heuristic: Box::new(TemporalFFHeuristic::new()),
+            max_makespan: None,
+            tie_breaker: None,
+            limits: SearchLimits::default(),
+            cancellation: None,
+            weight: 1.0,
+            cache_heuristic: false,
+            cost_model: CostModel::TaskMetric,
+            default_action_cost: 1.0,
+            progress_callback: None,
+            stats: SearchStats::default(),
+            frontier: None,
+            collector: None,
+            separation: DEFAULT_SEPARATION,
+        }
+    }
+
+    /// Statistics of the last call to `search` or `search_compiled`
+    pub fn stats(&self) -> &SearchStats {
+        &self.stats
+    }
+
+    /// Report anytime progress: `callback` gets a `SearchResult::Partial` each time the search
+    /// expands a state satisfying more goal conditions than any state before it
+    pub fn with_progress_callback(mut self, callback: impl FnMut(SearchResult) + Send + 'static) -> Self {
+        self.progress_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Guide the search with `heuristic` instead of the default `TemporalFFHeuristic`
+    pub fn with_heuristic(mut self, heuristic: Box<dyn TemporalHeuristic>) -> Self {
+        self.heuristic = heuristic;
+        self
+    }
+
+    /// Choose what plan cost measures. The bundled relaxation heuristics estimate time, so they
+    /// are not admissible under `CostModel::ActionCount`.
+    pub fn with_cost_model(mut self, cost_model: CostModel) -> Self {
+        self.cost_model = cost_model;
+        self
+    }
+
+    /// Cost of actions without a `total-cost` effect under `CostModel::ActionCount`; 1.0 unless set
+    pub fn with_default_action_cost(mut self, cost: f64) -> Self {
+        self.default_action_cost = cost;
+        self
+    }
+
+    /// Cache heuristic values by classical state, so a state reached again at another time or
+    /// with other pending effects is not re-evaluated. Only sound for heuristics that ignore both.
+    pub fn with_heuristic_cache(mut self, enabled: bool) -> Self {
+        self.cache_heuristic = enabled;
+        self
+    }
+
+    /// Weighted A*: order the open list by `g + w * h`. Weights above 1 trade optimality for
+    /// fewer expansions; plans then cost at most `w` times the optimum under an admissible heuristic.
+    /// A weight of 0 gives uniform-cost search.
+    ///
+    /// # Panics
+    /// If `w` is negative, infinite, or NaN.
+    pub fn with_weight(mut self, w: f64) -> Self {
+        assert!(w.is_finite() && w >= 0.0, "heuristic weight must be finite and non-negative, got {}", w);
+        self.weight = w;
+        self
+    }
+
+    /// Stop with `SearchResult::ResourceLimit` once either limit is exceeded
+    pub fn with_limits(mut self, limits: SearchLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Stop with `SearchResult::Cancelled` once `token` is cancelled, checked before each expansion
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Decide which of two nodes with equal f is expanded first
+    pub fn with_tie_breaker(mut self, tie_breaker: impl Fn(&NodeInfo, &NodeInfo) -> Ordering + Send + 'static) -> Self {
+        self.tie_breaker = Some(Box::new(tie_breaker));
+        self
+    }
+
+    /// Only accept plans that finish by `horizon`; if none does, the search reports
+    /// `SearchResult::Unsolvable`
+    pub fn with_max_makespan(mut self, horizon: f64) -> Self {
+        self.max_makespan = Some(horizon);
+        self
+    }
+
+    /// Keep interfering steps of returned plans at least `epsilon` apart, so an effect is never
+    /// scheduled at the very instant a later step checks or changes the same atom. Steps that do
+    /// not interfere may still coincide. Defaults to `DEFAULT_SEPARATION`, the 0.001 validators
+    /// such as VAL expect; 0 lets interfering steps touch. `CostModel::ParallelMakespan` counts
+    /// the gaps.
+    pub fn with_separation(mut self, epsilon: f64) -> Self {
+        self.separation = epsilon.max(0.0);
+        self
+    }
+}
+
+impl Default for TemporalAStarSearch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The bundled search engines, as selected by name in configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineKind {
+    /// `TemporalAStarSearch`, weighted when `PlannerConfig::weight` is not 1
+    AStar,
+    /// `TemporalGreedySearch`
+    Greedy,
+}
+
+impl EngineKind {
+    pub const ALL: [EngineKind; 2] = [EngineKind::AStar, EngineKind::Greedy];
+
+    /// Name accepted by `str::parse`
+    pub fn name(self) -> &'static str {
+        match self {
+            EngineKind::AStar => "astar",
+            EngineKind::Greedy => "greedy",
+        }
+    }
+}
+
+impl std::str::FromStr for EngineKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter()
+            .find(|kind| kind.name() == name)
+            .ok_or_else(|| format!("Unknown search engine '{}'", name))
+    }
+}
+
+/// Settings shared by the engines built with `create_engine`; each engine ignores the ones
+/// it has no use for
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannerConfig {
+    /// Heuristic weight for A*; greedy search ignores g entirely
+    pub weight: f64,
+    pub limits: SearchLimits,
+    /// Plan cost measure for A*
+    pub cost_model: CostModel,
+    /// Makespan horizon for A*
+    pub max_makespan: Option<f64>,
+    /// Token the engine polls before each expansion
+    pub cancellation: Option<CancellationToken>,
+    /// Least time between interfering steps of a plan, for both engines
+    pub separation: f64,
+}
+
+impl Default for PlannerConfig {
+    fn default() -> Self {
+        Self {
+            weight: 1.0,
+            limits: SearchLimits::default(),
+            cost_model: CostModel::default(),
+            max_makespan: None,
+            cancellation: None,
+            separation: DEFAULT_SEPARATION,
+        }
+    }
+}
+
+/// Build the engine of the given kind with the default heuristic and `config` applied
+pub fn create_engine(kind: EngineKind, config: &PlannerConfig) -> Box<dyn TemporalSearchEngine> {
+    create_engine_with_heuristic(kind, config, None)
+}
+
+/// As `create_engine`, guided by `heuristic` instead of the default when one is given
+pub(crate) fn create_engine_with_heuristic(
+    kind: EngineKind,
+    config: &PlannerConfig,
+    heuristic: Option<Box<dyn TemporalHeuristic>>,
+) -> Box<dyn TemporalSearchEngine> {
+    match kind {
+        EngineKind::AStar => {
+            let mut engine = TemporalAStarSearch::new()
+                .with_weight(config.weight)
+                .with_limits(config.limits)
+                .with_cost_model(config.cost_model)
+                .with_separation(config.separation);
+            if let Some(horizon) = config.max_makespan {
+                engine = engine.with_max_makespan(horizon);
+            }
+            if let Some(heuristic) = heuristic {
+                engine = engine.with_heuristic(heuristic);
+            }
+            if let Some(token) = config.cancellation.clone() {
+                engine = engine.with_cancellation(token);
+            }
+            Box::new(engine)
+        }
+        EngineKind::Greedy => {
+            let mut engine = TemporalGreedySearch::new()
+                .with_limits(config.limits)
+                .with_separation(config.separation);
+            if let Some(heuristic) = heuristic {
+                engine = engine.with_heuristic(heuristic);
+            }
+            if let Some(token) = config.cancellation.clone() {
+                engine = engine.with_cancellation(token);
+            }
+            Box::new(engine)
+        }
+    }
+}
+
+/// Follow the successor with the lowest h from the initial state until a goal state, without
+/// backtracking. Returns `None` at a dead end: no applicable action leads to a state that is
+/// new along the path and has a finite h. Cheap, and any plan found bounds the optimal cost.
+pub fn greedy_rollout(task: &TemporalTask, heuristic: &dyn TemporalHeuristic) -> Option<Plan> {
+    heuristic.prepare(task);
+    let compiled = task.compile();
+    let state_space = StateSpace::from_compiled(task, &compiled);
+    let mut state = state_space.initial_state();
+    if !state_space.satisfies_constraints(&state.classical_state) {
+        return None;
+    }
+    let mut visited = HashSet::from([state.key()]);
+    let mut plan = Plan {
+        actions: Vec::new(),
+        start_times: Vec::new(),
+        end_times: Vec::new(),
+        cost: 0.0,
+        sequential_cost: 0.0,
+        optimality: Optimality::default(),
+    };
+
+    let mut enabled = None;
+    while !state_space.is_goal(&state) {
+        let current = state_space.enabled_actions(&state, enabled.as_ref());
+        let (action_idx, successor, _) = state_space.applicable_actions(&state, &current)
+            .into_iter()
+            .filter_map(|(action_idx, _)| Some((Some(action_idx), state_space.apply_action_to_completion(&state, action_idx)?)))
+            .chain(state_space.wait_for_timed_literal(&state).map(|waited| (None, waited)))
+            .filter_map(|(action_idx, successor)| {
+                let h = heuristic.compute(&successor, task);
+                (h.is_finite() && !visited.contains(&successor.key())).then_some((action_idx, successor, h))
+            })
+            .min_by(|a, b| a.2.total_cmp(&b.2))?;
+        visited.insert(successor.key());
+        enabled = Some(current);
+        if let Some(action_idx) = action_idx {
+            plan.actions.push(action_idx);
+            plan.start_times.push(state.time);
+            plan.end_times.push(successor.time);
+        }
+        state = successor;
+    }
+    plan.cost = state.time;
+    plan.sequential_cost = state.time;
+    Some(schedule_plan(task, &plan))
+}
+
+/// Greedy best-first search: expands the open node with the lowest h, ignoring g. Plans are
+/// found faster on satisficing problems but need not be optimal. It shares successor
+/// generation, limits, statistics, and plan extraction with `TemporalAStarSearch`.
+pub struct TemporalGreedySearch {
+    engine: TemporalAStarSearch,
+}
+
+impl TemporalGreedySearch {
+    pub fn new() -> Self {
+        Self {
+            engine: TemporalAStarSearch::new(),
         }
     }
+
+    /// Guide the search with `heuristic` instead of the default `TemporalFFHeuristic`
+    pub fn with_heuristic(mut self, heuristic: Box<dyn TemporalHeuristic>) -> Self {
+        self.engine = self.engine.with_heuristic(heuristic);
+        self
+    }
+
+    /// Stop with `SearchResult::ResourceLimit` once either limit is exceeded
+    pub fn with_limits(mut self, limits: SearchLimits) -> Self {
+        self.engine = self.engine.with_limits(limits);
+        self
+    }
+
+    /// Stop with `SearchResult::Cancelled` once `token` is cancelled, checked before each expansion
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.engine = self.engine.with_cancellation(token);
+        self
+    }
+
+    /// Keep interfering steps of returned plans at least `epsilon` apart; see
+    /// `TemporalAStarSearch::with_separation`
+    pub fn with_separation(mut self, epsilon: f64) -> Self {
+        self.engine = self.engine.with_separation(epsilon);
+        self
+    }
+
+    /// Decide which of two nodes with equal h is expanded first
+    pub fn with_tie_breaker(mut self, tie_breaker: impl Fn(&NodeInfo, &NodeInfo) -> Ordering + Send + 'static) -> Self {
+        self.engine = self.engine.with_tie_breaker(tie_breaker);
+        self
+    }
+
+    /// Statistics of the last call to `search`
+    pub fn stats(&self) -> &SearchStats {
+        self.engine.stats()
+    }
+}
+
+impl Default for TemporalGreedySearch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TemporalSearchEngine for TemporalGreedySearch {
+    fn search(&mut self, task: &TemporalTask) -> SearchResult {
+        self.engine.search_space(task, &|node| node.h_value)
+    }
+
+    fn algorithm_name(&self) -> String {
+        self.engine.describe("Temporal greedy best-first")
+    }
 }
 
 impl TemporalSearchEngine for TemporalAStarSearch {
     fn search(&mut self, task: &TemporalTask) -> SearchResult {
-        let state_space = StateSpace::new((*task).clone());
-        let initial_state = TemporalState {
-            classical_state: task.initial_state.clone(),
-            scheduled_effects: Vec::new(),
-            time: 0.0,
+        let weight = self.weight;
+        let result = self.search_space(task, &|node| node.g_value + weight * node.h_value);
+        self.mark_optimality(task, result)
+    }
+
+    /// Weighted A* rerun with each weight of `ANYTIME_WEIGHTS` in turn, preceded by the
+    /// configured weight if that is higher; a plan is reported when it is cheaper than every
+    /// earlier one. A run that finds no plan, e.g. one stopped by `SearchLimits`, ends the schedule.
+    fn search_anytime(&mut self, task: &TemporalTask, improved: &mut dyn FnMut(&Plan) -> bool) -> SearchResult {
+        let configured = self.weight;
+        let mut weights = ANYTIME_WEIGHTS.to_vec();
+        if configured > weights[0] {
+            weights.insert(0, configured);
+        }
+
+        let mut best: Option<Plan> = None;
+        let mut stopped = None;
+        for weight in weights {
+            self.weight = weight;
+            match self.search(task) {
+                SearchResult::Solution(plan) => match &mut best {
+                    Some(best) if plan.cost >= best.cost - TIME_EPSILON => {
+                        // The same cost again, now from a stricter run that may prove it optimal
+                        if plan.cost <= best.cost + TIME_EPSILON {
+                            best.optimality = plan.optimality;
+                        }
+                    }
+                    _ => {
+                        let keep_going = improved(&plan);
+                        best = Some(plan);
+                        if !keep_going {
+                            break;
+                        }
+                    }
+                },
+                other => {
+                    stopped = Some(other);
+                    break;
+                }
+            }
+        }
+        self.weight = configured;
+        best.map(SearchResult::Solution)
+            .or(stopped)
+            .unwrap_or(SearchResult::Failure)
+    }
+
+    /// Keep popping goal nodes after the first plan, expanding states again when reached at an
+    /// equal g, until `limit` plans with distinct schedules are found or every node that could
+    /// still lead to a plan of the first one's cost is exhausted. The plans are all optimal
+    /// when the first is, i.e. under the conditions `Plan::optimality` reports.
+    fn search_all_optimal(&mut self, task: &TemporalTask, limit: usize) -> Vec<Plan> {
+        let plans = self.collect_plans(task, PlanCollector { limit, ties_only: true, diverse: false, plans: Vec::new() });
+        plans.into_iter().map(|plan| self.marked(task, plan)).collect()
+    }
+
+    /// Keep popping goal nodes after the first plan, expanding states again on every path that
+    /// does not loop back on itself so that the cheapest paths all reach the goal; plans ruled
+    /// out by one already found are skipped. Only the first plan carries an optimality
+    /// guarantee. The search may expand many nodes for a large `k`, so `SearchLimits` apply.
+    fn search_top_k(&mut self, task: &TemporalTask, k: usize, diverse: bool) -> Vec<Plan> {
+        let mut plans = self.collect_plans(task, PlanCollector { limit: k, ties_only: false, diverse, plans: Vec::new() });
+        plans.sort_by(|a, b| a.cost.total_cmp(&b.cost));
+        if let Some(first) = plans.first_mut() {
+            *first = self.marked(task, first.clone());
+        }
+        plans
+    }
+
+    fn algorithm_name(&self) -> String {
+        self.describe("Temporal A*")
+    }
+}
+
+impl TemporalAStarSearch {
+    /// As `search`, reusing `compiled`, which must come from `task.compile()`, instead of
+    /// compiling the task again
+    pub fn search_compiled(&mut self, task: &TemporalTask, compiled: &CompiledTask) -> SearchResult {
+        let weight = self.weight;
+        let state_space = StateSpace::from_compiled(task, compiled);
+        let result = self.search_from(task, &state_space, &|node| node.g_value + weight * node.h_value, None);
+        self.mark_optimality(task, result)
+    }
+
+    /// `base` followed by the weight and heuristic when they differ from the defaults, e.g.
+    /// `Temporal A* (w = 2, h^max)`
+    fn describe(&self, base: &str) -> String {
+        let mut settings = Vec::new();
+        if self.weight != 1.0 {
+            settings.push(format!("w = {}", self.weight));
+        }
+        let heuristic = self.heuristic.name();
+        if heuristic != TemporalFFHeuristic::new().name() {
+            settings.push(heuristic);
+        }
+        if settings.is_empty() {
+            base.to_string()
+        } else {
+            format!("{} ({})", base, settings.join(", "))
+        }
+    }
+
+    /// Record on a found plan what A* guarantees: optimality under the active cost model when
+    /// the heuristic is admissible for it, unweighted, and not cached across pending effects
+    fn mark_optimality(&self, task: &TemporalTask, mut result: SearchResult) -> SearchResult {
+        if let SearchResult::Solution(plan) = &mut result {
+            let cost_model = self.cost_model.resolve(task);
+            let exact = self.weight <= 1.0 && !self.cache_heuristic && self.heuristic.is_admissible(cost_model);
+            plan.optimality = Optimality {
+                cost_optimal: exact && cost_model == CostModel::ActionCount,
+                sequential_makespan_optimal: exact && cost_model == CostModel::Makespan,
+            };
+        }
+        result
+    }
+
+    /// Run the search with `collector` gathering goal nodes, returning the plans it keeps
+    fn collect_plans(&mut self, task: &TemporalTask, collector: PlanCollector) -> Vec<Plan> {
+        if collector.limit == 0 {
+            return Vec::new();
+        }
+        self.collector = Some(collector);
+        let weight = self.weight;
+        self.search_space(task, &|node| node.g_value + weight * node.h_value);
+        self.collector.take().map(|collector| collector.plans).unwrap_or_default()
+    }
+
+    /// `plan` with the optimality `mark_optimality` gives a plan of this search
+    fn marked(&self, task: &TemporalTask, plan: Plan) -> Plan {
+        match self.mark_optimality(task, SearchResult::Solution(plan)) {
+            SearchResult::Solution(plan) => plan,
+            _ => unreachable!("mark_optimality keeps the result's variant"),
+        }
+    }
+
+    /// Best-first search over the state space of `task`, through its compiled form, expanding
+    /// open nodes in `priority` order
+    fn search_space(&mut self, task: &TemporalTask, priority: Priority) -> SearchResult {
+        let compiled = task.compile();
+        self.search_from(task, &StateSpace::from_compiled(task, &compiled), priority, None)
+    }
+
+    /// Add `condition` to the goal of the last search that found a plan and continue that
+    /// search from where it stopped: its open nodes, re-evaluated against the extended goal,
+    /// and its closed states are kept rather than rebuilt from the initial state. Returns
+    /// `SearchResult::Failure` if there is no such search to resume.
+    pub fn add_goal_and_resume(&mut self, condition: Condition) -> SearchResult {
+        let Some(mut frontier) = self.frontier.take() else {
+            return SearchResult::Failure;
         };
+        frontier.task.goal_conditions.push(condition);
+        let task = frontier.task.clone();
+        let weight = self.weight;
+        let compiled = task.compile();
+        let state_space = StateSpace::from_compiled(&task, &compiled);
+        let result = self.search_from(&task, &state_space, &|node| node.g_value + weight * node.h_value, Some(frontier));
+        self.mark_optimality(&task, result)
+    }
+
+    fn search_from(&mut self, task: &TemporalTask, state_space: &StateSpace, priority: Priority, resumed: Option<Frontier>) -> SearchResult {
+        self.frontier = None;
+        let started_at = Instant::now();
+        self.heuristic.prepare(task);
+        let mut stats = SearchStats::default();
+        let result = self.run(task, state_space, priority, started_at, &mut stats, resumed);
+        stats.search_time = started_at.elapsed();
+        self.stats = stats;
+        result
+    }
 
+    fn run(
+        &mut self,
+        task: &TemporalTask,
+        state_space: &StateSpace,
+        priority: Priority,
+        started_at: Instant,
+        stats: &mut SearchStats,
+        resumed: Option<Frontier>,
+    ) -> SearchResult {
+        // A cheap relaxed reachability check spares a search that could only exhaust the space
+        if let Some(goal) = task.unreachable_goal() {
+            return SearchResult::Unsolvable(format!("goal {} is unreachable even ignoring delete effects", goal));
+        }
+
+        let initial_state = state_space.initial_state();
+        let cost_model = self.cost_model.resolve(task);
+        let metric = task.metric.as_ref().filter(|_| cost_model == CostModel::MetricValue);
+        let initial_metric = metric.and_then(|metric| metric.cost(&initial_state.classical_state, initial_state.time));
+
+        let tie_breaker = self.tie_breaker.as_deref().unwrap_or(&prefer_higher_g);
         let mut open_list = BinaryHeap::new();
-        let mut closed_list = HashMap::new();
-
-        let initial_node = SearchNode {
-            state: initial_state.clone(),
-            g_value: 0.0,
-            h_value: self.heuristic.compute(&initial_state, task),
-            parent: None,
-            action_idx: None,
+        let mut pruned_by_horizon = false;
+        let mut most_goals_met = state_space.satisfied_goal_count(&initial_state.classical_state);
+        let mut heuristic_cache = HashMap::new();
+        let footprints: Option<Vec<Footprint>> = (cost_model == CostModel::ParallelMakespan).then(|| {
+            task.ground_actions.iter().map(|action| Footprint::new(task, action)).collect()
+        });
+        // Of two copies of a state, the one with lower g is only sure to be at least as good when
+        // g is the elapsed time and no horizon makes the later copy's extra time matter
+        let timeless = cost_model == CostModel::Makespan && self.max_makespan.is_none();
+        let closed_key = |node: &SearchNode| -> ClosedKey {
+            (node.state, (!timeless).then(|| (node.time * 1000000.0).round() as i64))
         };
+        let min_duration = if self.max_makespan.is_some() { Self::min_step_duration(task) } else { 0.0 };
+        // Enabled actions of each expanded state, from which its successors' are updated
+        let mut enabled_actions: HashMap<StateId, EnabledActions> = HashMap::new();
 
-        open_list.push(initial_node);
+        let (mut nodes, open_nodes, mut closed_list, mut registry) = match resumed {
+            Some(frontier) => {
+                // Heuristic values were computed for the old goal
+                let mut nodes = frontier.nodes;
+                for &id in &frontier.open {
+                    let state = frontier.registry.resolve(nodes[id].state, nodes[id].time);
+                    nodes[id].h_value = self.evaluate(&state, task, &mut heuristic_cache, stats);
+                }
+                (nodes, frontier.open, frontier.closed, frontier.registry)
+            }
+            None => {
+                let mut registry = StateRegistry::new();
+                let initial_node = SearchNode {
+                    state: registry.intern(&initial_state),
+                    time: initial_state.time,
+                    g_value: 0.0,
+                    h_value: self.evaluate(&initial_state, task, &mut heuristic_cache, stats),
+                    depth: 0,
+                    parent: None,
+                    action_idx: None,
+                    step_end: 0.0,
+                };
+                let open = if state_space.satisfies_constraints(&initial_state.classical_state) {
+                    vec![0]
+                } else {
+                    Vec::new()
+                };
+                (vec![initial_node], open, HashMap::new(), registry)
+            }
+        };
+        stats.nodes_generated += open_nodes.len();
+        stats.interned_states = registry.len();
+        stats.peak_open_size = open_nodes.len();
+        for id in open_nodes {
+            open_list.push(OpenEntry { id, info: nodes[id].info(), priority: priority(&nodes[id]), tie_breaker });
+        }
 
-        while let Some(node) = open_list.pop() {
+        while let Some(OpenEntry { id, .. }) = open_list.pop() {
+            let node = nodes[id];
+            let state = registry.resolve(node.state, node.time);
+            let optimal_cost = self.collector.as_ref()
+                .filter(|collector| collector.ties_only)
+                .and_then(|collector| collector.plans.first())
+                .map(|plan| plan.cost);
+            if optimal_cost.is_some_and(|cost| node.g_value + node.h_value > cost + TIME_EPSILON) {
+                continue;
+            }
+            if self.collector.is_some() && state_space.is_goal(&state) {
+                if let SearchResult::Solution(plan) = self.extract_plan(task, &nodes, id) {
+                    let Some(collector) = self.collector.as_mut() else {
+                        unreachable!("checked above");
+                    };
+                    if collector.admits(&plan) {
+                        collector.plans.push(plan);
+                    }
+                    if collector.plans.len() >= collector.limit {
+                        return SearchResult::Failure;
+                    }
+                }
+                continue;
+            }
             // Check if goal reached
-            if self.is_goal(&node.state, task) {
-                return self.extract_plan(&node);
+            if state_space.is_goal(&state) {
+                stats.goal_h = Some(node.h_value);
+                stats.solution_depth = Some(node.depth);
+                let result = self.extract_plan(task, &nodes, id);
+                // The goal node was not expanded, so it stays on the frontier
+                let mut open: Vec<NodeId> = open_list.into_iter().map(|entry| entry.id).collect();
+                open.push(id);
+                self.frontier = Some(Frontier {
+                    task: task.clone(),
+                    nodes,
+                    open,
+                    closed: closed_list,
+                    registry,
+                });
+                return result;
             }
 
-            // Skip if already expanded
-            if closed_list.contains_key(&node.state.classical_state) {
+            // Skip states already expanded at an equal or lower g, but reopen them when a strictly
+            // cheaper path turns up, as it can under an inconsistent heuristic. Pending effects
+            // are part of the state's identity, and so is the time unless g measures it.
+            // Enumerating optimal plans needs every path of equal g, and enumerating the k
+            // cheapest every path, so then only paths that loop back to one of their own states
+            // are cut.
+            if let Some(collector) = &self.collector {
+                let reached_cheaper = collector.ties_only
+                    && closed_list.get(&closed_key(&node)).is_some_and(|&best_g| best_g < node.g_value - TIME_EPSILON);
+                if reached_cheaper || Self::revisits_ancestor(&nodes, id) {
+                    continue;
+                }
+            } else if closed_list.get(&closed_key(&node)).is_some_and(|&best_g| best_g <= node.g_value) {
                 continue;
             }
 
-            closed_list.insert(node.state.classical_state.clone(), node.g_value);
+            if self.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled) {
+                return SearchResult::Cancelled;
+            }
+            if self.limits.max_expansions.is_some_and(|limit| stats.nodes_expanded >= limit) {
+                return SearchResult::ResourceLimit(format!("expanded {} nodes without finding a plan", stats.nodes_expanded));
+            }
+            if self.limits.max_time.is_some_and(|limit| started_at.elapsed() >= limit) {
+                return SearchResult::ResourceLimit(format!(
+                    "searched for {} ms without finding a plan",
+                    started_at.elapsed().as_millis()
+                ));
+            }
+            stats.nodes_expanded += 1;
+            #[cfg(feature = "trace")]
+            log::trace!("expanding node {} with g = {}, h = {}\n{}", id, node.g_value, node.h_value, state.describe(task));
+
+            if let Some(callback) = self.progress_callback.as_mut() {
+                let goals_met = state_space.satisfied_goal_count(&state.classical_state);
+                if goals_met > most_goals_met {
+                    most_goals_met = goals_met;
+                    callback(SearchResult::Partial { plan: Self::plan_to(&nodes, id), goals_met });
+                }
+            }
 
-            // Process scheduled effects
-            let processed_state = self.process_scheduled_effects(&node.state);
+            closed_list.insert(closed_key(&node), node.g_value);
+            stats.distinct_states = closed_list.len();
 
-            // Generate successors
-            for (action_idx, start_time) in state_space.get_applicable_actions(&processed_state) {
-                let successor_state = state_space.apply_action(&processed_state, action_idx, start_time);
+            // Generate successors, each running one action to completion, once for each end of a
+            // bounded duration, or waiting for the next timed initial literal
+            let parent_enabled = node.parent.and_then(|parent| enabled_actions.get(&nodes[parent].state));
+            let enabled = state_space.enabled_actions(&state, parent_enabled);
+            let successors = state_space.applicable_actions(&state, &enabled)
+                .into_iter()
+                .flat_map(|(action_idx, _)| {
+                    let spec = &task.ground_actions[action_idx].duration_spec;
+                    let durations = spec.choices(&state.classical_state.numeric_values, self.separation);
+                    durations.into_iter().map(move |duration| (action_idx, duration))
+                })
+                .filter_map(|(action_idx, duration)| {
+                    let successor = state_space.apply_action_to_completion_with_duration(&state, action_idx, duration);
+                    #[cfg(feature = "trace")]
+                    if successor.is_none() {
+                        log::trace!(
+                            "pruned {} for {}: an over-all or end condition fails or a constraint is broken",
+                            task.ground_actions[action_idx].name,
+                            duration,
+                        );
+                    }
+                    Some((Some(action_idx), successor?))
+                })
+                .chain(state_space.wait_for_timed_literal(&state).map(|waited| (None, waited)));
+            enabled_actions.insert(node.state, enabled);
+            for (action_idx, successor_state) in successors {
                 
-                let g_value = node.g_value + (successor_state.time - node.state.time);
-                let h_value = self.heuristic.compute(&successor_state, task);
+                if let Some(horizon) = self.max_makespan {
+                    let earliest_finish = successor_state.time + Self::remaining_time_bound(state_space, &successor_state, min_duration);
+                    if earliest_finish > horizon {
+                        #[cfg(feature = "trace")]
+                        log::trace!("pruned a successor finishing no earlier than {}, past the horizon", earliest_finish);
+                        pruned_by_horizon = true;
+                        continue;
+                    }
+                }
+
+                let duration = successor_state.time - node.time;
+                let step_end = match (&footprints, action_idx) {
+                    (Some(footprints), Some(action_idx)) => {
+                        Self::parallel_step_end(&nodes, id, action_idx, duration, footprints, self.separation)
+                    }
+                    // Nothing can happen in parallel with waiting for the clock
+                    (Some(_), None) => successor_state.time,
+                    (None, _) => 0.0,
+                };
+                let g_value = match (cost_model, action_idx) {
+                    (CostModel::Makespan, _) => node.g_value + duration,
+                    (CostModel::ParallelMakespan, _) => node.g_value.max(step_end),
+                    (CostModel::ActionCount, Some(action_idx)) => {
+                        let cost = task.ground_actions[action_idx].cost_in(&state.classical_state.numeric_values);
+                        node.g_value + cost.unwrap_or(self.default_action_cost)
+                    }
+                    (CostModel::ActionCount, None) => node.g_value,
+                    // A metric that cannot be evaluated leaves g where it was
+                    (CostModel::MetricValue, _) => metric.zip(initial_metric)
+                        .and_then(|(metric, initial)| Some(metric.cost(&successor_state.classical_state, successor_state.time)? - initial))
+                        .unwrap_or(node.g_value),
+                    (CostModel::TaskMetric, _) => unreachable!("resolved above"),
+                };
+                let h_value = self.evaluate(&successor_state, task, &mut heuristic_cache, stats);
+                // A dead end by the heuristic; with w = 0 its priority would be NaN
+                if h_value == f64::INFINITY {
+                    continue;
+                }
 
                 let successor_node = SearchNode {
-                    state: successor_state,
+                    state: registry.intern(&successor_state),
+                    time: successor_state.time,
                     g_value,
                     h_value,
-                    parent: Some(Box::new(node.clone())),
-                    action_idx: Some(action_idx),
+                    depth: node.depth + 1,
+                    parent: Some(id),
+                    action_idx,
+                    step_end,
                 };
 
-                open_list.push(successor_node);
+                open_list.push(OpenEntry {
+                    id: nodes.len(),
+                    info: successor_node.info(),
+                    priority: priority(&successor_node),
+                    tie_breaker,
+                });
+                nodes.push(successor_node);
+                stats.nodes_generated += 1;
+                stats.interned_states = registry.len();
+                stats.peak_open_size = stats.peak_open_size.max(open_list.len());
             }
         }
 
+        if self.collector.is_some() {
+            return SearchResult::Failure;
+        }
+
+        if !task.always_constraints.is_empty() {
+            let diagnosis = self.diagnose_constraints(task);
+            // Not the frontier of this search
+            self.frontier = None;
+            if let Some(reason) = diagnosis {
+                return SearchResult::Unsolvable(reason);
+            }
+        }
+
+        if pruned_by_horizon {
+            let horizon = self.max_makespan.unwrap_or_default();
+            return SearchResult::Unsolvable(format!("no plan finishes within the makespan horizon {}", horizon));
+        }
+
         SearchResult::Failure
     }
 }
 
 impl TemporalAStarSearch {
-    fn is_goal(&self, state: &TemporalState, _task: &TemporalTask) -> bool {
-        // Check if all goal conditions are satisfied
-        // and no scheduled effects remain
-        state.scheduled_effects.is_empty() 
-            // TODO: Implement proper goal condition checking
-            // For now, just check if no scheduled effects remain
+    /// After the constrained space is exhausted, check whether dropping the `always`
+    /// constraints makes the goal reachable and, if so, name the constraints the plan breaks.
+    /// The check runs on a separate engine, so it neither reports progress to this one's
+    /// callback nor overwrites its stats.
+    fn diagnose_constraints(&self, task: &TemporalTask) -> Option<String> {
+        let mut unconstrained = task.clone();
+        unconstrained.always_constraints.clear();
+
+        let mut engine = TemporalAStarSearch {
+            max_makespan: self.max_makespan,
+            limits: self.limits,
+            cancellation: self.cancellation.clone(),
+            weight: self.weight,
+            cost_model: self.cost_model,
+            default_action_cost: self.default_action_cost,
+            separation: self.separation,
+            ..Self::new()
+        };
+        let SearchResult::Solution(plan) = engine.search(&unconstrained) else {
+            return None;
+        };
+
+        // Replay the plan, checking every intermediate state against the constraints
+        let state_space = StateSpace::new(unconstrained);
+        let mut state = state_space.initial_state();
+        let mut trajectory = vec![state.classical_state.clone()];
+        for ((&action_idx, &start_time), &end_time) in plan.actions.iter().zip(&plan.start_times).zip(&plan.end_times) {
+            // Timed literals due before the step, then wait for its start
+            while state.next_due().is_some_and(|time| time <= start_time) {
+                state = state_space.process_scheduled_effects(&state);
+                trajectory.push(state.classical_state.clone());
+            }
+            state.time = state.time.max(start_time);
+
+            // The duration the plan chose, which need not be the action's nominal one
+            let duration = end_time - start_time;
+            let started = state_space.apply_action_with_duration(&state, action_idx, start_time, duration);
+            trajectory.push(started.classical_state);
+            state = state_space.apply_action_to_completion_with_duration(&state, action_idx, duration)?;
+            trajectory.push(state.classical_state.clone());
+        }
+
+        let violated: Vec<String> = task.always_constraints.iter()
+            .filter(|constraint| trajectory.iter().any(|s| !state_space.check_condition(constraint, s)))
+            .map(|constraint| format!("(always {})", constraint))
+            .collect();
+        if violated.is_empty() {
+            return None;
+        }
+
+        Some(format!(
+            "goal is reachable only by violating trajectory constraints: {}",
+            violated.join(", ")
+        ))
     }
 
-    fn process_scheduled_effects(&self, state: &TemporalState) -> TemporalState {
-        let mut new_state = state.clone();
-        
-        // Find next time point
-        let next_time = new_state.scheduled_effects
-            .iter()
-            .map(|e| e.time)
-            .min_by(|a, b| a.partial_cmp(b).unwrap())
-            .unwrap_or(new_state.time);
-
-        // Advance time
-        new_state.time = next_time;
-
-        // Apply effects scheduled for this time
-        let mut remaining_effects = Vec::new();
-        for effect in new_state.scheduled_effects {
-            if effect.time <= next_time {
-                // Apply effect
-                // TODO: Apply scheduled effect to state
-                // For now, just consume the effect
-            } else {
-                remaining_effects.push(effect);
+    /// Heuristic value of `state`, looked up in `cache` first when caching is enabled
+    fn evaluate(
+        &self,
+        state: &TemporalState,
+        task: &TemporalTask,
+        cache: &mut HashMap<State, f64>,
+        stats: &mut SearchStats,
+    ) -> f64 {
+        if !self.cache_heuristic {
+            return self.heuristic.compute(state, task);
+        }
+        if let Some(&h_value) = cache.get(&state.classical_state) {
+            stats.heuristic_cache_hits += 1;
+            return h_value;
+        }
+        stats.heuristic_cache_misses += 1;
+        let h_value = self.heuristic.compute(state, task);
+        cache.insert(state.classical_state.clone(), h_value);
+        h_value
+    }
+
+    /// Admissible estimate of the time still needed from `state`: pending effects must be
+    /// reached, and an unsatisfied goal needs at least one more action of `min_duration`
+    fn remaining_time_bound(state_space: &StateSpace, state: &TemporalState, min_duration: f64) -> f64 {
+        let pending = state.actions_finish() - state.time;
+        let next_action = if state_space.is_goal(state) { 0.0 } else { min_duration };
+        pending.max(next_action)
+    }
+
+    /// Shortest duration any ground action can take in any state, for `remaining_time_bound`.
+    /// Durations reading a fluent some action changes cannot be bounded and count as 0, and so
+    /// does everything when a timed literal may reach the goal without another action.
+    fn min_step_duration(task: &TemporalTask) -> f64 {
+        if !task.timed_literals.is_empty() {
+            return 0.0;
+        }
+        let mut static_values = task.initial_state.numeric_values.clone();
+        for action in &task.ground_actions {
+            let conditional = action.conditional_effects_start.iter()
+                .chain(&action.conditional_effects_end)
+                .flat_map(|conditional| &conditional.numeric_effects);
+            for effect in action.numeric_effects_start.iter().chain(&action.numeric_effects_end).chain(conditional) {
+                static_values.remove(&effect.function.key());
             }
         }
-        
-        new_state.scheduled_effects = remaining_effects;
-        new_state
+        task.ground_actions.iter()
+            .map(|action| action.duration_spec.bounds(&static_values).map_or(0.0, |(min, _)| min.max(0.0)))
+            .min_by(f64::total_cmp)
+            .unwrap_or(0.0)
+    }
+
+    /// The plan to node `goal` of `nodes`, with steps that do not interfere scheduled in parallel
+    fn extract_plan(&self, task: &TemporalTask, nodes: &[SearchNode], goal: NodeId) -> SearchResult {
+        SearchResult::Solution(schedule_plan_with_separation(task, &Self::plan_to(nodes, goal), self.separation))
+    }
+
+    /// End of `action_idx` taking `duration` after node `node` of `nodes`, started as soon as
+    /// every earlier step it interferes with has ended, plus `separation`, as
+    /// `schedule_plan_with_separation` would place it
+    fn parallel_step_end(
+        nodes: &[SearchNode],
+        node: NodeId,
+        action_idx: usize,
+        duration: f64,
+        footprints: &[Footprint],
+        separation: f64,
+    ) -> f64 {
+        let mut start: f64 = 0.0;
+        let mut current = Some(node);
+        while let Some(ancestor) = current.map(|id| &nodes[id]) {
+            if ancestor.action_idx.is_some_and(|previous| footprints[previous].interferes(&footprints[action_idx])) {
+                start = start.max(ancestor.step_end + separation);
+            }
+            current = ancestor.parent;
+        }
+        start + duration
     }
 
-    fn extract_plan(&self, goal_node: &SearchNode) -> SearchResult {
+    /// Whether node `id` of `nodes` is in the same state as one of its ancestors
+    fn revisits_ancestor(nodes: &[SearchNode], id: NodeId) -> bool {
+        let mut current = nodes[id].parent;
+        while let Some(ancestor) = current.map(|ancestor| &nodes[ancestor]) {
+            if ancestor.state == nodes[id].state {
+                return true;
+            }
+            current = ancestor.parent;
+        }
+        false
+    }
+
+    /// The actions on the path from the initial state to node `goal` of `nodes`, one after another
+    fn plan_to(nodes: &[SearchNode], goal: NodeId) -> Plan {
         let mut plan = Vec::new();
-        let mut current = Some(goal_node);
+        let mut current = Some(&nodes[goal]);
 
         while let Some(node) = current {
-            if let Some(action_idx) = node.action_idx {
-                plan.push((action_idx, node.state.time));
+            current = node.parent.map(|parent| &nodes[parent]);
+            // Each action starts when the state it was applied in was reached and runs until
+            // the state it leads to
+            if let (Some(action_idx), Some(parent)) = (node.action_idx, current) {
+                plan.push((action_idx, parent.time, node.time));
             }
-            current = node.parent.as_ref().map(|p| p.as_ref());
         }
 
         plan.reverse();
         
-        SearchResult::Solution(Plan {
-            actions: plan.into_iter().map(|(idx, _)| idx).collect(),
-            cost: goal_node.g_value,
-        })
+        Plan {
+            actions: plan.iter().map(|&(idx, _, _)| idx).collect(),
+            start_times: plan.iter().map(|&(_, start, _)| start).collect(),
+            end_times: plan.iter().map(|&(_, _, end)| end).collect(),
+            cost: nodes[goal].g_value,
+            sequential_cost: nodes[goal].time,
+            optimality: Optimality::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Admissible but inconsistent: claims 5 at `a`, whose true distance to the goal is 7
+    struct OverestimateAtA;
+
+    impl TemporalHeuristic for OverestimateAtA {
+        fn compute(&self, state: &TemporalState, task: &TemporalTask) -> f64 {
+            let at_a = task.fact_id("at", &["a".to_string()]).unwrap();
+            if state.classical_state.facts[at_a] { 5.0 } else { 0.0 }
+        }
+    }
+
+    #[test]
+    fn test_cheaper_path_reopens_closed_state() {
+        let domain = r#"
+(define (domain roads)
+  (:requirements :durative-actions :numeric-fluents)
+  (:predicates (at ?x) (road ?from ?to))
+  (:functions (length ?from ?to))
+  (:durative-action drive
+    :parameters (?from ?to)
+    :duration (= ?duration (length ?from ?to))
+    :condition (at start (and (at ?from) (road ?from ?to)))
+    :effect (and (at start (not (at ?from))) (at end (at ?to)))))
+"#;
+        // s-a-c-g costs 1 + 1 + 6 = 8, but c is first reached through b at g = 5
+        let problem = r#"
+(define (problem roads-problem)
+  (:domain roads)
+  (:objects s a b c g)
+  (:init (at s)
+         (road s a) (= (length s a) 1)
+         (road a c) (= (length a c) 1)
+         (road s b) (= (length s b) 4)
+         (road b c) (= (length b c) 1)
+         (road c g) (= (length c g) 6))
+  (:goal (at g)))
+"#;
+        let task = TemporalTask::from_pddl(domain, problem);
+        let mut search_engine = TemporalAStarSearch::new().with_heuristic(Box::new(OverestimateAtA));
+
+        match search_engine.search(&task) {
+            SearchResult::Solution(plan) => {
+                assert_eq!(plan.cost, 8.0);
+                let route: Vec<&str> = plan.actions.iter().map(|&idx| task.ground_actions[idx].name.as_str()).collect();
+                assert_eq!(route, vec!["drive s a", "drive a c", "drive c g"]);
+            }
+            other => panic!("Expected a plan, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_long_plan_is_extracted_from_the_node_arena() {
+        let domain = r#"
+(define (domain corridor)
+  (:requirements :durative-actions)
+  (:predicates (at ?x) (next ?from ?to))
+  (:durative-action step
+    :parameters (?from ?to)
+    :duration (= ?duration 1)
+    :condition (at start (and (at ?from) (next ?from ?to)))
+    :effect (and (at start (not (at ?from))) (at end (at ?to)))))
+"#;
+        let cells: Vec<String> = (0..=20).map(|i| format!("c{}", i)).collect();
+        let links: Vec<String> = cells.windows(2).map(|pair| format!("(next {} {})", pair[0], pair[1])).collect();
+        let problem = format!(
+            "(define (problem corridor-20) (:domain corridor) (:objects {}) (:init (at c0) {}) (:goal (at c20)))",
+            cells.join(" "),
+            links.join(" ")
+        );
+        let task = TemporalTask::from_pddl(domain, &problem);
+        let mut search_engine = TemporalAStarSearch::new().with_separation(0.0);
+
+        let SearchResult::Solution(plan) = search_engine.search(&task) else {
+            panic!("Expected a plan through the corridor");
+        };
+        let steps: Vec<&str> = plan.actions.iter().map(|&idx| task.ground_actions[idx].name.as_str()).collect();
+        let expected: Vec<String> = cells.windows(2).map(|pair| format!("step {} {}", pair[0], pair[1])).collect();
+        assert_eq!(steps, expected);
+        assert_eq!(plan.start_times, (0..20).map(f64::from).collect::<Vec<_>>());
+        assert_eq!(plan.end_times, (1..=20).map(f64::from).collect::<Vec<_>>());
+
+        // Every generated node is stored once, however deep it lies
+        let frontier = search_engine.frontier.as_ref().expect("A solved search keeps its frontier");
+        assert_eq!(frontier.nodes.len(), search_engine.stats().nodes_generated);
+        assert!(frontier.nodes.iter().enumerate().all(|(id, node)| node.parent.is_none_or(|parent| parent < id)));
+    }
+
+    /// Admissible but weak: half the number of unmet goals, each of which takes one time unit
+    struct HalfUnmetGoals;
+
+    impl TemporalHeuristic for HalfUnmetGoals {
+        fn compute(&self, state: &TemporalState, task: &TemporalTask) -> f64 {
+            let unmet = task.goal_conditions.len() - task.satisfied_goal_count(&state.classical_state);
+            0.5 * unmet as f64
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_weight_reduces_expansions() {
+        let domain = r#"
+(define (domain chores)
+  (:requirements :durative-actions)
+  (:predicates (done ?t) (idle ?t))
+  (:durative-action work
+    :parameters (?t)
+    :duration (= ?duration 1)
+    :condition (and)
+    :effect (at end (done ?t)))
+  (:durative-action dawdle
+    :parameters (?t)
+    :duration (= ?duration 1)
+    :condition (and)
+    :effect (at end (idle ?t))))
+"#;
+        let problem = r#"
+(define (problem chores-problem)
+  (:domain chores)
+  (:objects t1 t2 t3 t4)
+  (:init)
+  (:goal (and (done t1) (done t2) (done t3) (done t4))))
+"#;
+        let task = TemporalTask::from_pddl(domain, problem);
+        let solve = |weight: f64| {
+            let mut search_engine = TemporalAStarSearch::new()
+                .with_heuristic(Box::new(HalfUnmetGoals))
+                .with_weight(weight);
+            let SearchResult::Solution(plan) = search_engine.search(&task) else {
+                panic!("Expected a plan with weight {}", weight);
+            };
+            (plan.cost, search_engine.stats().nodes_expanded)
+        };
+
+        let (plain_cost, plain_expanded) = solve(1.0);
+        let (weighted_cost, weighted_expanded) = solve(2.0);
+        assert_eq!(plain_cost, 4.0);
+        assert_eq!(weighted_cost, 4.0);
+        assert!(
+            weighted_expanded < plain_expanded,
+            "w = 2 expanded {} nodes, plain A* {}",
+            weighted_expanded,
+            plain_expanded
+        );
+    }
+
+    #[test]
+    fn test_horizon_keeps_the_earlier_copy_of_a_state_open() {
+        let domain = r#"
+(define (domain prep)
+  (:requirements :durative-actions)
+  (:predicates (half) (ready) (step) (done))
+  (:durative-action slow-prep
+    :parameters ()
+    :duration (= ?duration 4)
+    :condition (and)
+    :effect (at end (ready)))
+  (:durative-action quick-start
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (and)
+    :effect (at end (half)))
+  (:durative-action quick-finish
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (at start (half))
+    :effect (and (at end (not (half))) (at end (ready))))
+  (:durative-action first-step
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (at start (ready))
+    :effect (at end (step)))
+  (:durative-action second-step
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (at start (step))
+    :effect (at end (done))))
+"#;
+        let problem = r#"
+(define (problem prep-problem)
+  (:domain prep)
+  (:init)
+  (:goal (done)))
+"#;
+        // slow-prep reaches (ready) with one action at time 4, too late to finish by 5; the
+        // two quick actions reach the same state at time 2 but cost more, so it is found second
+        let task = TemporalTask::from_pddl(domain, problem);
+        let mut search_engine = TemporalAStarSearch::new()
+            .with_cost_model(CostModel::ActionCount)
+            .with_max_makespan(5.0);
+        let SearchResult::Solution(plan) = search_engine.search(&task) else {
+            panic!("Expected a plan within the horizon");
+        };
+        assert_eq!(plan.cost, 4.0);
+        assert!(plan.makespan() <= 5.0 + TIME_EPSILON);
+    }
+
+    #[test]
+    fn test_zero_weight_searches_by_cost_alone() {
+        // Breaking the key makes the goal unreachable, so FF rates that successor infinite
+        let domain = r#"
+(define (domain locked)
+  (:requirements :durative-actions)
+  (:predicates (has-key) (open))
+  (:durative-action unlock
+    :parameters ()
+    :duration (= ?duration 2)
+    :condition (at start (has-key))
+    :effect (at end (open)))
+  (:durative-action break-key
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (at start (has-key))
+    :effect (at end (not (has-key)))))
+"#;
+        let problem = r#"
+(define (problem locked-problem)
+  (:domain locked)
+  (:init (has-key))
+  (:goal (open)))
+"#;
+        let task = TemporalTask::from_pddl(domain, problem);
+        let mut search_engine = TemporalAStarSearch::new().with_weight(0.0);
+        let SearchResult::Solution(plan) = search_engine.search(&task) else {
+            panic!("Expected a plan with w = 0");
+        };
+        assert_eq!(plan.cost, 2.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "heuristic weight must be finite and non-negative")]
+    fn test_nan_weight_is_rejected() {
+        let _ = TemporalAStarSearch::new().with_weight(f64::NAN);
+    }
+
+    #[test]
+    fn test_snap_times_keeps_step_order() {
+        let plan = Plan {
+            actions: vec![0, 1, 2, 3],
+            start_times: vec![0.0, 1.2, 1.2, 2.9],
+            end_times: vec![1.2, 2.9, 2.0, 4.0],
+            cost: 4.0,
+            sequential_cost: 4.6,
+            optimality: Optimality::default(),
+        };
+        let snapped = plan.snap_times(0.5).expect("Snapping to 0.5 keeps every step in order");
+        assert_eq!(snapped.start_times, vec![0.0, 1.0, 1.0, 3.0]);
+        assert!((snapped.end_times[3] - 4.1).abs() < 1e-9, "Steps keep their durations");
+        assert_eq!(snapped.actions, plan.actions);
+
+        let crowded = Plan {
+            start_times: vec![0.0, 0.1, 1.2, 2.9],
+            ..plan
+        };
+        assert!(crowded.snap_times(0.5).is_none(), "0.0 and 0.1 would both snap to 0.0");
+    }
+
+    #[test]
+    fn test_snap_times_rejects_invalid_grids() {
+        let plan = Plan {
+            actions: vec![0, 1],
+            start_times: vec![0.0, 1.0],
+            end_times: vec![1.0, 2.0],
+            cost: 2.0,
+            sequential_cost: 2.0,
+            optimality: Optimality::default(),
+        };
+        for grid in [0.0, -0.5, f64::NAN, f64::INFINITY] {
+            assert!(plan.snap_times(grid).is_none(), "grid {} should be rejected", grid);
+        }
+        assert!(plan.snap_times(1.0).is_some());
+    }
+}
@@ -0,0 +1,130 @@
+// S-expression reader for PDDL source, keeping the position of every token
+use std::fmt;
+
+/// 1-based line and column of a token in the source text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Span {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SExpr {
+    Atom { text: String, span: Span },
+    List { items: Vec<SExpr>, span: Span },
+}
+
+impl SExpr {
+    pub fn span(&self) -> Span {
+        match self {
+            SExpr::Atom { span, .. } | SExpr::List { span, .. } => *span,
+        }
+    }
+
+    pub fn as_atom(&self) -> Option<&str> {
+        match self {
+            SExpr::Atom { text, .. } => Some(text),
+            SExpr::List { .. } => None,
+        }
+    }
+
+    /// Children of a list; empty for an atom
+    pub fn items(&self) -> &[SExpr] {
+        match self {
+            SExpr::Atom { .. } => &[],
+            SExpr::List { items, .. } => items,
+        }
+    }
+
+    /// Leading atom of a list, e.g. `and` in `(and ...)`
+    pub fn head(&self) -> Option<&str> {
+        self.items().first().and_then(SExpr::as_atom)
+    }
+}
+
+impl fmt::Display for SExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SExpr::Atom { text, .. } => write!(f, "{}", text),
+            SExpr::List { items, .. } => {
+                write!(f, "(")?;
+                for (idx, item) in items.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReadError {
+    /// A `)` with no matching `(`
+    UnmatchedClose(Span),
+    /// A `(` that is never closed
+    Unclosed(Span),
+}
+
+/// Read every top-level expression in `source`. A `;` removes the rest of its line before any
+/// token is formed, so commented-out blocks never reach the parser.
+pub(crate) fn read_all(source: &str) -> Result<Vec<SExpr>, ReadError> {
+    read(source, true)
+}
+
+/// Like `read_all`, but drops unmatched `)` and closes unclosed lists at the end of input
+pub(crate) fn read_lenient(source: &str) -> Vec<SExpr> {
+    read(source, false).unwrap_or_default()
+}
+
+fn read(source: &str, strict: bool) -> Result<Vec<SExpr>, ReadError> {
+    // Lists under construction, innermost last
+    let mut stack: Vec<(Span, Vec<SExpr>)> = Vec::new();
+    let mut top_level = Vec::new();
+    let mut atom: Option<(Span, String)> = None;
+
+    fn push(expr: SExpr, stack: &mut [(Span, Vec<SExpr>)], top_level: &mut Vec<SExpr>) {
+        match stack.last_mut() {
+            Some((_, items)) => items.push(expr),
+            None => top_level.push(expr),
+        }
+    }
+
+    for (line_idx, line) in source.lines().enumerate() {
+        let code = line.split(';').next().unwrap_or_default();
+        for (column_idx, ch) in code.chars().enumerate() {
+            let span = Span { line: line_idx + 1, column: column_idx + 1 };
+            if ch == '(' || ch == ')' || ch.is_whitespace() {
+                if let Some((atom_span, text)) = atom.take() {
+                    push(SExpr::Atom { text, span: atom_span }, &mut stack, &mut top_level);
+                }
+            }
+            match ch {
+                '(' => stack.push((span, Vec::new())),
+                ')' => match stack.pop() {
+                    Some((list_span, items)) => {
+                        push(SExpr::List { items, span: list_span }, &mut stack, &mut top_level);
+                    }
+                    None if strict => return Err(ReadError::UnmatchedClose(span)),
+                    None => {}
+                },
+                _ if ch.is_whitespace() => {}
+                _ => atom.get_or_insert_with(|| (span, String::new())).1.push(ch),
+            }
+        }
+        // Atoms never continue onto the next line
+        if let Some((atom_span, text)) = atom.take() {
+            push(SExpr::Atom { text, span: atom_span }, &mut stack, &mut top_level);
+        }
+    }
+
+    if let Some((span, _)) = stack.first().filter(|_| strict) {
+        return Err(ReadError::Unclosed(*span));
+    }
+    while let Some((list_span, items)) = stack.pop() {
+        push(SExpr::List { items, span: list_span }, &mut stack, &mut top_level);
+    }
+    Ok(top_level)
+}
@@ -1,89 +1,1418 @@
 // f:\common\Source_Code\TemporalFastDownward\rust\src\temporal_planner\state_space.rs
-use super::temporal_task::{TemporalTask, State, TemporalAction};
-use std::collections::HashMap;
+use super::compiled_task::{resolve_condition, resolve_effect, CompiledLiteral, CompiledTask};
+use super::temporal_task::{TemporalTask, State, Condition, ConditionalEffect, Effect, FactSet, NumericCondition, NumericEffect};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Clone)]
 pub struct TemporalState {
     pub classical_state: State,
     pub scheduled_effects: Vec<ScheduledEffect>,
+    pub scheduled_numeric_effects: Vec<ScheduledNumericEffect>,
+    /// Over-all conditions of the actions still running
+    pub invariants: Vec<Invariant>,
     pub time: f64,
 }
 
+/// Hashable identity of a `TemporalState` for duplicate detection.
+///
+/// Pending effects are recorded by how far in the future they are due rather than by absolute
+/// time: the task depends on the clock only through pending effects, timed initial literals
+/// included, so two states that differ only by a time shift have the same futures. Their costs
+/// to reach and their fit within a makespan horizon still differ, so the key alone only
+/// identifies duplicates when the search's g is the elapsed time and there is no horizon; A*
+/// adds the time to its closed list otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TemporalStateKey {
+    state: State,
+    pending: Vec<PendingKey>,
+}
+
+/// `(due offset in microseconds, action, what is pending)`; `None` for a timed initial literal
+type PendingKey = (i64, Option<usize>, Pending);
+
+/// What a pending entry of a state's key stands for. An action's end effects are fixed by the
+/// action, so a numeric one is told apart only by its guard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Pending {
+    Literal { fact: Option<u32>, negative: bool, guard: Option<usize> },
+    Numeric { guard: Option<usize> },
+    OverAll,
+}
+
+impl TemporalState {
+    pub fn key(&self) -> TemporalStateKey {
+        TemporalStateKey {
+            state: self.classical_state.clone(),
+            pending: self.pending_key(),
+        }
+    }
+
+    /// The pending part of `key`, sorted
+    fn pending_key(&self) -> Vec<PendingKey> {
+        let offset = |time: f64| ((time - self.time) * 1000000.0).round() as i64;
+        let mut pending: Vec<PendingKey> = self.scheduled_effects.iter()
+            .map(|scheduled| {
+                let CompiledLiteral { fact, negative } = scheduled.literal;
+                (offset(scheduled.time), scheduled.action_id, Pending::Literal { fact, negative, guard: scheduled.guard })
+            })
+            .chain(self.scheduled_numeric_effects.iter().map(|scheduled| {
+                (offset(scheduled.time), Some(scheduled.action_id), Pending::Numeric { guard: scheduled.guard })
+            }))
+            .chain(self.invariants.iter().map(|invariant| (offset(invariant.until), Some(invariant.action_id), Pending::OverAll)))
+            .collect();
+        pending.sort_unstable();
+        pending
+    }
+
+    /// Whether `self` and `other` have equal keys, without building them
+    fn same_key(&self, other: &TemporalState) -> bool {
+        self.classical_state == other.classical_state && self.pending_key() == other.pending_key()
+    }
+
+    /// Time of the earliest pending effect, if any
+    pub fn next_due(&self) -> Option<f64> {
+        self.scheduled_effects.iter()
+            .map(|scheduled| scheduled.time)
+            .chain(self.scheduled_numeric_effects.iter().map(|scheduled| scheduled.time))
+            .min_by(f64::total_cmp)
+    }
+
+    /// Time by which every running action has finished, or the state's own time when none
+    /// is; timed initial literals still to come do not count
+    pub fn actions_finish(&self) -> f64 {
+        self.scheduled_effects.iter()
+            .filter(|scheduled| scheduled.action_id.is_some())
+            .map(|scheduled| scheduled.time)
+            .chain(self.scheduled_numeric_effects.iter().map(|scheduled| scheduled.time))
+            .fold(self.time, f64::max)
+    }
+
+    /// Multi-line dump of the state for diagnosing a search: the clock, the atoms that hold,
+    /// fluent values, pending effects with the time they are due, and running over-all conditions
+    pub fn describe(&self, task: &TemporalTask) -> String {
+        let action_name = |action_id: Option<usize>| match action_id {
+            Some(action_id) => task.ground_actions[action_id].name.clone(),
+            None => "timed literal".to_string(),
+        };
+
+        let mut lines = vec![format!("time {}", self.time)];
+        let facts: Vec<String> = self.classical_state.facts.ones()
+            .map(|fact| format!("({})", task.fact_names[fact]))
+            .collect();
+        lines.push(format!("facts: {}", facts.join(" ")));
+        let mut fluents: Vec<(&String, &f64)> = self.classical_state.numeric_values.iter().collect();
+        fluents.sort_by(|a, b| a.0.cmp(b.0));
+        for (fluent, value) in fluents {
+            lines.push(format!("  ({}) = {}", fluent, value));
+        }
+
+        let mut pending: Vec<(f64, String)> = self.scheduled_effects.iter()
+            .map(|scheduled| {
+                let sign = if scheduled.effect.is_delete { "-" } else { "+" };
+                let guard = scheduled.guard.map(|guard| format!(" if conditional effect {} holds", guard)).unwrap_or_default();
+                let effect = format!("{}({}) from {}{}", sign, scheduled.effect.key(), action_name(scheduled.action_id), guard);
+                (scheduled.time, effect)
+            })
+            .chain(self.scheduled_numeric_effects.iter().map(|scheduled| {
+                let op = format!("{:?}", scheduled.effect.op).to_lowercase();
+                let effect = format!("{} ({}) from {}", op, scheduled.effect.function.key(), action_name(Some(scheduled.action_id)));
+                (scheduled.time, effect)
+            }))
+            .collect();
+        pending.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for (time, effect) in pending {
+            lines.push(format!("pending at {}: {}", time, effect));
+        }
+        for invariant in &self.invariants {
+            lines.push(format!("over all of {} until {}", action_name(Some(invariant.action_id)), invariant.until));
+        }
+        lines.join("\n")
+    }
+
+    /// The same state with its clock and every pending effect moved `by` time units
+    pub fn shifted(&self, by: f64) -> TemporalState {
+        let mut shifted = self.clone();
+        shifted.time += by;
+        for scheduled in &mut shifted.scheduled_effects {
+            scheduled.time += by;
+        }
+        for scheduled in &mut shifted.scheduled_numeric_effects {
+            scheduled.time += by;
+        }
+        for invariant in &mut shifted.invariants {
+            invariant.until += by;
+        }
+        shifted
+    }
+}
+
+/// Small integer standing for a state interned in a `StateRegistry`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StateId(u32);
+
+impl StateId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Interns `TemporalState`s by `TemporalState::key`, so that a search can hold and compare
+/// `StateId`s instead of cloning whole states. States that differ only by a time shift share
+/// an id; each is stored once, shifted to time zero, and `resolve` puts it back on a given
+/// clock.
+#[derive(Debug, Clone, Default)]
+pub struct StateRegistry {
+    /// Latest state registered with each key hash
+    ids: HashMap<u64, StateId>,
+    /// For each state, the one registered before it with the same key hash
+    same_hash: Vec<Option<StateId>>,
+    states: Vec<TemporalState>,
+}
+
+impl StateRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Id of `state`, registering it on first sighting
+    pub fn intern(&mut self, state: &TemporalState) -> StateId {
+        let mut hasher = DefaultHasher::new();
+        state.classical_state.hash(&mut hasher);
+        state.pending_key().hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let mut candidate = self.ids.get(&hash).copied();
+        while let Some(id) = candidate {
+            if self.states[id.index()].same_key(state) {
+                return id;
+            }
+            candidate = self.same_hash[id.index()];
+        }
+        let id = StateId(u32::try_from(self.states.len()).expect("more than u32::MAX states registered"));
+        self.states.push(state.shifted(-state.time));
+        self.same_hash.push(self.ids.insert(hash, id));
+        id
+    }
+
+    /// The state registered as `id`, with its clock at `time`
+    pub fn resolve(&self, id: StateId, time: f64) -> TemporalState {
+        self.states[id.index()].shifted(time)
+    }
+
+    /// Number of distinct states registered
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ScheduledEffect {
     pub time: f64,
-    pub effect: super::temporal_task::Effect,
+    pub effect: Effect,
+    /// `effect` resolved to its fact index when it was scheduled
+    pub literal: CompiledLiteral,
+    /// Action whose end effect this is; `None` for a timed initial literal
+    pub action_id: Option<usize>,
+    /// Index into the action's `conditional_effects_end` whose antecedent must hold when due
+    pub guard: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScheduledNumericEffect {
+    pub time: f64,
+    pub effect: NumericEffect,
     pub action_id: usize,
+    /// Index into the action's `conditional_effects_end` whose antecedent must hold when due
+    pub guard: Option<usize>,
 }
 
-pub struct StateSpace {
-    task: TemporalTask,
-    state_registry: HashMap<State, usize>,
+/// Obligation that the `over all` conditions of a running action hold until it ends at `until`.
+/// Effects due at `until` itself no longer have to respect them.
+#[derive(Debug, Clone)]
+pub struct Invariant {
+    pub action_id: usize,
+    pub until: f64,
+}
+
+/// Picks a list of compiled literals out of a `CompiledTask`
+type LiteralsOf<'f> = &'f dyn for<'a> Fn(&'a CompiledTask) -> &'a [CompiledLiteral];
+
+/// Ground actions whose boolean start conditions hold in one state. `StateSpace::enabled_actions`
+/// derives a successor's set from its parent's, rechecking only the actions whose start
+/// conditions mention a fact that changed in between.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnabledActions {
+    /// Facts of the state the set was computed for
+    facts: FactSet,
+    /// One bit per ground action
+    actions: FactSet,
 }
 
-impl StateSpace {
+impl EnabledActions {
+    /// Indices of the enabled actions, in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.actions.ones()
+    }
+
+    pub fn contains(&self, action_idx: usize) -> bool {
+        self.actions.contains(action_idx)
+    }
+
+    /// Number of enabled actions
+    pub fn len(&self) -> usize {
+        self.actions.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The boolean start conditions of every ground action resolved to fact indices, and for each
+/// fact the actions whose start conditions mention it: the only ones a change of that fact can
+/// enable or disable.
+struct SuccessorGenerator {
+    /// `None` for an action with a positive start condition outside the fact table, which can
+    /// never start
+    start_conditions: Vec<Option<Vec<CompiledLiteral>>>,
+    /// Actions whose start conditions mention each fact, in index order
+    mentioned_by: Vec<Vec<usize>>,
+}
+
+impl SuccessorGenerator {
+    fn new(task: &TemporalTask) -> Self {
+        let mut mentioned_by = vec![Vec::new(); task.fact_names.len()];
+        let start_conditions = task.ground_actions.iter().enumerate()
+            .map(|(action_idx, action)| {
+                let literals: Vec<CompiledLiteral> = action.conditions_start.iter()
+                    .map(|condition| resolve_condition(task, condition))
+                    .collect();
+                // A literal without a fact holds exactly when it is negative
+                if literals.iter().any(|literal| literal.fact.is_none() && !literal.negative) {
+                    return None;
+                }
+                for fact in literals.iter().filter_map(|literal| literal.fact) {
+                    let actions: &mut Vec<usize> = &mut mentioned_by[fact as usize];
+                    if actions.last() != Some(&action_idx) {
+                        actions.push(action_idx);
+                    }
+                }
+                Some(literals)
+            })
+            .collect();
+        Self { start_conditions, mentioned_by }
+    }
+
+    fn is_enabled(&self, action_idx: usize, facts: &FactSet) -> bool {
+        self.start_conditions[action_idx].as_ref()
+            .is_some_and(|literals| literals.iter().all(|literal| literal.holds(facts)))
+    }
+
+    /// Enabled actions where `facts` hold, checking every action
+    fn scan(&self, facts: &FactSet) -> EnabledActions {
+        EnabledActions {
+            facts: facts.clone(),
+            actions: (0..self.start_conditions.len()).map(|idx| self.is_enabled(idx, facts)).collect(),
+        }
+    }
+
+    /// `parent` updated for `facts`, rechecking the actions that mention a fact whose truth
+    /// differs
+    fn update(&self, parent: &EnabledActions, facts: &FactSet) -> EnabledActions {
+        let mut actions = parent.actions.clone();
+        for fact in parent.facts.differences(facts) {
+            for &action_idx in &self.mentioned_by[fact] {
+                actions.set(action_idx, self.is_enabled(action_idx, facts));
+            }
+        }
+        EnabledActions { facts: facts.clone(), actions }
+    }
+}
+
+/// The over-all conditions of one ground action and the atoms and fluents its effects change,
+/// resolved once per task for the checks against running actions. Conditional effects count
+/// as if they fire.
+struct Interference {
+    over_all: Vec<CompiledLiteral>,
+    /// Fluents the numeric over-all conditions read, sorted; all fluent lists index one table
+    /// built with the state space
+    over_all_fluents: Vec<usize>,
+    start_effects: Vec<CompiledLiteral>,
+    end_effects: Vec<CompiledLiteral>,
+    /// Fluents the numeric start effects assign, sorted
+    start_fluents: Vec<usize>,
+    /// Fluents the numeric end effects assign, sorted
+    end_fluents: Vec<usize>,
+}
+
+impl Interference {
+    /// One entry per ground action of `task`
+    fn for_task(task: &TemporalTask) -> Vec<Self> {
+        let mut fluent_ids: HashMap<String, usize> = HashMap::new();
+        let mut intern = |keys: HashSet<String>| -> Vec<usize> {
+            let mut ids: Vec<usize> = keys.into_iter()
+                .map(|key| {
+                    let next = fluent_ids.len();
+                    *fluent_ids.entry(key).or_insert(next)
+                })
+                .collect();
+            ids.sort_unstable();
+            ids
+        };
+        let literals = |effects: &[Effect], conditional: &[ConditionalEffect]| -> Vec<CompiledLiteral> {
+            effects.iter()
+                .chain(conditional.iter().flat_map(|conditional| &conditional.effects))
+                .map(|effect| resolve_effect(task, effect))
+                .collect()
+        };
+        let assigned = |effects: &[NumericEffect], conditional: &[ConditionalEffect]| -> HashSet<String> {
+            effects.iter()
+                .chain(conditional.iter().flat_map(|conditional| &conditional.numeric_effects))
+                .map(|effect| effect.function.key())
+                .collect()
+        };
+        task.ground_actions.iter()
+            .map(|action| {
+                let mut read = HashSet::new();
+                for condition in &action.numeric_conditions_over_all {
+                    condition.collect_fluents(&mut read);
+                }
+                Self {
+                    over_all: action.conditions_over_all.iter()
+                        .map(|condition| resolve_condition(task, condition))
+                        .collect(),
+                    over_all_fluents: intern(read),
+                    start_effects: literals(&action.effects_start, &action.conditional_effects_start),
+                    end_effects: literals(&action.effects_end, &action.conditional_effects_end),
+                    start_fluents: intern(assigned(&action.numeric_effects_start, &action.conditional_effects_start)),
+                    end_fluents: intern(assigned(&action.numeric_effects_end, &action.conditional_effects_end)),
+                }
+            })
+            .collect()
+    }
+
+    /// Whether one of `effects` falsifies an over-all condition, or one of `fluents` is read by
+    /// a numeric one, whatever value it is given
+    fn broken_by(&self, effects: &[CompiledLiteral], fluents: &[usize]) -> bool {
+        effects.iter().any(|effect| {
+            effect.fact.is_some() && self.over_all.iter()
+                .any(|condition| effect.fact == condition.fact && effect.negative != condition.negative)
+        }) || fluents.iter().any(|fluent| self.over_all_fluents.binary_search(fluent).is_ok())
+    }
+}
+
+/// The states reachable in a task and the transitions between them. It owns the task when made
+/// with `new`, and borrows it, with its compiled form, when made with `from_compiled`.
+pub struct StateSpace<'t> {
+    task: Cow<'t, TemporalTask>,
+    /// Index-based form of `task`; when present, boolean conditions and effects are read from it
+    /// instead of being looked up by atom name
+    compiled: Option<&'t CompiledTask>,
+    successors: SuccessorGenerator,
+    /// Parallel to `task.ground_actions`
+    interference: Vec<Interference>,
+}
+
+impl StateSpace<'static> {
     pub fn new(task: TemporalTask) -> Self {
         Self {
-            task,
-            state_registry: HashMap::new(),
+            successors: SuccessorGenerator::new(&task),
+            interference: Interference::for_task(&task),
+            task: Cow::Owned(task),
+            compiled: None,
         }
     }
+}
 
+impl<'t> StateSpace<'t> {
+    /// State space over `task` that checks conditions through `compiled`, which must have been
+    /// built from the same task; neither is copied
+    pub fn from_compiled(task: &'t TemporalTask, compiled: &'t CompiledTask) -> Self {
+        Self {
+            successors: SuccessorGenerator::new(task),
+            interference: Interference::for_task(task),
+            task: Cow::Borrowed(task),
+            compiled: Some(compiled),
+        }
+    }
+
+    /// The task's initial state at time 0, with its timed initial literals pending
+    pub fn initial_state(&self) -> TemporalState {
+        TemporalState {
+            classical_state: self.task.initial_state.clone(),
+            scheduled_effects: self.task.timed_literals.iter()
+                .map(|literal| ScheduledEffect {
+                    time: literal.time,
+                    effect: literal.effect.clone(),
+                    literal: resolve_effect(&self.task, &literal.effect),
+                    action_id: None,
+                    guard: None,
+                })
+                .collect(),
+            scheduled_numeric_effects: Vec::new(),
+            invariants: Vec::new(),
+            time: 0.0,
+        }
+    }
+
+    /// Actions whose boolean start conditions hold in `state`. Given `parent`, the set of a state
+    /// `state` was reached from, only the actions whose start conditions mention a fact that
+    /// changed since are rechecked; without one every action is.
+    pub fn enabled_actions(&self, state: &TemporalState, parent: Option<&EnabledActions>) -> EnabledActions {
+        let facts = &state.classical_state.facts;
+        match parent {
+            Some(parent) => self.successors.update(parent, facts),
+            None => self.successors.scan(facts),
+        }
+    }
+
+    /// Actions that can start in `state`, with the time they would start, in index order
     pub fn get_applicable_actions(&self, state: &TemporalState) -> Vec<(usize, f64)> {
-        let mut applicable = Vec::new();
-        
-        for (idx, action) in self.task.actions.iter().enumerate() {
-            if self.is_applicable(action, state) {
-                applicable.push((idx, state.time));
-            }
+        self.applicable_actions(state, &self.enabled_actions(state, None))
+    }
+
+    /// `get_applicable_actions` from the `enabled_actions` of `state`, checking the rest of
+    /// each enabled action's applicability
+    pub fn applicable_actions(&self, state: &TemporalState, enabled: &EnabledActions) -> Vec<(usize, f64)> {
+        debug_assert!(enabled.facts == state.classical_state.facts, "enabled actions of another state");
+        enabled.iter()
+            .filter(|&idx| self.can_start(idx, state))
+            .map(|idx| (idx, state.time))
+            .collect()
+    }
+
+    /// Whether an action whose boolean start conditions hold in `state` can start there: its
+    /// numeric start conditions hold, its duration is defined, and some duration in its range
+    /// conflicts with no running action
+    fn can_start(&self, action_idx: usize, state: &TemporalState) -> bool {
+        let action = &self.task.ground_actions[action_idx];
+        let numeric_values = &state.classical_state.numeric_values;
+        if !action.numeric_conditions_start.iter().all(|condition| condition.is_satisfied(numeric_values)) {
+            return false;
         }
-        
-        applicable
+
+        // Durations over undefined fluents leave the action inapplicable
+        let Some((min, max)) = action.duration_spec.bounds(numeric_values) else {
+            return false;
+        };
+
+        self.conflict_free_ends(action_idx, state, state.time)
+            .is_some_and(|(earliest, latest)| earliest.max(state.time + min) <= latest.min(state.time + max))
     }
 
-    fn is_applicable(&self, action: &TemporalAction, state: &TemporalState) -> bool {
-        // Check start conditions
-        for condition in &action.conditions_start {
-            if !self.check_condition(condition, &state.classical_state) {
-                return false;
+    /// Earliest and latest time at which `action_idx`, started at `start_time` in `state`, may
+    /// end without breaking an over-all condition, its own or that of an action still running;
+    /// `None` when its start effects break one whenever it ends.
+    ///
+    /// An action counts as running while it has effects scheduled or an over-all condition in
+    /// `state.invariants`. The check is conservative: guarded effects are assumed to fire, and a
+    /// numeric effect breaks a numeric over-all condition whenever it assigns a fluent the
+    /// condition reads, whatever the value.
+    fn conflict_free_ends(&self, action_idx: usize, state: &TemporalState, start_time: f64) -> Option<(f64, f64)> {
+        let own = &self.interference[action_idx];
+        let mut earliest = start_time;
+        let mut latest = f64::INFINITY;
+
+        // Pending effects that break its over-all conditions must not come before it ends
+        for scheduled in &state.scheduled_effects {
+            if own.broken_by(std::slice::from_ref(&scheduled.literal), &[]) {
+                latest = latest.min(scheduled.time);
             }
         }
-        
-        // Check mutex constraints
-        // ...existing code...
-        
-        true
+        for scheduled in &state.scheduled_numeric_effects {
+            if own.broken_by(&[], &self.interference[scheduled.action_id].end_fluents) {
+                latest = latest.min(scheduled.time);
+            }
+        }
+
+        // Its own effects must leave the over-all conditions of running actions alone; end
+        // effects only matter if they come before the running action has finished
+        let mut running: HashMap<usize, f64> = HashMap::new();
+        let ends = state.scheduled_effects.iter()
+            .filter_map(|scheduled| Some((scheduled.action_id?, scheduled.time)))
+            .chain(state.invariants.iter().map(|invariant| (invariant.action_id, invariant.until)));
+        for (running_idx, until) in ends {
+            let finish = running.entry(running_idx).or_insert(until);
+            *finish = finish.max(until);
+        }
+        for (running_idx, finish) in running {
+            let required = &self.interference[running_idx];
+            if required.broken_by(&own.start_effects, &own.start_fluents) {
+                return None;
+            }
+            if required.broken_by(&own.end_effects, &own.end_fluents) {
+                earliest = earliest.max(finish);
+            }
+        }
+
+        Some((earliest, latest))
+    }
+
+    /// Whether `condition` holds in `state`; atoms outside the task's fact table are false
+    pub fn check_condition(&self, condition: &Condition, state: &State) -> bool {
+        resolve_condition(&self.task, condition).holds(&state.facts)
+    }
+
+    /// Whether all conditions hold; the boolean ones are read from the compiled task through
+    /// `compiled` when there is one
+    fn satisfies(
+        &self,
+        conditions: &[Condition],
+        compiled: LiteralsOf,
+        numeric_conditions: &[NumericCondition],
+        state: &State,
+    ) -> bool {
+        let facts_hold = match self.compiled {
+            Some(task) => compiled(task).iter().all(|literal| literal.holds(&state.facts)),
+            None => conditions.iter().all(|condition| self.check_condition(condition, state)),
+        };
+        facts_hold && numeric_conditions.iter().all(|condition| condition.is_satisfied(&state.numeric_values))
+    }
+
+    /// Whether every `always` constraint of the task holds in `state`
+    pub fn satisfies_constraints(&self, state: &State) -> bool {
+        self.satisfies(&self.task.always_constraints, &|compiled| &compiled.always_constraints, &[], state)
+    }
+
+    /// Goal conditions hold and no action is still running; timed initial literals still to
+    /// come do not matter
+    pub fn is_goal(&self, state: &TemporalState) -> bool {
+        state.scheduled_effects.iter().all(|scheduled| scheduled.action_id.is_none())
+            && state.scheduled_numeric_effects.is_empty()
+            && self.satisfies(
+                &self.task.goal_conditions,
+                &|compiled| &compiled.goal_conditions,
+                &self.task.numeric_goal_conditions,
+                &state.classical_state,
+            )
+    }
+
+    /// `TemporalTask::satisfied_goal_count`, with the boolean goals read from the compiled task
+    /// when there is one
+    pub fn satisfied_goal_count(&self, state: &State) -> usize {
+        let Some(compiled) = self.compiled else {
+            return self.task.satisfied_goal_count(state);
+        };
+        let facts = compiled.goal_conditions.iter().filter(|literal| literal.holds(&state.facts)).count();
+        let numeric = self.task.numeric_goal_conditions.iter()
+            .filter(|condition| condition.is_satisfied(&state.numeric_values))
+            .count();
+        facts + numeric
     }
 
-    fn check_condition(&self, _condition: &super::temporal_task::Condition, _state: &State) -> bool {
-        // Check if condition is satisfied in state
-        // TODO: Implement condition checking
-        true  // Assume all conditions are satisfied for now
+    /// Nominal duration of a ground action started in `state`, evaluated before its start effects
+    pub fn action_duration(&self, action_idx: usize, state: &State) -> f64 {
+        let action = &self.task.ground_actions[action_idx];
+        action.duration_spec.evaluate(&state.numeric_values).unwrap_or(action.duration)
+    }
+
+    /// `effects` resolved to fact indices, taken from the compiled task when there is one
+    fn effect_literals(&self, effects: &[Effect], compiled: LiteralsOf) -> Vec<CompiledLiteral> {
+        match self.compiled {
+            Some(task) => compiled(task).to_vec(),
+            None => effects.iter().map(|effect| resolve_effect(&self.task, effect)).collect(),
+        }
     }
 
     pub fn apply_action(&self, state: &TemporalState, action_idx: usize, start_time: f64) -> TemporalState {
-        let action = &self.task.actions[action_idx];
+        self.apply_action_with_duration(state, action_idx, start_time, self.action_duration(action_idx, &state.classical_state))
+    }
+
+    /// As `apply_action`, with the action running for `duration`, e.g. one of
+    /// `DurationSpec::choices`
+    pub fn apply_action_with_duration(&self, state: &TemporalState, action_idx: usize, start_time: f64, duration: f64) -> TemporalState {
+        let action = &self.task.ground_actions[action_idx];
+        let end_time = start_time + duration;
         let mut new_state = state.clone();
         
-        // Apply start effects immediately
-        for effect in &action.effects_start {
-            self.apply_effect(&mut new_state.classical_state, effect);
+        // Apply start effects immediately, with conditional ones whose antecedent holds beforehand
+        let mut effects = self.effect_literals(&action.effects_start, &|compiled| &compiled.actions[action_idx].effects_start);
+        let mut numeric_effects = action.numeric_effects_start.clone();
+        for (index, conditional) in action.conditional_effects_start.iter().enumerate() {
+            let antecedent: LiteralsOf = &|compiled| &compiled.actions[action_idx].conditional_effects_start[index].conditions;
+            if self.satisfies(&conditional.conditions, antecedent, &conditional.numeric_conditions, &state.classical_state) {
+                effects.extend(self.effect_literals(
+                    &conditional.effects,
+                    &|compiled| &compiled.actions[action_idx].conditional_effects_start[index].effects,
+                ));
+                numeric_effects.extend(conditional.numeric_effects.iter().cloned());
+            }
         }
+        Self::apply_effects(&mut new_state.classical_state, &effects);
+        self.apply_numeric_effects(&mut new_state.classical_state, &numeric_effects);
+        self.apply_axioms(&mut new_state.classical_state);
         
-        // Schedule end effects
-        for effect in &action.effects_end {
+        // Schedule end effects; conditional ones keep a guard checked once they are due
+        let end_literals = self.effect_literals(&action.effects_end, &|compiled| &compiled.actions[action_idx].effects_end);
+        let end_effects = action.effects_end.iter()
+            .zip(end_literals)
+            .map(|(effect, literal)| (effect, literal, None))
+            .chain(action.conditional_effects_end.iter().enumerate().flat_map(|(guard, conditional)| {
+                let literals = self.effect_literals(
+                    &conditional.effects,
+                    &|compiled| &compiled.actions[action_idx].conditional_effects_end[guard].effects,
+                );
+                conditional.effects.iter().zip(literals).map(move |(effect, literal)| (effect, literal, Some(guard)))
+            }));
+        for (effect, literal, guard) in end_effects {
             new_state.scheduled_effects.push(ScheduledEffect {
-                time: start_time + action.duration,
+                time: end_time,
+                effect: effect.clone(),
+                literal,
+                action_id: Some(action_idx),
+                guard,
+            });
+        }
+        let numeric_end_effects = action.numeric_effects_end.iter()
+            .map(|effect| (effect, None))
+            .chain(action.conditional_effects_end.iter().enumerate().flat_map(|(guard, conditional)| {
+                conditional.numeric_effects.iter().map(move |effect| (effect, Some(guard)))
+            }));
+        for (effect, guard) in numeric_end_effects {
+            new_state.scheduled_numeric_effects.push(ScheduledNumericEffect {
+                time: end_time,
                 effect: effect.clone(),
                 action_id: action_idx,
+                guard,
             });
         }
+        if !action.conditions_over_all.is_empty() || !action.numeric_conditions_over_all.is_empty() {
+            new_state.invariants.push(Invariant { action_id: action_idx, until: end_time });
+        }
         
         new_state
     }
 
-    fn apply_effect(&self, _state: &mut State, _effect: &super::temporal_task::Effect) {
-        // Apply effect to state
-        // TODO: Implement effect application
+    /// As `apply_action`, but `None` when the start effects break the over-all conditions of
+    /// this or another running action
+    pub fn try_apply_action(&self, state: &TemporalState, action_idx: usize, start_time: f64) -> Option<TemporalState> {
+        let duration = self.action_duration(action_idx, &state.classical_state);
+        self.try_apply_action_with_duration(state, action_idx, start_time, duration)
+    }
+
+    /// As `try_apply_action`, with the action running for `duration`
+    pub fn try_apply_action_with_duration(&self, state: &TemporalState, action_idx: usize, start_time: f64, duration: f64) -> Option<TemporalState> {
+        let started = self.apply_action_with_duration(state, action_idx, start_time, duration);
+        self.invariants_hold(&started).then_some(started)
+    }
+
+    /// Whether the over-all conditions of every running action hold in `state`
+    pub fn invariants_hold(&self, state: &TemporalState) -> bool {
+        state.invariants.iter().all(|invariant| {
+            let action = &self.task.ground_actions[invariant.action_id];
+            let over_all: LiteralsOf = &|compiled| &compiled.actions[invariant.action_id].conditions_over_all;
+            self.satisfies(&action.conditions_over_all, over_all, &action.numeric_conditions_over_all, &state.classical_state)
+        })
+    }
+
+    /// Start an action at the state's time and advance the clock until it has finished.
+    ///
+    /// Returns `None` when the action's over-all or end conditions fail, or when a state
+    /// passed through violates an `always` constraint. Timed initial literals due while the
+    /// action runs take effect on the way; later ones stay pending.
+    pub fn apply_action_to_completion(&self, state: &TemporalState, action_idx: usize) -> Option<TemporalState> {
+        let duration = self.action_duration(action_idx, &state.classical_state);
+        self.apply_action_to_completion_with_duration(state, action_idx, duration)
+    }
+
+    /// As `apply_action_to_completion`, with the action running for `duration`; `None` as well
+    /// when ending then could break the over-all conditions of an action still running
+    pub fn apply_action_to_completion_with_duration(&self, state: &TemporalState, action_idx: usize, duration: f64) -> Option<TemporalState> {
+        let action = &self.task.ground_actions[action_idx];
+        let end_time = state.time + duration;
+        let (earliest, latest) = self.conflict_free_ends(action_idx, state, state.time)?;
+        if end_time < earliest || end_time > latest {
+            return None;
+        }
+
+        // Nothing else runs concurrently, so the state after the start effects changes before
+        // the end only through timed literals
+        let mut finished = self.try_apply_action_with_duration(state, action_idx, state.time, duration)?;
+        if !self.satisfies_constraints(&finished.classical_state) {
+            return None;
+        }
+        while finished.next_due().is_some_and(|time| time < end_time) {
+            finished = self.advance(&finished)?;
+            if !self.satisfies_constraints(&finished.classical_state) {
+                return None;
+            }
+        }
+
+        let at_end: LiteralsOf = &|compiled| &compiled.actions[action_idx].conditions_end;
+        if !self.satisfies(&action.conditions_end, at_end, &action.numeric_conditions_end, &finished.classical_state) {
+            return None;
+        }
+        while finished.next_due().is_some_and(|time| time <= end_time) {
+            finished = self.process_scheduled_effects(&finished);
+        }
+        // Actions without end effects still occupy their full duration
+        finished.time = end_time;
+        finished.invariants.retain(|invariant| invariant.until > end_time);
+
+        self.satisfies_constraints(&finished.classical_state).then_some(finished)
+    }
+
+    /// Let the clock run, with no action started, until the next timed initial literal has
+    /// taken effect; `None` when none is pending or the new state violates an `always` constraint
+    pub fn wait_for_timed_literal(&self, state: &TemporalState) -> Option<TemporalState> {
+        if state.scheduled_effects.iter().any(|scheduled| scheduled.action_id.is_some()) {
+            return None;
+        }
+        state.next_due()?;
+        let waited = self.process_scheduled_effects(state);
+        self.satisfies_constraints(&waited.classical_state).then_some(waited)
+    }
+
+    /// As `process_scheduled_effects`, but `None` when the effects break the over-all conditions
+    /// of an action that is still running afterwards, pruning that branch
+    pub fn advance(&self, state: &TemporalState) -> Option<TemporalState> {
+        let advanced = self.process_scheduled_effects(state);
+        self.invariants_hold(&advanced).then_some(advanced)
+    }
+
+    /// Advance to the next scheduled time point and apply every effect due by then. Actions
+    /// ending by then drop their over-all conditions; see `advance` for checking the others.
+    pub fn process_scheduled_effects(&self, state: &TemporalState) -> TemporalState {
+        let mut new_state = state.clone();
+        
+        // Find next time point
+        let next_time = new_state.next_due().unwrap_or(new_state.time);
+
+        // Advance time
+        new_state.time = next_time;
+        new_state.invariants.retain(|invariant| invariant.until > next_time);
+
+        // Apply effects scheduled for this time, judging guards against the state before any of them
+        let before = state.classical_state.clone();
+        let (due, remaining): (Vec<_>, Vec<_>) = new_state.scheduled_effects
+            .into_iter()
+            .partition(|e| e.time <= next_time);
+        let due_effects: Vec<CompiledLiteral> = due.into_iter()
+            .filter(|e| e.action_id.is_none_or(|action_idx| self.guard_holds(action_idx, e.guard, &before)))
+            .map(|e| e.literal)
+            .collect();
+        Self::apply_effects(&mut new_state.classical_state, &due_effects);
+        new_state.scheduled_effects = remaining;
+
+        let (due, remaining): (Vec<_>, Vec<_>) = new_state.scheduled_numeric_effects
+            .into_iter()
+            .partition(|e| e.time <= next_time);
+        let due_effects: Vec<NumericEffect> = due.into_iter()
+            .filter(|e| self.guard_holds(e.action_id, e.guard, &before))
+            .map(|e| e.effect)
+            .collect();
+        self.apply_numeric_effects(&mut new_state.classical_state, &due_effects);
+        new_state.scheduled_numeric_effects = remaining;
+        self.apply_axioms(&mut new_state.classical_state);
+
+        new_state
+    }
+
+    fn guard_holds(&self, action_idx: usize, guard: Option<usize>, state: &State) -> bool {
+        guard.is_none_or(|guard| {
+            let conditional = &self.task.ground_actions[action_idx].conditional_effects_end[guard];
+            let antecedent: LiteralsOf = &|compiled| &compiled.actions[action_idx].conditional_effects_end[guard].conditions;
+            self.satisfies(&conditional.conditions, antecedent, &conditional.numeric_conditions, state)
+        })
     }
-}
\ No newline at end of file
+
+    fn apply_axioms(&self, state: &mut State) {
+        match self.compiled {
+            Some(compiled) => compiled.apply_axioms(state),
+            None => self.task.apply_axioms(state),
+        }
+    }
+
+    /// Apply effects due at the same time point. An atom both deleted and added among them ends
+    /// up true: deletes are applied before adds, for start effects, end effects, and conditional
+    /// effects alike. `TemporalTask::try_from_pddl` rejects actions that do this unconditionally.
+    fn apply_effects(state: &mut State, effects: &[CompiledLiteral]) {
+        let (deletes, adds): (Vec<&CompiledLiteral>, Vec<&CompiledLiteral>) = effects.iter().partition(|e| e.negative);
+        for effect in deletes.into_iter().chain(adds) {
+            if let Some(fact) = effect.fact {
+                state.facts.set(fact as usize, !effect.negative);
+            }
+        }
+    }
+
+    fn apply_numeric_effects(&self, state: &mut State, effects: &[NumericEffect]) {
+        // Evaluate every right-hand side against the pre-effect values before assigning
+        let updates: Vec<(String, f64)> = effects
+            .iter()
+            .filter_map(|effect| {
+                effect.resulting_value(&state.numeric_values)
+                    .map(|value| (effect.function.key(), value))
+            })
+            .collect();
+
+        for (key, value) in updates {
+            state.numeric_values.insert(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temporal_task::{Comparison, DurationSpec, NumericExpr, NumericOp};
+
+    const COUNTER_DOMAIN: &str = r#"
+(define (domain counter)
+  (:requirements :strips :durative-actions :numeric-fluents)
+  (:predicates (ready))
+  (:functions (count))
+
+  (:action bump
+    :parameters ()
+    :precondition (ready)
+    :effect (increase (count) 2.0))
+
+  (:durative-action drain
+    :parameters ()
+    :duration (= ?duration 3.0)
+    :condition (at start (ready))
+    :effect (and (at start (decrease (count) (* 2 (count))))
+                 (at end (assign (count) 10))))
+)
+"#;
+
+    const COUNTER_PROBLEM: &str = r#"
+(define (problem counter-problem)
+  (:domain counter)
+  (:init (ready))
+  (:goal (ready))
+)
+"#;
+
+    fn initial_state(task: &TemporalTask) -> TemporalState {
+        TemporalState {
+            classical_state: task.initial_state.clone(),
+            scheduled_effects: Vec::new(),
+            scheduled_numeric_effects: Vec::new(),
+            invariants: Vec::new(),
+            time: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_numeric_effects_are_parsed() {
+        let task = TemporalTask::from_pddl(COUNTER_DOMAIN, COUNTER_PROBLEM);
+
+        let bump = &task.actions[0];
+        assert!(bump.effects_end.is_empty(), "increase must not be parsed as a predicate");
+        assert_eq!(bump.numeric_effects_end.len(), 1);
+        assert_eq!(bump.numeric_effects_end[0].op, NumericOp::Increase);
+        assert_eq!(bump.numeric_effects_end[0].function.key(), "count");
+
+        let drain = &task.actions[1];
+        assert_eq!(drain.numeric_effects_start.len(), 1);
+        assert_eq!(drain.numeric_effects_start[0].op, NumericOp::Decrease);
+        assert_eq!(drain.numeric_effects_end.len(), 1);
+        assert_eq!(drain.numeric_effects_end[0].op, NumericOp::Assign);
+    }
+
+    #[test]
+    fn test_declared_functions_are_initialized() {
+        let domain = r#"
+(define (domain fleet)
+  (:requirements :typing :numeric-fluents)
+  (:types truck)
+  (:predicates (ready))
+  (:functions (fuel) (load ?t - truck) - number))
+"#;
+        let problem = r#"
+(define (problem fleet-problem)
+  (:domain fleet)
+  (:objects t1 t2 - truck)
+  (:init (ready) (= (fuel) 30) (= (load t2) 4))
+  (:goal (ready)))
+"#;
+        let task = TemporalTask::from_pddl(domain, problem);
+        assert_eq!(task.functions.len(), 2);
+        assert_eq!(task.functions[1].name, "load");
+        assert_eq!(task.functions[1].parameter_types, vec!["truck".to_string()]);
+
+        let values = &task.initial_state.numeric_values;
+        assert_eq!(values.get("fuel"), Some(&30.0));
+        assert_eq!(values.get("load t1"), Some(&0.0), "Fluents without an :init value default to zero");
+        assert_eq!(values.get("load t2"), Some(&4.0));
+    }
+
+    #[test]
+    fn test_state_key_includes_pending_effects() {
+        let task = TemporalTask::from_pddl(COUNTER_DOMAIN, COUNTER_PROBLEM);
+        let state_space = StateSpace::new(task.clone());
+        let drain = 1;
+
+        let started = state_space.apply_action(&initial_state(&task), drain, 0.0);
+        let same_facts = TemporalState {
+            scheduled_numeric_effects: Vec::new(),
+            ..started.clone()
+        };
+        assert_ne!(started.key(), same_facts.key(), "A pending end effect distinguishes otherwise equal states");
+
+        let later = TemporalState { time: 5.0, ..initial_state(&task) };
+        let started_later = state_space.apply_action(&later, drain, 5.0);
+        assert_eq!(started.key(), started_later.key(), "Only the time until an effect is due matters");
+
+        let halfway = TemporalState { time: 1.5, ..started.clone() };
+        assert_ne!(started.key(), halfway.key(), "The same effect due sooner is a different state");
+    }
+
+    #[test]
+    fn test_identical_states_share_an_id() {
+        let task = TemporalTask::from_pddl(COUNTER_DOMAIN, COUNTER_PROBLEM);
+        let state_space = StateSpace::new(task.clone());
+        let drain = 1;
+        let mut registry = StateRegistry::new();
+
+        let started = state_space.apply_action(&initial_state(&task), drain, 0.0);
+        let id = registry.intern(&started);
+        assert_eq!(registry.intern(&started.clone()), id);
+        let later = TemporalState { time: 5.0, ..initial_state(&task) };
+        let started_later = state_space.apply_action(&later, drain, 5.0);
+        assert_eq!(registry.intern(&started_later), id, "A time shift does not make a new state");
+        assert_ne!(registry.intern(&initial_state(&task)), id);
+        assert_eq!(registry.len(), 2);
+
+        let resolved = registry.resolve(id, 5.0);
+        assert_eq!(resolved.time, 5.0);
+        assert_eq!(resolved.scheduled_numeric_effects[0].time, started_later.scheduled_numeric_effects[0].time);
+        assert_eq!(resolved.key(), started_later.key());
+    }
+
+    #[test]
+    fn test_describe_lists_facts_and_pending_effects() {
+        let mut task = TemporalTask::from_pddl(COUNTER_DOMAIN, COUNTER_PROBLEM);
+        task.initial_state.numeric_values.insert("count".to_string(), 4.0);
+        let state_space = StateSpace::new(task.clone());
+
+        let started = state_space.apply_action(&initial_state(&task), 1, 0.0);
+        let description = started.describe(&task);
+        assert!(description.starts_with("time 0\n"), "{}", description);
+        assert!(description.contains("facts: (ready)"), "{}", description);
+        assert!(description.contains("(count) = -4"), "The start effect has applied: {}", description);
+        assert!(description.contains("pending at 3: assign (count) from drain"), "{}", description);
+
+        let finished = state_space.process_scheduled_effects(&started).describe(&task);
+        assert!(finished.contains("(count) = 10") && !finished.contains("pending"), "{}", finished);
+    }
+
+    #[test]
+    fn test_effect_breaking_a_running_invariant_prunes_the_branch() {
+        let domain = r#"
+(define (domain guard)
+  (:requirements :strips :durative-actions :negative-preconditions)
+  (:predicates (ready) (done))
+  (:durative-action hold
+    :parameters ()
+    :duration (= ?duration 4.0)
+    :condition (over all (ready))
+    :effect (at end (done)))
+  (:durative-action knock
+    :parameters ()
+    :duration (= ?duration 1.0)
+    :effect (at start (not (ready))))
+  (:durative-action fade
+    :parameters ()
+    :duration (= ?duration 2.0)
+    :effect (at end (not (ready))))
+)
+"#;
+        let problem = "(define (problem p) (:domain guard) (:init (ready)) (:goal (done)))";
+        let task = TemporalTask::from_pddl(domain, problem);
+        let state_space = StateSpace::new(task.clone());
+        let action = |name: &str| task.ground_actions.iter().position(|action| action.name == name).unwrap();
+
+        let holding = state_space.apply_action(&initial_state(&task), action("hold"), 0.0);
+        assert_eq!(holding.invariants.len(), 1);
+        assert!(state_space.try_apply_action(&holding, action("knock"), 0.0).is_none(), "(ready) is deleted while hold runs");
+
+        // fade starts harmlessly but deletes (ready) at 2, before hold ends at 4
+        let fading = state_space.try_apply_action(&holding, action("fade"), 0.0).unwrap();
+        assert!(state_space.advance(&fading).is_none());
+
+        // Once hold has ended, deleting (ready) breaks nothing
+        let held = state_space.apply_action_to_completion(&initial_state(&task), action("hold")).unwrap();
+        assert!(held.invariants.is_empty());
+        let knocked = state_space.try_apply_action(&held, action("knock"), held.time).unwrap();
+        assert!(!knocked.classical_state.facts[task.fact_id("ready", &[]).unwrap()]);
+    }
+
+    #[test]
+    fn test_increase_effect_bumps_numeric_value() {
+        let mut task = TemporalTask::from_pddl(COUNTER_DOMAIN, COUNTER_PROBLEM);
+        task.initial_state.numeric_values.insert("count".to_string(), 1.0);
+        let state_space = StateSpace::new(task.clone());
+
+        let started = state_space.apply_action(&initial_state(&task), 0, 0.0);
+        assert_eq!(started.classical_state.numeric_values["count"], 1.0, "End effect applies at the end");
+
+        let finished = state_space.process_scheduled_effects(&started);
+        assert_eq!(finished.time, 1.0);
+        assert_eq!(finished.classical_state.numeric_values["count"], 3.0);
+        assert!(finished.scheduled_numeric_effects.is_empty());
+    }
+
+    #[test]
+    fn test_start_and_end_numeric_effects() {
+        let mut task = TemporalTask::from_pddl(COUNTER_DOMAIN, COUNTER_PROBLEM);
+        task.initial_state.numeric_values.insert("count".to_string(), 4.0);
+        let state_space = StateSpace::new(task.clone());
+
+        let started = state_space.apply_action(&initial_state(&task), 1, 0.0);
+        assert_eq!(started.classical_state.numeric_values["count"], -4.0);
+
+        let finished = state_space.process_scheduled_effects(&started);
+        assert_eq!(finished.time, 3.0);
+        assert_eq!(finished.classical_state.numeric_values["count"], 10.0);
+    }
+
+    #[test]
+    fn test_numeric_precondition_gates_applicability() {
+        let domain = r#"
+(define (domain fuel)
+  (:requirements :strips :durative-actions :numeric-fluents)
+  (:predicates (ready))
+  (:functions (fuel))
+
+  (:action refuel
+    :parameters ()
+    :precondition (and (ready) (< (fuel) 10))
+    :effect (assign (fuel) 10))
+
+  (:durative-action drive
+    :parameters ()
+    :duration (= ?duration 2.0)
+    :condition (and (at start (>= (fuel) 10))
+                    (over all (> (fuel) 0)))
+    :effect (at end (decrease (fuel) 10))))
+"#;
+        let mut task = TemporalTask::from_pddl(domain, COUNTER_PROBLEM);
+        let (refuel, drive) = (&task.actions[0], &task.actions[1]);
+        assert_eq!(refuel.conditions_start.len(), 1, "Comparison must not be parsed as a predicate");
+        assert_eq!(refuel.numeric_conditions_start.len(), 1);
+        assert_eq!(drive.numeric_conditions_start[0].comparison, Comparison::GreaterEqual);
+        assert_eq!(drive.numeric_conditions_over_all[0].comparison, Comparison::Greater);
+
+        let applicable = |task: &TemporalTask| {
+            StateSpace::new(task.clone())
+                .get_applicable_actions(&initial_state(task))
+                .into_iter()
+                .map(|(idx, _)| idx)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(applicable(&task), vec![0], "Declared fluents start at zero");
+        task.initial_state.numeric_values.remove("fuel");
+        assert_eq!(applicable(&task), Vec::<usize>::new(), "Undefined fluents satisfy no comparison");
+        task.initial_state.numeric_values.insert("fuel".to_string(), 5.0);
+        assert_eq!(applicable(&task), vec![0]);
+        task.initial_state.numeric_values.insert("fuel".to_string(), 10.0);
+        assert_eq!(applicable(&task), vec![1]);
+    }
+
+    #[test]
+    fn test_durations_evaluated_from_fluents() {
+        let domain = r#"
+(define (domain travel)
+  (:requirements :durative-actions :numeric-fluents)
+  (:predicates (ready))
+  (:functions (distance) (speed) (rest-time))
+
+  (:durative-action wait
+    :parameters ()
+    :duration (= ?duration 4.5)
+    :condition (at start (ready))
+    :effect (at end (ready)))
+
+  (:durative-action rest
+    :parameters ()
+    :duration (= ?duration (rest-time))
+    :condition (at start (ready))
+    :effect (at end (ready)))
+
+  (:durative-action drive
+    :parameters ()
+    :duration (= ?duration (/ (distance) (speed)))
+    :condition (at start (ready))
+    :effect (at end (ready))))
+"#;
+        let mut task = TemporalTask::from_pddl(domain, COUNTER_PROBLEM);
+        assert_eq!(task.actions[0].duration_spec, DurationSpec::Exact(NumericExpr::Number(4.5)));
+        assert_eq!(task.actions[0].duration, 4.5);
+        assert!(matches!(task.actions[2].duration_spec, DurationSpec::Exact(NumericExpr::Divide(_, _))));
+
+        let values = &mut task.initial_state.numeric_values;
+        values.insert("rest-time".to_string(), 2.0);
+        values.insert("distance".to_string(), 30.0);
+        values.insert("speed".to_string(), 4.0);
+        let state_space = StateSpace::new(task.clone());
+        let state = initial_state(&task);
+
+        let end_time = |action_idx| state_space.apply_action_to_completion(&state, action_idx).unwrap().time;
+        assert_eq!(end_time(0), 4.5);
+        assert_eq!(end_time(1), 2.0);
+        assert_eq!(end_time(2), 7.5);
+
+        task.initial_state.numeric_values.remove("speed");
+        let applicable: Vec<usize> = StateSpace::new(task.clone())
+            .get_applicable_actions(&initial_state(&task))
+            .into_iter()
+            .map(|(idx, _)| idx)
+            .collect();
+        assert_eq!(applicable, vec![0, 1], "Undefined duration fluents make the action inapplicable");
+    }
+
+    #[test]
+    fn test_duration_bounds_are_parsed() {
+        let domain = r#"
+(define (domain flexible)
+  (:requirements :durative-actions :duration-inequalities :numeric-fluents)
+  (:predicates (ready))
+  (:functions (max-charge))
+
+  (:durative-action charge
+    :parameters ()
+    :duration (<= ?duration (max-charge))
+    :condition (at start (ready))
+    :effect (at end (ready)))
+
+  (:durative-action cool
+    :parameters ()
+    :duration (and (>= ?duration 2) (<= ?duration 6))
+    :condition (at start (ready))
+    :effect (at end (ready)))
+
+  (:durative-action stall
+    :parameters ()
+    :duration (and (>= ?duration 5) (<= ?duration 3))
+    :condition (at start (ready))
+    :effect (at end (ready))))
+"#;
+        let mut task = TemporalTask::from_pddl(domain, COUNTER_PROBLEM);
+        assert!(matches!(
+            &task.actions[0].duration_spec,
+            DurationSpec::Bounded { lower: None, upper: Some(NumericExpr::Function(_)) }
+        ));
+        assert_eq!(task.actions[1].duration_spec, DurationSpec::Bounded {
+            lower: Some(NumericExpr::Number(2.0)),
+            upper: Some(NumericExpr::Number(6.0)),
+        });
+        assert_eq!(task.actions[1].duration, 2.0, "Bounded durations take their shortest value");
+
+        task.initial_state.numeric_values.insert("max-charge".to_string(), 8.0);
+        let values = &task.initial_state.numeric_values;
+        assert_eq!(task.actions[0].duration_spec.bounds(values), Some((0.0, 8.0)));
+        assert_eq!(task.actions[1].duration_spec.bounds(values), Some((2.0, 6.0)));
+        assert_eq!(task.actions[2].duration_spec.bounds(values), None, "Empty range");
+
+        let state_space = StateSpace::new(task.clone());
+        let state = initial_state(&task);
+        assert_eq!(state_space.action_duration(0, &state.classical_state), 8.0);
+        assert_eq!(state_space.action_duration(1, &state.classical_state), 2.0);
+        let applicable: Vec<usize> = state_space.get_applicable_actions(&state)
+            .into_iter()
+            .map(|(idx, _)| idx)
+            .collect();
+        assert_eq!(applicable, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_conflicting_actions_cannot_overlap() {
+        let domain = r#"
+(define (domain crane)
+  (:requirements :strips :durative-actions)
+  (:predicates (power) (light) (lifted) (welded))
+
+  (:durative-action lift
+    :parameters ()
+    :duration (= ?duration 3)
+    :condition (over all (power))
+    :effect (and (at start (not (light))) (at end (light)) (at end (lifted))))
+
+  (:durative-action weld
+    :parameters ()
+    :duration (= ?duration 2)
+    :condition (over all (light))
+    :effect (and (at start (not (power))) (at end (power)) (at end (welded)))))
+"#;
+        let problem = "(define (problem p) (:domain crane) (:init (power) (light)) (:goal (and (lifted) (welded))))";
+        let task = TemporalTask::from_pddl(domain, problem);
+        let state_space = StateSpace::new(task.clone());
+        let applicable = |state: &TemporalState| -> Vec<usize> {
+            state_space.get_applicable_actions(state).into_iter().map(|(idx, _)| idx).collect()
+        };
+        let initial = initial_state(&task);
+        assert_eq!(applicable(&initial), vec![0, 1]);
+
+        // Welding would cut the power lifting needs over all
+        let lifting = state_space.apply_action(&initial, 0, 0.0);
+        assert!(!applicable(&lifting).contains(&1));
+        // Lifting would put out the light welding needs over all
+        let welding = state_space.apply_action(&initial, 1, 0.0);
+        assert!(!applicable(&welding).contains(&0));
+
+        let lifted = state_space.apply_action_to_completion(&initial, 0).unwrap();
+        assert_eq!(applicable(&lifted), vec![0, 1], "Nothing is running any more");
+        let done = state_space.apply_action_to_completion(&lifted, 1).unwrap();
+        assert!(state_space.is_goal(&done));
+    }
+
+    #[test]
+    fn test_conflicts_use_the_duration_the_action_runs() {
+        let domain = r#"
+(define (domain crane)
+  (:requirements :strips :durative-actions)
+  (:predicates (power) (lifted) (charged))
+
+  (:durative-action lift
+    :parameters ()
+    :duration (= ?duration 3)
+    :condition (over all (power))
+    :effect (at end (lifted)))
+
+  (:durative-action recharge
+    :parameters ()
+    :duration (and (>= ?duration 1) (<= ?duration 5))
+    :condition (and)
+    :effect (and (at end (not (power))) (at end (charged)))))
+"#;
+        let problem = "(define (problem p) (:domain crane) (:init (power)) (:goal (and (lifted) (charged))))";
+        let task = TemporalTask::from_pddl(domain, problem);
+        let state_space = StateSpace::new(task.clone());
+        let lifting = state_space.apply_action(&initial_state(&task), 0, 0.0);
+
+        // Cutting the power is fine once lifting has finished at 3, so only long recharges fit
+        assert!(state_space.get_applicable_actions(&lifting).iter().any(|&(idx, _)| idx == 1));
+        assert!(state_space.apply_action_to_completion_with_duration(&lifting, 1, 1.0).is_none());
+        assert!(state_space.apply_action_to_completion_with_duration(&lifting, 1, 5.0).is_some());
+    }
+
+    #[test]
+    fn test_numeric_over_all_conditions_block_conflicts() {
+        let domain = r#"
+(define (domain tank)
+  (:requirements :strips :durative-actions :numeric-fluents)
+  (:predicates (pumped) (drained))
+  (:functions (level))
+
+  (:durative-action pump
+    :parameters ()
+    :duration (= ?duration 4)
+    :condition (over all (>= (level) 5))
+    :effect (at end (pumped)))
+
+  (:durative-action drain
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (and)
+    :effect (and (at start (decrease (level) 1)) (at end (drained)))))
+"#;
+        let problem = "(define (problem p) (:domain tank) (:init (= (level) 10)) (:goal (and (pumped) (drained))))";
+        let task = TemporalTask::from_pddl(domain, problem);
+        let state_space = StateSpace::new(task.clone());
+        let initial = initial_state(&task);
+        let applicable = |state: &TemporalState| -> Vec<usize> {
+            state_space.get_applicable_actions(state).into_iter().map(|(idx, _)| idx).collect()
+        };
+
+        // Any change to the level counts, whatever value it leaves
+        let pumping = state_space.apply_action(&initial, 0, 0.0);
+        assert_eq!(applicable(&pumping), vec![0]);
+        let pumped = state_space.apply_action_to_completion(&initial, 0).unwrap();
+        assert_eq!(applicable(&pumped), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_conditional_effects_fire_only_when_guarded() {
+        let domain = r#"
+(define (domain lights)
+  (:requirements :strips :durative-actions :conditional-effects)
+  (:predicates (ready) (powered) (lit) (charged) (warm))
+
+  (:action flip
+    :parameters ()
+    :precondition (ready)
+    :effect (when (powered) (lit)))
+
+  (:durative-action charge
+    :parameters ()
+    :duration (= ?duration 2)
+    :condition (at start (ready))
+    :effect (and (at start (when (powered) (warm)))
+                 (at end (when (and (powered) (warm)) (charged)))
+                 (at end (not (powered))))))
+"#;
+        let task = TemporalTask::from_pddl(domain, COUNTER_PROBLEM);
+        assert_eq!(task.actions[0].conditional_effects_end.len(), 1);
+        assert!(task.actions[0].effects_end.is_empty(), "Guarded effects must not become unconditional");
+        assert_eq!(task.actions[1].conditional_effects_start.len(), 1);
+        assert_eq!(task.actions[1].conditional_effects_end.len(), 1);
+        assert_eq!(task.actions[1].conditional_effects_end[0].conditions.len(), 2);
+
+        let fact = |name: &str| task.fact_id(name, &[]).unwrap();
+        let state_space = StateSpace::new(task.clone());
+        let unpowered = initial_state(&task);
+        let mut powered = initial_state(&task);
+        powered.classical_state.facts.set(fact("powered"), true);
+
+        let after_flip = state_space.apply_action_to_completion(&unpowered, 0).unwrap();
+        assert!(!after_flip.classical_state.facts[fact("lit")], "Guard unsatisfied");
+        let after_flip = state_space.apply_action_to_completion(&powered, 0).unwrap();
+        assert!(after_flip.classical_state.facts[fact("lit")]);
+
+        let after_charge = state_space.apply_action_to_completion(&powered, 1).unwrap();
+        let facts = &after_charge.classical_state.facts;
+        assert!(facts[fact("warm")] && facts[fact("charged")]);
+        assert!(!facts[fact("powered")], "Guards see the state before the end effects");
+        let after_charge = state_space.apply_action_to_completion(&unpowered, 1).unwrap();
+        assert!(!after_charge.classical_state.facts[fact("charged")]);
+    }
+
+    #[test]
+    fn test_derived_predicates_in_over_all_conditions() {
+        let domain = r#"
+(define (domain guards)
+  (:requirements :strips :typing :durative-actions :derived-predicates)
+  (:types robot)
+  (:predicates (charged ?r - robot) (armed ?r - robot) (safe ?r - robot))
+
+  (:derived (safe ?r - robot) (and (charged ?r) (not (armed ?r))))
+
+  (:durative-action arm
+    :parameters (?r - robot)
+    :duration (= ?duration 2)
+    :condition (over all (safe ?r))
+    :effect (at start (armed ?r)))
+
+  (:durative-action patrol
+    :parameters (?r - robot)
+    :duration (= ?duration 3)
+    :condition (over all (safe ?r))
+    :effect (at end (not (charged ?r)))))
+"#;
+        let problem = "(define (problem p) (:domain guards) (:objects r1 - robot) (:init (charged r1)) (:goal (armed r1)))";
+        let task = TemporalTask::from_pddl(domain, problem);
+        assert_eq!(task.axioms.len(), 1);
+        let safe = task.fact_id("safe", &["r1".to_string()]).unwrap();
+        assert!(task.initial_state.facts[safe], "Derived atoms hold in the initial state");
+
+        let state_space = StateSpace::new(task.clone());
+        let state = initial_state(&task);
+        assert!(
+            state_space.apply_action_to_completion(&state, 0).is_none(),
+            "Arming makes the robot unsafe while the over-all condition must hold"
+        );
+        let after_patrol = state_space.apply_action_to_completion(&state, 1)
+            .expect("Safety only lapses once the patrol has ended");
+        assert!(!after_patrol.classical_state.facts[safe], "Derived atoms follow their basic facts");
+    }
+}
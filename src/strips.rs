@@ -0,0 +1,287 @@
+// Compilation of temporal tasks into classical STRIPS
+use super::temporal_task::{Condition, Effect, TemporalAction, TemporalTask};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// A ground STRIPS action over the fact indices of `StripsTask::facts`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StripsAction {
+    pub name: String,
+    pub preconditions: Vec<usize>,
+    /// Facts that must be false, from negative conditions and unfired conditional effects
+    pub negative_preconditions: Vec<usize>,
+    pub add_effects: Vec<usize>,
+    pub delete_effects: Vec<usize>,
+}
+
+/// Classical task with positive goals; actions may have negative preconditions
+#[derive(Debug, Clone, PartialEq)]
+pub struct StripsTask {
+    pub facts: Vec<String>,
+    pub initial_state: Vec<bool>,
+    pub goal: Vec<usize>,
+    pub actions: Vec<StripsAction>,
+}
+
+impl StripsTask {
+    pub fn is_applicable(&self, action: &StripsAction, state: &[bool]) -> bool {
+        action.preconditions.iter().all(|&fact| state[fact])
+            && action.negative_preconditions.iter().all(|&fact| !state[fact])
+    }
+
+    /// Successor state: deletes are applied before adds
+    pub fn apply(&self, action: &StripsAction, state: &[bool]) -> Vec<bool> {
+        let mut next = state.to_vec();
+        for &fact in &action.delete_effects {
+            next[fact] = false;
+        }
+        for &fact in &action.add_effects {
+            next[fact] = true;
+        }
+        next
+    }
+
+    pub fn is_goal(&self, state: &[bool]) -> bool {
+        self.goal.iter().all(|&fact| state[fact])
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum StripsError {
+    #[error("action '{action}' uses numeric fluents")]
+    NumericFluents { action: String },
+    #[error("the goal compares numeric fluents")]
+    NumericGoal,
+    #[error("negative goal {condition} cannot be expressed in STRIPS")]
+    NegativeGoal { condition: String },
+    #[error("trajectory constraint {constraint} cannot be expressed in STRIPS")]
+    TrajectoryConstraint { constraint: String },
+    #[error("derived predicate '{predicate}' cannot be expressed in STRIPS")]
+    DerivedPredicate { predicate: String },
+    #[error("timed initial literal {literal} cannot be expressed in STRIPS")]
+    TimedLiteral { literal: String },
+}
+
+impl TemporalTask {
+    /// Compile this task into canonical STRIPS.
+    ///
+    /// Durative actions become unit actions whose effects are the start effects followed by
+    /// the end effects. Conditional effects are compiled away by splitting each action into one
+    /// variant where the effect fires and one per antecedent literal that blocks it. Numeric
+    /// fluents, negative goals, trajectory constraints, derived predicates, and timed initial
+    /// literals have no STRIPS counterpart and are reported as errors.
+    pub fn to_strips(&self) -> Result<StripsTask, StripsError> {
+        if !self.numeric_goal_conditions.is_empty() {
+            return Err(StripsError::NumericGoal);
+        }
+        if let Some(constraint) = self.always_constraints.first() {
+            return Err(StripsError::TrajectoryConstraint {
+                constraint: format!("(always {})", constraint),
+            });
+        }
+
+        if let Some(axiom) = self.axioms.first() {
+            return Err(StripsError::DerivedPredicate { predicate: axiom.predicate.clone() });
+        }
+        if let Some(timed) = self.timed_literals.first() {
+            let effect = &timed.effect;
+            let literal = if effect.is_delete {
+                format!("(at {} (not ({})))", timed.time, effect.key())
+            } else {
+                format!("(at {} ({}))", timed.time, effect.key())
+            };
+            return Err(StripsError::TimedLiteral { literal });
+        }
+
+        let mut compiler = StripsCompiler {
+            facts: self.fact_names.clone(),
+            fact_ids: self.fact_ids.clone(),
+        };
+
+        let mut goal = Vec::new();
+        for condition in &self.goal_conditions {
+            if condition.is_negative {
+                return Err(StripsError::NegativeGoal { condition: condition.to_string() });
+            }
+            let id = compiler.fact_id(condition.key());
+            push_unique(&mut goal, id);
+        }
+
+        let mut actions = Vec::new();
+        for action in &self.ground_actions {
+            if uses_numeric_fluents(action) {
+                return Err(StripsError::NumericFluents { action: action.name.clone() });
+            }
+            for variant in split_conditional_effects(action) {
+                if let Some(compiled) = compiler.compile_action(&variant) {
+                    actions.push(compiled);
+                }
+            }
+        }
+
+        let mut initial_state: Vec<bool> = self.initial_state.facts.iter().collect();
+        initial_state.resize(compiler.facts.len(), false);
+
+        Ok(StripsTask {
+            facts: compiler.facts,
+            initial_state,
+            goal,
+            actions,
+        })
+    }
+}
+
+/// Fact table being built up during compilation; starts from the task's own index
+struct StripsCompiler {
+    facts: Vec<String>,
+    fact_ids: HashMap<String, usize>,
+}
+
+impl StripsCompiler {
+    /// `None` when the action can never complete, e.g. its start effects delete an over-all
+    /// condition, or its conditions contradict each other
+    fn compile_action(&mut self, action: &TemporalAction) -> Option<StripsAction> {
+        let (start_adds, start_deletes) = self.effect_ids(&action.effects_start);
+        let (end_adds, end_deletes) = self.effect_ids(&action.effects_end);
+
+        let mut preconditions = Vec::new();
+        let mut negative_preconditions = Vec::new();
+        for condition in &action.conditions_start {
+            let id = self.fact_id(condition.key());
+            if condition.is_negative {
+                push_unique(&mut negative_preconditions, id);
+            } else {
+                push_unique(&mut preconditions, id);
+            }
+        }
+
+        // Over-all and end conditions are checked after the start effects
+        for condition in action.conditions_over_all.iter().chain(&action.conditions_end) {
+            let id = self.fact_id(condition.key());
+            let (made_true, made_false) = if condition.is_negative {
+                (&start_deletes, &start_adds)
+            } else {
+                (&start_adds, &start_deletes)
+            };
+            if made_true.contains(&id) {
+                continue;
+            }
+            if made_false.contains(&id) {
+                return None;
+            }
+            if condition.is_negative {
+                push_unique(&mut negative_preconditions, id);
+            } else {
+                push_unique(&mut preconditions, id);
+            }
+        }
+
+        if preconditions.iter().any(|fact| negative_preconditions.contains(fact)) {
+            return None;
+        }
+
+        // Sequencing (s - d1) + a1 then (- d2) + a2 nets to add (a1 - d2) + a2, delete d1 + d2
+        let mut add_effects: Vec<usize> = start_adds.iter()
+            .copied()
+            .filter(|fact| !end_deletes.contains(fact))
+            .collect();
+        for fact in end_adds {
+            push_unique(&mut add_effects, fact);
+        }
+        let mut delete_effects = Vec::new();
+        for fact in start_deletes.into_iter().chain(end_deletes) {
+            if !add_effects.contains(&fact) {
+                push_unique(&mut delete_effects, fact);
+            }
+        }
+
+        Some(StripsAction {
+            name: action.name.clone(),
+            preconditions,
+            negative_preconditions,
+            add_effects,
+            delete_effects,
+        })
+    }
+
+    fn effect_ids(&mut self, effects: &[Effect]) -> (Vec<usize>, Vec<usize>) {
+        let mut adds = Vec::new();
+        let mut deletes = Vec::new();
+        for effect in effects {
+            let id = self.fact_id(effect.key());
+            if effect.is_delete {
+                push_unique(&mut deletes, id);
+            } else {
+                push_unique(&mut adds, id);
+            }
+        }
+        // Within one time point an atom both added and deleted ends up true
+        deletes.retain(|fact| !adds.contains(fact));
+        (adds, deletes)
+    }
+
+    fn fact_id(&mut self, key: String) -> usize {
+        if let Some(&id) = self.fact_ids.get(&key) {
+            return id;
+        }
+        let id = self.facts.len();
+        self.fact_ids.insert(key.clone(), id);
+        self.facts.push(key);
+        id
+    }
+}
+
+fn uses_numeric_fluents(action: &TemporalAction) -> bool {
+    let conditional_numeric = action.conditional_effects_start.iter()
+        .chain(&action.conditional_effects_end)
+        .any(|conditional| !conditional.numeric_conditions.is_empty() || !conditional.numeric_effects.is_empty());
+    conditional_numeric
+        || !action.numeric_conditions_start.is_empty()
+        || !action.numeric_conditions_over_all.is_empty()
+        || !action.numeric_conditions_end.is_empty()
+        || !action.numeric_effects_start.is_empty()
+        || !action.numeric_effects_end.is_empty()
+}
+
+/// Variants of `action` without conditional effects: each conditional effect either fires, with
+/// its antecedent added to the conditions at its time point, or is blocked by one negated literal
+fn split_conditional_effects(action: &TemporalAction) -> Vec<TemporalAction> {
+    let mut base = action.clone();
+    base.conditional_effects_start.clear();
+    base.conditional_effects_end.clear();
+    let mut variants = vec![base];
+
+    let conditionals = action.conditional_effects_start.iter()
+        .map(|conditional| (conditional, true))
+        .chain(action.conditional_effects_end.iter().map(|conditional| (conditional, false)));
+    for (conditional, at_start) in conditionals {
+        let mut split = Vec::new();
+        for variant in variants {
+            let mut fired = variant.clone();
+            let (conditions, effects) = if at_start {
+                (&mut fired.conditions_start, &mut fired.effects_start)
+            } else {
+                (&mut fired.conditions_end, &mut fired.effects_end)
+            };
+            conditions.extend(conditional.conditions.iter().cloned());
+            effects.extend(conditional.effects.iter().cloned());
+            split.push(fired);
+
+            for condition in &conditional.conditions {
+                let mut blocked = variant.clone();
+                let conditions = if at_start { &mut blocked.conditions_start } else { &mut blocked.conditions_end };
+                conditions.push(Condition { is_negative: !condition.is_negative, ..condition.clone() });
+                split.push(blocked);
+            }
+        }
+        variants = split;
+    }
+
+    variants
+}
+
+fn push_unique(ids: &mut Vec<usize>, id: usize) {
+    if !ids.contains(&id) {
+        ids.push(id);
+    }
+}
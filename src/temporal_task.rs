@@ -1,646 +1,2732 @@
 // f:\common\Source_Code\TemporalFastDownward\rust\src\temporal_planner\temporal_task.rs
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs::File;
 use std::hash::{Hash, Hasher};
-use regex::Regex;
+use std::io::{BufReader, BufWriter};
+use std::ops::Index;
+use std::path::Path;
+use thiserror::Error;
+use super::compiled_task::{resolve_condition, CompiledTask};
+use super::finite_domain::FiniteDomainEncoding;
+use super::sexpr::{self, ReadError, SExpr};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TemporalAction {
     pub name: String,
+    /// Declared with `:durative-action` rather than `:action`
+    pub is_durative: bool,
+    /// Nominal duration: the value of `duration_spec` when it needs no fluents, 1.0 otherwise
     pub duration: f64,
+    pub duration_spec: DurationSpec,
     pub conditions_start: Vec<Condition>,
     pub conditions_over_all: Vec<Condition>,
     pub conditions_end: Vec<Condition>,
     pub effects_start: Vec<Effect>,
     pub effects_end: Vec<Effect>,
+    pub numeric_effects_start: Vec<NumericEffect>,
+    pub numeric_effects_end: Vec<NumericEffect>,
+    pub numeric_conditions_start: Vec<NumericCondition>,
+    pub numeric_conditions_over_all: Vec<NumericCondition>,
+    pub numeric_conditions_end: Vec<NumericCondition>,
+    pub conditional_effects_start: Vec<ConditionalEffect>,
+    pub conditional_effects_end: Vec<ConditionalEffect>,
 }
 
-#[derive(Debug, Clone)]
+impl TemporalAction {
+    /// Copy of this schema with its parameters replaced per `binding`, named after the bound values
+    fn instantiate(&self, binding: &HashMap<String, String>, values: &[String]) -> Self {
+        let bind_conditions = |conditions: &[Condition]| conditions.iter().map(|c| c.bind(binding)).collect();
+        let bind_effects = |effects: &[Effect]| effects.iter().map(|e| e.bind(binding)).collect();
+        let bind_numeric_conditions = |conditions: &[NumericCondition]| conditions.iter().map(|c| c.bind(binding)).collect();
+        let bind_numeric_effects = |effects: &[NumericEffect]| effects.iter().map(|e| e.bind(binding)).collect();
+        let bind_conditional_effects = |effects: &[ConditionalEffect]| effects.iter().map(|e| e.bind(binding)).collect();
+
+        Self {
+            name: atom_key(&self.name, values),
+            is_durative: self.is_durative,
+            duration: self.duration,
+            duration_spec: self.duration_spec.bind(binding),
+            conditions_start: bind_conditions(&self.conditions_start),
+            conditions_over_all: bind_conditions(&self.conditions_over_all),
+            conditions_end: bind_conditions(&self.conditions_end),
+            effects_start: bind_effects(&self.effects_start),
+            effects_end: bind_effects(&self.effects_end),
+            numeric_effects_start: bind_numeric_effects(&self.numeric_effects_start),
+            numeric_effects_end: bind_numeric_effects(&self.numeric_effects_end),
+            numeric_conditions_start: bind_numeric_conditions(&self.numeric_conditions_start),
+            numeric_conditions_over_all: bind_numeric_conditions(&self.numeric_conditions_over_all),
+            numeric_conditions_end: bind_numeric_conditions(&self.numeric_conditions_end),
+            conditional_effects_start: bind_conditional_effects(&self.conditional_effects_start),
+            conditional_effects_end: bind_conditional_effects(&self.conditional_effects_end),
+        }
+    }
+
+    /// Action cost from constant `(increase (total-cost) k)` effects; `None` if the action does
+    /// not touch `total-cost`
+    pub fn cost(&self) -> Option<f64> {
+        self.cost_in(&HashMap::new())
+    }
+
+    /// Like `cost`, with fluents in `k`, e.g. `(road-length ?from ?to)`, read from `values`, the
+    /// fluents of the state the action starts in. An increase by an undefined fluent adds nothing.
+    pub fn cost_in(&self, values: &HashMap<String, f64>) -> Option<f64> {
+        let increments: Vec<f64> = self.numeric_effects_start.iter()
+            .chain(&self.numeric_effects_end)
+            .filter(|effect| effect.op == NumericOp::Increase && effect.function.key() == "total-cost")
+            .map(|effect| effect.value.evaluate(values).unwrap_or(0.0))
+            .collect();
+        (!increments.is_empty()).then(|| increments.iter().sum())
+    }
+
+    /// Every name this action passes as an argument, to a predicate or a fluent, in any of its
+    /// conditions, effects, or its duration; for a schema, its parameters and the domain
+    /// constants it mentions
+    fn arguments(&self) -> HashSet<&str> {
+        let conditional_effects = || self.conditional_effects_start.iter().chain(&self.conditional_effects_end);
+        let conditions = self.conditions_start.iter()
+            .chain(&self.conditions_over_all)
+            .chain(&self.conditions_end)
+            .chain(conditional_effects().flat_map(|effect| &effect.conditions));
+        let effects = self.effects_start.iter()
+            .chain(&self.effects_end)
+            .chain(conditional_effects().flat_map(|effect| &effect.effects));
+        let numeric_conditions = self.numeric_conditions_start.iter()
+            .chain(&self.numeric_conditions_over_all)
+            .chain(&self.numeric_conditions_end)
+            .chain(conditional_effects().flat_map(|effect| &effect.numeric_conditions));
+        let numeric_effects = self.numeric_effects_start.iter()
+            .chain(&self.numeric_effects_end)
+            .chain(conditional_effects().flat_map(|effect| &effect.numeric_effects));
+
+        let mut arguments: HashSet<&str> = conditions.flat_map(|condition| &condition.args)
+            .chain(effects.flat_map(|effect| &effect.args))
+            .map(String::as_str)
+            .collect();
+        for condition in numeric_conditions {
+            condition.left.collect_arguments(&mut arguments);
+            condition.right.collect_arguments(&mut arguments);
+        }
+        for effect in numeric_effects {
+            arguments.extend(effect.function.args.iter().map(String::as_str));
+            effect.value.collect_arguments(&mut arguments);
+        }
+        self.duration_spec.collect_arguments(&mut arguments);
+        arguments
+    }
+
+    /// An atom that this action both adds and deletes unconditionally at the same time point,
+    /// written like `Effect::key`
+    pub fn contradictory_effect(&self) -> Option<String> {
+        [&self.effects_start, &self.effects_end].into_iter().find_map(|effects| {
+            effects.iter()
+                .filter(|effect| !effect.is_delete)
+                .find(|added| effects.iter().any(|deleted| deleted.is_delete && deleted.key() == added.key()))
+                .map(Effect::key)
+        })
+    }
+}
+
+fn bind_args(args: &[String], binding: &HashMap<String, String>) -> Vec<String> {
+    args.iter()
+        .map(|arg| binding.get(arg).unwrap_or(arg).clone())
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Condition {
     pub predicate: String,
     pub args: Vec<String>,
     pub is_negative: bool,
 }
 
-#[derive(Debug, Clone)]
+impl Condition {
+    /// Key of the atom this condition tests, as used in `TemporalTask::fact_names`
+    pub fn key(&self) -> String {
+        atom_key(&self.predicate, &self.args)
+    }
+
+    /// `Some(equal)` when this tests object equality, `(= a b)`, rather than an atom. Equality
+    /// holds without a fact in the state, so these conditions are never indexed.
+    pub fn equality(&self) -> Option<bool> {
+        match (self.predicate.as_str(), self.args.as_slice()) {
+            ("=", [left, right]) => Some(left == right),
+            _ => None,
+        }
+    }
+
+    fn bind(&self, binding: &HashMap<String, String>) -> Self {
+        Self { args: bind_args(&self.args, binding), ..self.clone() }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_negative {
+            write!(f, "(not ({}))", self.key())
+        } else {
+            write!(f, "({})", self.key())
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Effect {
     pub predicate: String,
     pub args: Vec<String>,
     pub is_delete: bool,
 }
 
-#[derive(Debug, Clone)]
-pub struct TemporalTask {
-    pub initial_state: State,
-    pub goal_conditions: Vec<Condition>,
-    pub actions: Vec<TemporalAction>,
-    pub mutex_groups: Vec<MutexGroup>,
+impl Effect {
+    /// Key of the atom this effect adds or deletes
+    pub fn key(&self) -> String {
+        atom_key(&self.predicate, &self.args)
+    }
+
+    fn bind(&self, binding: &HashMap<String, String>) -> Self {
+        Self { args: bind_args(&self.args, binding), ..self.clone() }
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct State {
-    pub facts: Vec<bool>,
-    pub numeric_values: HashMap<String, f64>,
+/// A literal from the problem's `:init` that takes effect partway through, e.g. `(at 10 (door-open))`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimedLiteral {
+    pub time: f64,
+    pub effect: Effect,
 }
 
-impl PartialEq for State {
-    fn eq(&self, other: &Self) -> bool {
-        self.facts == other.facts &&
-        self.numeric_values.len() == other.numeric_values.len() &&
-        self.numeric_values.iter().all(|(k, v)| {
-            other.numeric_values.get(k).map_or(false, |ov| (v - ov).abs() < f64::EPSILON)
-        })
+fn atom_key(predicate: &str, args: &[String]) -> String {
+    if args.is_empty() {
+        predicate.to_string()
+    } else {
+        format!("{} {}", predicate, args.join(" "))
     }
 }
 
-impl Eq for State {}
-
-impl Hash for State {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.facts.hash(state);
-        for (k, v) in &self.numeric_values {
-            k.hash(state);
-            // Use a simple approximation for f64 hashing
-            let int_val = (v * 1000000.0).round() as i64;
-            int_val.hash(state);
-        }
-    }
+/// A problem object or domain constant with its declared type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypedObject {
+    pub name: String,
+    pub type_name: String,
 }
 
-#[derive(Debug, Clone)]
-pub struct MutexGroup {
-    pub facts: Vec<usize>,
+/// A predicate declared in `:predicates`, e.g. `(at ?r - robot ?l - location)`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PredicateSignature {
+    pub name: String,
+    /// Declared type of each parameter, `object` when untyped
+    pub parameter_types: Vec<String>,
 }
 
-// PDDL parsing structures
-#[derive(Debug, Clone)]
-struct PDDLDomain {
-    name: String,
-    requirements: Vec<String>,
-    types: Vec<String>,
-    predicates: Vec<PDDLPredicate>,
-    actions: Vec<PDDLAction>,
+/// Sizes of a parsed task, as reported by `TemporalTask::summary`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskSummary {
+    /// Lifted action schemas, durative or not
+    pub actions: usize,
+    /// Schemas declared with `:durative-action`
+    pub durative_actions: usize,
+    /// Ground actions left after instantiation and simplification
+    pub ground_actions: usize,
+    pub predicates: usize,
+    pub functions: usize,
+    pub objects: usize,
+    /// Ground atoms in the fact table
+    pub facts: usize,
+    /// Propositional goal conditions; numeric goals are counted separately
+    pub goal_conditions: usize,
+    pub numeric_goal_conditions: usize,
 }
 
-#[derive(Debug, Clone)]
-struct PDDLPredicate {
-    name: String,
-    parameters: Vec<PDDLParameter>,
+impl fmt::Display for TaskSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Actions: {} ({} durative, {} ground)", self.actions, self.durative_actions, self.ground_actions)?;
+        writeln!(f, "Predicates: {}", self.predicates)?;
+        writeln!(f, "Functions: {}", self.functions)?;
+        writeln!(f, "Objects: {}", self.objects)?;
+        writeln!(f, "Facts: {}", self.facts)?;
+        write!(f, "Goal conditions: {} ({} numeric)", self.goal_conditions, self.numeric_goal_conditions)
+    }
 }
 
-#[derive(Debug, Clone)]
-struct PDDLParameter {
-    name: String,
-    type_name: Option<String>,
+/// A numeric fluent declared in `:functions`, e.g. `(fuel ?v - vehicle)`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionSignature {
+    pub name: String,
+    /// Declared type of each parameter, `object` when untyped
+    pub parameter_types: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
-struct PDDLAction {
-    name: String,
-    parameters: Vec<PDDLParameter>,
-    precondition: Option<PDDLFormula>,
-    effect: Option<PDDLFormula>,
-    duration: Option<PDDLDuration>,
-    is_durative: bool,
+/// A numeric fluent reference such as `(fuel truck1)`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionTerm {
+    pub name: String,
+    pub args: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
-enum PDDLDuration {
-    Fixed(f64),
-    Variable(String),
-    Expression(Box<PDDLFormula>),
+impl FunctionTerm {
+    /// Key under which this fluent is stored in `State::numeric_values`
+    pub fn key(&self) -> String {
+        atom_key(&self.name, &self.args)
+    }
+
+    fn bind(&self, binding: &HashMap<String, String>) -> Self {
+        Self { name: self.name.clone(), args: bind_args(&self.args, binding) }
+    }
 }
 
-#[derive(Debug, Clone)]
-enum PDDLFormula {
-    Predicate {
-        name: String,
-        args: Vec<String>,
-        negated: bool,
-    },
-    And(Vec<PDDLFormula>),
-    Or(Vec<PDDLFormula>),
-    Not(Box<PDDLFormula>),
-    AtStart(Box<PDDLFormula>),
-    AtEnd(Box<PDDLFormula>),
-    OverAll(Box<PDDLFormula>),
+/// Arithmetic expression over numeric fluents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NumericExpr {
+    Number(f64),
+    Function(FunctionTerm),
+    Add(Box<NumericExpr>, Box<NumericExpr>),
+    Subtract(Box<NumericExpr>, Box<NumericExpr>),
+    Multiply(Box<NumericExpr>, Box<NumericExpr>),
+    Divide(Box<NumericExpr>, Box<NumericExpr>),
 }
 
-impl TemporalTask {
-    pub fn new() -> Self {
-        Self {
-            initial_state: State {
-                facts: Vec::new(),
-                numeric_values: HashMap::new(),
-            },
-            goal_conditions: Vec::new(),
-            actions: Vec::new(),
-            mutex_groups: Vec::new(),
+impl NumericExpr {
+    /// Evaluate against fluent values; `None` if a referenced fluent is undefined
+    pub fn evaluate(&self, values: &HashMap<String, f64>) -> Option<f64> {
+        self.evaluate_with(&|key| values.get(key).copied())
+    }
+
+    /// Evaluate with each fluent's value, by key, from `lookup`
+    pub fn evaluate_with(&self, lookup: &dyn Fn(&str) -> Option<f64>) -> Option<f64> {
+        match self {
+            NumericExpr::Number(value) => Some(*value),
+            NumericExpr::Function(term) => lookup(&term.key()),
+            NumericExpr::Add(lhs, rhs) => Some(lhs.evaluate_with(lookup)? + rhs.evaluate_with(lookup)?),
+            NumericExpr::Subtract(lhs, rhs) => Some(lhs.evaluate_with(lookup)? - rhs.evaluate_with(lookup)?),
+            NumericExpr::Multiply(lhs, rhs) => Some(lhs.evaluate_with(lookup)? * rhs.evaluate_with(lookup)?),
+            NumericExpr::Divide(lhs, rhs) => Some(lhs.evaluate_with(lookup)? / rhs.evaluate_with(lookup)?),
         }
     }
 
-    pub fn from_pddl(domain_content: &str, problem_content: &str) -> Self {
-        // Parse the PDDL domain and problem files
-        let domain = Self::parse_pddl_domain(domain_content);
-        let mut task = Self::new();
-        
-        // Convert PDDL actions to temporal actions
-        task.actions = Self::convert_pddl_actions(&domain.actions, &domain.predicates);
-        
-        // Parse problem file for initial state and goals
-        let (initial_state, goal_conditions) = Self::parse_pddl_problem(problem_content, &domain.predicates);
-        task.initial_state = initial_state;
-        task.goal_conditions = goal_conditions;
-        
-        task
+    /// Keys of the fluents the expression reads
+    fn collect_fluents(&self, fluents: &mut HashSet<String>) {
+        match self {
+            NumericExpr::Number(_) => {}
+            NumericExpr::Function(term) => {
+                fluents.insert(term.key());
+            }
+            NumericExpr::Add(lhs, rhs)
+            | NumericExpr::Subtract(lhs, rhs)
+            | NumericExpr::Multiply(lhs, rhs)
+            | NumericExpr::Divide(lhs, rhs) => {
+                lhs.collect_fluents(fluents);
+                rhs.collect_fluents(fluents);
+            }
+        }
     }
-    
-    fn parse_pddl_domain(content: &str) -> PDDLDomain {
-        let mut domain = PDDLDomain {
-            name: String::new(),
-            requirements: Vec::new(),
-            types: Vec::new(),
-            predicates: Vec::new(),
-            actions: Vec::new(),
-        };
-        
-        // Remove comments and normalize whitespace
-        let cleaned_content = Self::clean_pddl_content(content);
-        
-        // Parse domain name
-        if let Some(name) = Self::extract_domain_name(&cleaned_content) {
-            domain.name = name;
+
+    /// Arguments of the fluents the expression reads, such as `truck1` in `(fuel truck1)`
+    fn collect_arguments<'a>(&'a self, arguments: &mut HashSet<&'a str>) {
+        match self {
+            NumericExpr::Number(_) => {}
+            NumericExpr::Function(term) => arguments.extend(term.args.iter().map(String::as_str)),
+            NumericExpr::Add(lhs, rhs)
+            | NumericExpr::Subtract(lhs, rhs)
+            | NumericExpr::Multiply(lhs, rhs)
+            | NumericExpr::Divide(lhs, rhs) => {
+                lhs.collect_arguments(arguments);
+                rhs.collect_arguments(arguments);
+            }
         }
-        
-        // Parse requirements
-        domain.requirements = Self::extract_requirements(&cleaned_content);
-        
-        // Parse types
-        domain.types = Self::extract_types(&cleaned_content);
-        
-        // Parse predicates
-        domain.predicates = Self::extract_predicates(&cleaned_content);
-        
-        // Parse actions
-        domain.actions = Self::extract_actions(&cleaned_content);
-        
-        domain
     }
-    
-    fn clean_pddl_content(content: &str) -> String {
-        // Remove comments (lines starting with ;)
-        let comment_regex = Regex::new(r";.*$").unwrap();
-        let lines: Vec<&str> = content.lines().collect();
-        let cleaned_lines: Vec<String> = lines
-            .iter()
-            .map(|line| comment_regex.replace_all(line, "").to_string())
-            .collect();
-        
-        // Join lines and normalize whitespace
-        cleaned_lines.join(" ")
-            .split_whitespace()
-            .collect::<Vec<&str>>()
-            .join(" ")
+
+    fn bind(&self, binding: &HashMap<String, String>) -> Self {
+        let bind_box = |expr: &NumericExpr| Box::new(expr.bind(binding));
+        match self {
+            NumericExpr::Number(value) => NumericExpr::Number(*value),
+            NumericExpr::Function(term) => NumericExpr::Function(term.bind(binding)),
+            NumericExpr::Add(lhs, rhs) => NumericExpr::Add(bind_box(lhs), bind_box(rhs)),
+            NumericExpr::Subtract(lhs, rhs) => NumericExpr::Subtract(bind_box(lhs), bind_box(rhs)),
+            NumericExpr::Multiply(lhs, rhs) => NumericExpr::Multiply(bind_box(lhs), bind_box(rhs)),
+            NumericExpr::Divide(lhs, rhs) => NumericExpr::Divide(bind_box(lhs), bind_box(rhs)),
+        }
     }
-    
-    fn extract_domain_name(content: &str) -> Option<String> {
-        let regex = Regex::new(r"\(define\s+\(domain\s+([^)]+)\)").unwrap();
-        regex.captures(content)
-            .and_then(|caps| caps.get(1))
-            .map(|m| m.as_str().to_string())
+}
+
+/// Whether a `Metric` is to be made small or large
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetricDirection {
+    Minimize,
+    Maximize,
+}
+
+/// The problem's `(:metric minimize|maximize <expression>)`. In the expression `(total-time)`
+/// stands for the plan's makespan and `(total-cost)` for the fluent that action costs increase.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metric {
+    pub direction: MetricDirection,
+    pub expression: NumericExpr,
+}
+
+impl Metric {
+    /// Whether the expression is `(total-time)` alone
+    pub fn is_total_time(&self) -> bool {
+        matches!(&self.expression, NumericExpr::Function(term) if term.key() == "total-time")
     }
-    
-    fn extract_requirements(content: &str) -> Vec<String> {
-        let regex = Regex::new(r"\(:requirements\s+([^)]+)\)").unwrap();
-        if let Some(caps) = regex.captures(content) {
-            if let Some(reqs_str) = caps.get(1) {
-                return reqs_str.as_str()
-                    .split_whitespace()
-                    .map(|s| s.to_string())
-                    .collect();
+
+    /// Whether the expression is `(total-cost)` alone
+    pub fn is_total_cost(&self) -> bool {
+        matches!(&self.expression, NumericExpr::Function(term) if term.key() == "total-cost")
+    }
+
+    /// The expression's value in `state` reached at `time`, negated for `Maximize` so that
+    /// lower is always better; `None` if a fluent it reads is undefined
+    pub fn cost(&self, state: &State, time: f64) -> Option<f64> {
+        let value = self.expression.evaluate_with(&|key| match key {
+            "total-time" => Some(time),
+            _ => state.numeric_values.get(key).copied(),
+        })?;
+        Some(match self.direction {
+            MetricDirection::Minimize => value,
+            MetricDirection::Maximize => -value,
+        })
+    }
+}
+
+/// How long an action takes, from its `:duration` constraint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DurationSpec {
+    /// `(= ?duration <expr>)`
+    Exact(NumericExpr),
+    /// `(>= ?duration <lower>)` and/or `(<= ?duration <upper>)`. The search branches on the ends
+    /// of the range, see `choices`, and the scheduler settles each step anywhere within it. Both
+    /// ends are inclusive: `try_from_pddl` rejects the strict `<` and `>`, which `from_pddl`
+    /// reads as `<=` and `>=` with a message in `TemporalTask::warnings`.
+    Bounded {
+        lower: Option<NumericExpr>,
+        upper: Option<NumericExpr>,
+    },
+}
+
+impl DurationSpec {
+    /// Nominal duration given the fluent values when the action starts, for callers that need a
+    /// single value; `None` if a fluent is undefined or the bounds admit no duration. Bounded
+    /// durations resolve to their lower bound, or to the upper bound when the range is open at 0.
+    pub fn evaluate(&self, values: &HashMap<String, f64>) -> Option<f64> {
+        match self {
+            DurationSpec::Exact(_) => self.bounds(values).map(|(duration, _)| duration),
+            DurationSpec::Bounded { lower, upper } => {
+                let (min, max) = self.bounds(values)?;
+                match (lower, upper) {
+                    (None, Some(_)) => Some(max),
+                    _ => Some(min),
+                }
             }
         }
-        Vec::new()
     }
-    
-    fn extract_types(content: &str) -> Vec<String> {
-        let regex = Regex::new(r"\(:types\s+([^)]+)\)").unwrap();
-        if let Some(caps) = regex.captures(content) {
-            if let Some(types_str) = caps.get(1) {
-                return types_str.as_str()
-                    .split_whitespace()
-                    .filter(|s| !s.starts_with('-'))
-                    .map(|s| s.to_string())
-                    .collect();
-            }
+
+    /// Durations the search tries for one application: the exact duration, or both ends of a
+    /// bounded range, with `least` standing in for the lower end of a range open at 0. Empty if a
+    /// fluent is undefined or the range is empty.
+    pub fn choices(&self, values: &HashMap<String, f64>, least: f64) -> Vec<f64> {
+        let Some((min, max)) = self.bounds(values) else {
+            return Vec::new();
+        };
+        let shortest = if min > 0.0 { min } else { least.min(max) };
+        let mut choices = vec![shortest];
+        if max.is_finite() && max > shortest {
+            choices.push(max);
         }
-        Vec::new()
+        choices
     }
-    
-    fn extract_predicates(content: &str) -> Vec<PDDLPredicate> {
-        let mut predicates = Vec::new();
-        let predicate_regex = Regex::new(r"\(([a-zA-Z0-9_-]+)([^)]*)\)").unwrap();
-        
-        // Find the predicates section
-        if let Some(start) = content.find("(:predicates") {
-            let after_predicates = &content[start..];
-            let mut depth = 0;
-            let mut pred_section = String::new();
-            
-            for ch in after_predicates.chars() {
-                pred_section.push(ch);
-                match ch {
-                    '(' => depth += 1,
-                    ')' => {
-                        depth -= 1;
-                        if depth == 0 {
-                            break;
-                        }
-                    }
-                    _ => {}
+
+    /// Inclusive `(min, max)` range of durations allowed in `values`; `None` if a fluent is
+    /// undefined, the range is empty, or an exact duration is negative, which PDDL 2.1 makes the
+    /// action inapplicable
+    pub fn bounds(&self, values: &HashMap<String, f64>) -> Option<(f64, f64)> {
+        let (min, max) = match self {
+            DurationSpec::Exact(expr) => {
+                let duration = expr.evaluate(values).filter(|&duration| duration >= 0.0)?;
+                (duration, duration)
+            }
+            DurationSpec::Bounded { lower, upper } => {
+                let min = match lower {
+                    Some(expr) => expr.evaluate(values)?.max(0.0),
+                    None => 0.0,
+                };
+                let max = match upper {
+                    Some(expr) => expr.evaluate(values)?,
+                    None => f64::INFINITY,
+                };
+                (min, max)
+            }
+        };
+        (min <= max).then_some((min, max))
+    }
+
+    fn collect_fluents(&self, fluents: &mut HashSet<String>) {
+        match self {
+            DurationSpec::Exact(expr) => expr.collect_fluents(fluents),
+            DurationSpec::Bounded { lower, upper } => {
+                for expr in lower.iter().chain(upper) {
+                    expr.collect_fluents(fluents);
                 }
             }
-            
-            // Extract individual predicates
-            for cap in predicate_regex.captures_iter(&pred_section) {
-                if let Some(pred_name) = cap.get(1) {
-                    let name = pred_name.as_str().to_string();
-                    if name != "predicates" {
-                        let params_str = cap.get(2).map(|m| m.as_str()).unwrap_or("");
-                        let parameters = Self::parse_parameters(params_str);
-                        
-                        predicates.push(PDDLPredicate {
-                            name,
-                            parameters,
-                        });
-                    }
+        }
+    }
+
+    fn collect_arguments<'a>(&'a self, arguments: &mut HashSet<&'a str>) {
+        match self {
+            DurationSpec::Exact(expr) => expr.collect_arguments(arguments),
+            DurationSpec::Bounded { lower, upper } => {
+                for expr in lower.iter().chain(upper) {
+                    expr.collect_arguments(arguments);
                 }
             }
         }
-        
-        predicates
     }
-    
-    fn extract_actions(content: &str) -> Vec<PDDLAction> {
-        let mut actions = Vec::new();
-        let action_regex = Regex::new(r"\(:(action|durative-action)\s+([a-zA-Z0-9_-]+)").unwrap();
-        
-        for cap in action_regex.captures_iter(content) {
-            if let Some(action_name) = cap.get(2) {
-                let action_type = cap.get(1).unwrap().as_str();
-                let name = action_name.as_str().to_string();
-                let action_start = cap.get(0).unwrap().start();
-                
-                // Find the complete action definition
-                let action_content = Self::extract_balanced_expression(&content[action_start..]);
-                
-                // Parse action components
-                let parameters = Self::extract_action_parameters(&action_content);
-                let (precondition, effect, duration, is_durative) = if action_type == "durative-action" {
-                    // Parse durative action
-                    let condition = Self::extract_action_condition(&action_content);
-                    let effect = Self::extract_action_effect(&action_content);
-                    let duration = Self::extract_action_duration(&action_content);
-                    (condition, effect, Some(duration), true)
-                } else {
-                    // Parse regular action
-                    let precondition = Self::extract_action_precondition(&action_content);
-                    let effect = Self::extract_action_effect(&action_content);
-                    (precondition, effect, None, false)
-                };
-                
-                actions.push(PDDLAction {
-                    name,
-                    parameters,
-                    precondition,
-                    effect,
-                    duration,
-                    is_durative,
+
+    fn bind(&self, binding: &HashMap<String, String>) -> Self {
+        match self {
+            DurationSpec::Exact(expr) => DurationSpec::Exact(expr.bind(binding)),
+            DurationSpec::Bounded { lower, upper } => DurationSpec::Bounded {
+                lower: lower.as_ref().map(|expr| expr.bind(binding)),
+                upper: upper.as_ref().map(|expr| expr.bind(binding)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NumericOp {
+    Increase,
+    Decrease,
+    Assign,
+    ScaleUp,
+    ScaleDown,
+}
+
+/// Effect on a numeric fluent, e.g. `(increase (fuel ?v) 5)`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NumericEffect {
+    pub function: FunctionTerm,
+    pub op: NumericOp,
+    pub value: NumericExpr,
+}
+
+impl NumericEffect {
+    /// Compute the fluent's new value given the current fluent values
+    pub fn resulting_value(&self, values: &HashMap<String, f64>) -> Option<f64> {
+        let operand = self.value.evaluate(values)?;
+        let current = values.get(&self.function.key()).copied();
+        match self.op {
+            NumericOp::Assign => Some(operand),
+            // Unset fluents accumulate from zero so `(increase (total-cost) n)` works without init
+            NumericOp::Increase => Some(current.unwrap_or(0.0) + operand),
+            NumericOp::Decrease => Some(current.unwrap_or(0.0) - operand),
+            NumericOp::ScaleUp => Some(current? * operand),
+            NumericOp::ScaleDown => Some(current? / operand),
+        }
+    }
+
+    fn bind(&self, binding: &HashMap<String, String>) -> Self {
+        Self {
+            function: self.function.bind(binding),
+            op: self.op,
+            value: self.value.bind(binding),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    Less,
+    LessEqual,
+    Equal,
+    GreaterEqual,
+    Greater,
+    /// Written `(not (= a b))`; PDDL has no operator for it
+    NotEqual,
+}
+
+impl Comparison {
+    /// Compares the values rounded to the grid `State` equality uses, so states that count as the
+    /// same agree on every condition; nothing holds of NaN
+    pub fn holds(&self, lhs: f64, rhs: f64) -> bool {
+        if lhs.is_nan() || rhs.is_nan() {
+            return false;
+        }
+        let (lhs, rhs) = (quantize(lhs), quantize(rhs));
+        match self {
+            Comparison::Less => lhs < rhs,
+            Comparison::LessEqual => lhs <= rhs,
+            Comparison::Equal => lhs == rhs,
+            Comparison::GreaterEqual => lhs >= rhs,
+            Comparison::Greater => lhs > rhs,
+            Comparison::NotEqual => lhs != rhs,
+        }
+    }
+
+    /// The comparison that holds exactly when this one does not, for `(not (>= a b))` and the like
+    pub fn negated(&self) -> Self {
+        match self {
+            Comparison::Less => Comparison::GreaterEqual,
+            Comparison::LessEqual => Comparison::Greater,
+            Comparison::Equal => Comparison::NotEqual,
+            Comparison::GreaterEqual => Comparison::Less,
+            Comparison::Greater => Comparison::LessEqual,
+            Comparison::NotEqual => Comparison::Equal,
+        }
+    }
+}
+
+/// Numeric precondition such as `(>= (fuel ?v) 10)`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NumericCondition {
+    pub comparison: Comparison,
+    pub left: NumericExpr,
+    pub right: NumericExpr,
+}
+
+impl NumericCondition {
+    /// Comparisons over undefined fluents are false
+    pub fn is_satisfied(&self, values: &HashMap<String, f64>) -> bool {
+        match (self.left.evaluate(values), self.right.evaluate(values)) {
+            (Some(lhs), Some(rhs)) => self.comparison.holds(lhs, rhs),
+            _ => false,
+        }
+    }
+
+    /// Keys of the fluents either side reads
+    pub(crate) fn collect_fluents(&self, fluents: &mut HashSet<String>) {
+        self.left.collect_fluents(fluents);
+        self.right.collect_fluents(fluents);
+    }
+
+    fn bind(&self, binding: &HashMap<String, String>) -> Self {
+        Self {
+            comparison: self.comparison,
+            left: self.left.bind(binding),
+            right: self.right.bind(binding),
+        }
+    }
+}
+
+/// `(when <antecedent> <consequent>)`: the effects apply only if the antecedent holds in the
+/// state just before the effect's time point. Temporal annotations inside the antecedent are
+/// not distinguished; it is evaluated when the effects would fire.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConditionalEffect {
+    pub conditions: Vec<Condition>,
+    pub numeric_conditions: Vec<NumericCondition>,
+    pub effects: Vec<Effect>,
+    pub numeric_effects: Vec<NumericEffect>,
+}
+
+impl ConditionalEffect {
+    fn bind(&self, binding: &HashMap<String, String>) -> Self {
+        Self {
+            conditions: self.conditions.iter().map(|c| c.bind(binding)).collect(),
+            numeric_conditions: self.numeric_conditions.iter().map(|c| c.bind(binding)).collect(),
+            effects: self.effects.iter().map(|e| e.bind(binding)).collect(),
+            numeric_effects: self.numeric_effects.iter().map(|e| e.bind(binding)).collect(),
+        }
+    }
+}
+
+/// Which analyses `TemporalTask::preprocess_with` runs; reachability pruning always does
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PreprocessOptions {
+    /// Also drop actions that cannot contribute, directly or through other actions, to the goal,
+    /// the trajectory constraints, or the metric
+    pub relevance: bool,
+}
+
+/// Derived predicate rule `(:derived (predicate args...) body)`: the atom holds exactly when it
+/// follows from the basic facts through the task's rules
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Axiom {
+    pub predicate: String,
+    pub args: Vec<String>,
+    pub conditions: Vec<Condition>,
+    pub numeric_conditions: Vec<NumericCondition>,
+}
+
+impl Axiom {
+    /// Key of the derived atom
+    pub fn key(&self) -> String {
+        atom_key(&self.predicate, &self.args)
+    }
+
+    fn bind(&self, binding: &HashMap<String, String>) -> Self {
+        Self {
+            predicate: self.predicate.clone(),
+            args: bind_args(&self.args, binding),
+            conditions: self.conditions.iter().map(|c| c.bind(binding)).collect(),
+            numeric_conditions: self.numeric_conditions.iter().map(|c| c.bind(binding)).collect(),
+        }
+    }
+}
+
+/// Why `TemporalTask::try_from_pddl` rejected its input; `file` is "domain" or "problem" and
+/// positions are 1-based
+#[derive(Debug, Error, PartialEq)]
+pub enum PddlParseError {
+    #[error("{file} line {line}, column {column}: unmatched ')'")]
+    UnmatchedCloseParen { file: &'static str, line: usize, column: usize },
+    #[error("{file} line {line}, column {column}: '(' is never closed")]
+    UnclosedParen { file: &'static str, line: usize, column: usize },
+    #[error("{file} line {line}, column {column}: unknown section '{section}'")]
+    UnknownSection { file: &'static str, section: String, line: usize, column: usize },
+    #[error("{file} line {line}, column {column}: unexpected token '{token}', expected {expected}")]
+    UnexpectedToken { file: &'static str, token: String, expected: &'static str, line: usize, column: usize },
+    #[error("{file} line {line}, column {column}: predicate '{predicate}' used in {context} is not declared in :predicates")]
+    UndeclaredPredicate { file: &'static str, predicate: String, context: String, line: usize, column: usize },
+    #[error("{file} line {line}, column {column}: predicate '{predicate}' used in {context} with {found} argument(s), but declared with {expected}")]
+    ArityMismatch { file: &'static str, predicate: String, context: String, expected: usize, found: usize, line: usize, column: usize },
+    #[error("domain: action '{action}' both adds and deletes ({atom}) at the same time point")]
+    ContradictoryEffects { action: String, atom: String },
+    #[error("problem line {line}, column {column}: argument '{argument}' of ({predicate} ...) in the initial state has type {found}, but the predicate expects {expected}")]
+    ArgumentTypeMismatch {
+        predicate: String,
+        argument: String,
+        expected: String,
+        found: String,
+        line: usize,
+        column: usize,
+    },
+    #[error("domain line {line}, column {column}: effect {effect} of durative action '{action}' is not wrapped in (at start ...) or (at end ...)")]
+    UntimedEffect { action: String, effect: String, line: usize, column: usize },
+    #[error("{file} line {line}, column {column}: {construct} in {context} needs the {requirement} requirement, which is not declared")]
+    UndeclaredRequirement {
+        file: &'static str,
+        requirement: &'static str,
+        construct: &'static str,
+        context: String,
+        line: usize,
+        column: usize,
+    },
+    #[error("{file} line {line}, column {column}: {construct} in {context} is not supported")]
+    UnsupportedConstruct { file: &'static str, construct: &'static str, context: String, line: usize, column: usize },
+}
+
+const DOMAIN_SECTIONS: &[&str] = &[
+    ":requirements", ":types", ":constants", ":predicates", ":functions",
+    ":action", ":durative-action", ":derived", ":constraints",
+];
+
+const PROBLEM_SECTIONS: &[&str] = &[
+    ":domain", ":requirements", ":objects", ":init", ":goal", ":metric", ":constraints",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemporalTask {
+    /// Flags declared in the domain's and problem's `:requirements`, e.g. `:durative-actions`
+    pub requirements: Vec<String>,
+    pub initial_state: State,
+    /// Timed initial literals, in `:init` order
+    pub timed_literals: Vec<TimedLiteral>,
+    pub goal_conditions: Vec<Condition>,
+    pub numeric_goal_conditions: Vec<NumericCondition>,
+    /// What plans should optimize, from the problem's `:metric`
+    pub metric: Option<Metric>,
+    /// Conditions from `(:constraints (always ...))` that every visited state must satisfy
+    pub always_constraints: Vec<Condition>,
+    /// Lifted action schemas as written in the domain, one per disjunct of a disjunctive
+    /// condition
+    pub actions: Vec<TemporalAction>,
+    /// Actions instantiated with every type-compatible binding of objects; plans index into this
+    pub ground_actions: Vec<TemporalAction>,
+    /// Ground derived-predicate rules, re-evaluated after every state change
+    pub axioms: Vec<Axiom>,
+    /// Predicates declared in the domain's `:predicates` section
+    pub predicates: Vec<PredicateSignature>,
+    /// Numeric fluents declared in the domain's `:functions` section
+    pub functions: Vec<FunctionSignature>,
+    pub objects: Vec<TypedObject>,
+    /// Declared supertype of each domain type
+    pub type_parents: HashMap<String, String>,
+    /// Ground atom for each index of `State::facts`, e.g. `"at robot1 depot"`
+    pub fact_names: Vec<String>,
+    pub fact_ids: HashMap<String, usize>,
+    /// Groups of facts of which at most one holds in any reachable state
+    pub mutex_groups: Vec<MutexGroup>,
+    /// Problems the parser worked around, such as duplicated sections
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct State {
+    pub facts: FactSet,
+    pub numeric_values: HashMap<String, f64>,
+}
+
+/// Steps per unit at which fluent values identify a state. `PartialEq` and `Hash` for `State`
+/// both compare values rounded to this grid, so equal states always hash alike.
+const FLUENT_RESOLUTION: f64 = 1000000.0;
+
+fn quantize(value: f64) -> i64 {
+    (value * FLUENT_RESOLUTION).round() as i64
+}
+
+impl PartialEq for State {
+    fn eq(&self, other: &Self) -> bool {
+        self.facts == other.facts &&
+        self.numeric_values.len() == other.numeric_values.len() &&
+        self.numeric_values.iter().all(|(k, v)| {
+            other.numeric_values.get(k).is_some_and(|ov| quantize(*v) == quantize(*ov))
+        })
+    }
+}
+
+impl Eq for State {}
+
+impl Hash for State {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.facts.hash(state);
+        // Sorted, since two equal maps may iterate in different orders
+        let mut fluents: Vec<(&String, i64)> = self.numeric_values.iter()
+            .map(|(k, v)| (k, quantize(*v)))
+            .collect();
+        fluents.sort_unstable();
+        fluents.hash(state);
+    }
+}
+
+/// Truth value of each fact, packed 64 to a word so that comparing and hashing a state touches
+/// one `u64` per 64 facts. Indexing reads like a `Vec<bool>`; writes go through `set`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FactSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl FactSet {
+    /// `len` facts, all false
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, fact: usize) -> bool {
+        assert!(fact < self.len, "fact {} out of range for {} facts", fact, self.len);
+        self.words[fact / 64] & (1 << (fact % 64)) != 0
+    }
+
+    pub fn set(&mut self, fact: usize, holds: bool) {
+        assert!(fact < self.len, "fact {} out of range for {} facts", fact, self.len);
+        let bit = 1 << (fact % 64);
+        if holds {
+            self.words[fact / 64] |= bit;
+        } else {
+            self.words[fact / 64] &= !bit;
+        }
+    }
+
+    /// Number of facts that hold
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|fact| self.contains(fact))
+    }
+
+    /// Indices of the facts that hold, in increasing order
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, &word)| Self::word_ones(index, word))
+    }
+
+    /// Indices of the facts that hold in exactly one of `self` and `other`, in increasing order;
+    /// both must have the same length
+    pub fn differences<'a>(&'a self, other: &'a FactSet) -> impl Iterator<Item = usize> + 'a {
+        assert_eq!(self.len, other.len, "comparing fact sets of different lengths");
+        self.words.iter()
+            .zip(&other.words)
+            .enumerate()
+            .flat_map(|(index, (&word, &other))| Self::word_ones(index, word ^ other))
+    }
+
+    /// Indices of the set bits of the `index`th word
+    fn word_ones(index: usize, word: u64) -> impl Iterator<Item = usize> {
+        let mut remaining = word;
+        std::iter::from_fn(move || {
+            (remaining != 0).then(|| {
+                let bit = remaining.trailing_zeros() as usize;
+                remaining &= remaining - 1;
+                index * 64 + bit
+            })
+        })
+    }
+}
+
+impl Index<usize> for FactSet {
+    type Output = bool;
+
+    fn index(&self, fact: usize) -> &bool {
+        if self.contains(fact) { &true } else { &false }
+    }
+}
+
+impl FromIterator<bool> for FactSet {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut facts = Self::default();
+        for holds in iter {
+            if facts.len % 64 == 0 {
+                facts.words.push(0);
+            }
+            facts.len += 1;
+            facts.set(facts.len - 1, holds);
+        }
+        facts
+    }
+}
+
+impl From<Vec<bool>> for FactSet {
+    fn from(facts: Vec<bool>) -> Self {
+        facts.into_iter().collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MutexGroup {
+    pub facts: Vec<usize>,
+}
+
+/// Cap on the candidate invariants `infer_mutex_groups` explores before it stops refining
+const MAX_INVARIANT_CANDIDATES: usize = 10_000;
+
+/// One predicate's share of a candidate invariant: the argument positions that hold the
+/// invariant's parameters, in parameter order, and at most one free position ranging over any object
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct InvariantPart {
+    predicate: String,
+    order: Vec<usize>,
+    counted: Option<usize>,
+}
+
+impl InvariantPart {
+    fn arity(&self) -> usize {
+        self.order.len() + usize::from(self.counted.is_some())
+    }
+
+    fn matches(&self, predicate: &str, args: &[String]) -> bool {
+        self.predicate == predicate && self.arity() == args.len()
+    }
+
+    /// The arguments of a matching atom standing for the invariant's parameters
+    fn parameters<'a, S: AsRef<str>>(&self, args: &'a [S]) -> Vec<&'a str> {
+        self.order.iter().map(|&position| args[position].as_ref()).collect()
+    }
+
+    /// Part for the atom `predicate args` binding the invariant's parameters to `terms`; `None`
+    /// unless each term is a distinct argument and at most one argument is left over
+    fn covering(predicate: &str, args: &[String], terms: &[&str]) -> Option<Self> {
+        let order: Vec<usize> = terms.iter()
+            .map(|term| args.iter().position(|arg| arg == term))
+            .collect::<Option<_>>()?;
+        let mut left: Vec<usize> = (0..args.len()).filter(|position| !order.contains(position)).collect();
+        if order.len() + left.len() != args.len() || left.len() > 1 {
+            return None;
+        }
+        Some(InvariantPart { predicate: predicate.to_string(), order, counted: left.pop() })
+    }
+}
+
+// PDDL parsing structures
+#[derive(Debug, Clone)]
+struct PDDLDomain {
+    requirements: Vec<String>,
+    type_parents: HashMap<String, String>,
+    constants: Vec<TypedObject>,
+    predicates: Vec<PDDLPredicate>,
+    functions: Vec<PDDLPredicate>,
+    actions: Vec<PDDLAction>,
+    axioms: Vec<PDDLAxiom>,
+    always_constraints: Vec<Condition>,
+}
+
+/// A domain parsed once, to build tasks for many problems with
+/// `TemporalTask::from_domain_and_problem` without reading the domain again
+#[derive(Debug, Clone)]
+pub struct ParsedDomain {
+    domain: PDDLDomain,
+    /// The domain's schemas as temporal actions
+    actions: Vec<TemporalAction>,
+    /// Sections of the domain's `(define ...)`, for the checks of `try_from_domain_and_problem`
+    sections: Vec<SExpr>,
+}
+
+impl ParsedDomain {
+    /// Parse the `(define (domain ...))` in `domain_content` leniently, as `TemporalTask::from_pddl` does
+    pub fn parse(domain_content: &str) -> Self {
+        Self::from_forms(&sexpr::read_lenient(domain_content))
+    }
+
+    /// Parse `domain_content`, rejecting the malformed domains `TemporalTask::try_from_pddl`
+    /// rejects before looking at a problem: unbalanced parentheses, unknown sections, and stray
+    /// tokens
+    pub fn try_parse(domain_content: &str) -> Result<Self, PddlParseError> {
+        Ok(Self::from_sections(TemporalTask::read_define(domain_content, "domain", DOMAIN_SECTIONS)?))
+    }
+
+    fn from_forms(forms: &[SExpr]) -> Self {
+        let sections = TemporalTask::find_define(forms, "domain")
+            .and_then(|define| define.items().get(2..))
+            .unwrap_or_default();
+        Self::from_sections(sections.to_vec())
+    }
+
+    fn from_sections(sections: Vec<SExpr>) -> Self {
+        let domain = TemporalTask::parse_pddl_domain(&sections);
+        let actions = TemporalTask::convert_pddl_actions(&domain.actions, &domain.predicates);
+        Self { domain, actions, sections }
+    }
+
+    /// Lifted action schemas, as every task built from this domain has them; a schema with a
+    /// disjunctive condition appears once per disjunct
+    pub fn actions(&self) -> &[TemporalAction] {
+        &self.actions
+    }
+}
+
+/// Lifted derived-predicate rule with the typed parameters of its head
+#[derive(Debug, Clone)]
+struct PDDLAxiom {
+    parameters: Vec<PDDLParameter>,
+    axiom: Axiom,
+}
+
+#[derive(Debug, Clone)]
+struct PDDLProblem {
+    requirements: Vec<String>,
+    objects: Vec<TypedObject>,
+    init_atoms: Vec<String>,
+    numeric_values: HashMap<String, f64>,
+    timed_literals: Vec<TimedLiteral>,
+    goals: ConditionBucket,
+    metric: Option<Metric>,
+    always_constraints: Vec<Condition>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct PDDLPredicate {
+    name: String,
+    parameters: Vec<PDDLParameter>,
+}
+
+#[derive(Debug, Clone)]
+struct PDDLParameter {
+    name: String,
+    type_name: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct PDDLAction {
+    name: String,
+    parameters: Vec<PDDLParameter>,
+    precondition: Option<PDDLFormula>,
+    effect: Option<PDDLFormula>,
+    duration: Option<DurationSpec>,
+    /// The duration constraint has a strict `<` or `>`, read as `<=` or `>=`
+    strict_duration: bool,
+    is_durative: bool,
+}
+
+#[derive(Debug, Clone)]
+enum PDDLFormula {
+    Predicate {
+        name: String,
+        args: Vec<String>,
+        negated: bool,
+    },
+    And(Vec<PDDLFormula>),
+    Or(Vec<PDDLFormula>),
+    Not(Box<PDDLFormula>),
+    AtStart(Box<PDDLFormula>),
+    AtEnd(Box<PDDLFormula>),
+    OverAll(Box<PDDLFormula>),
+    Always(Box<PDDLFormula>),
+    NumericEffect(NumericEffect),
+    NumericComparison(NumericCondition),
+    /// `(when <antecedent> <consequent>)`
+    When(Box<PDDLFormula>, Box<PDDLFormula>),
+    /// `(exists (?y - type) <body>)`, only expanded in derived-predicate bodies
+    Exists(Vec<PDDLParameter>, Box<PDDLFormula>),
+}
+
+/// A keyword of an action section with the value that follows it, e.g. `:condition` and the
+/// condition formula, as `TemporalTask::try_from_pddl` checks them
+struct ActionField<'a> {
+    action: String,
+    durative: bool,
+    keyword: &'a str,
+    body: &'a SExpr,
+}
+
+impl ActionField<'_> {
+    /// Whether this is the action's condition, which `:durative-action` calls `:condition` and
+    /// `:action` `:precondition`; either is accepted for both
+    fn is_condition(&self) -> bool {
+        matches!(self.keyword, ":precondition" | ":condition")
+    }
+
+    /// How errors name the action
+    fn context(&self) -> String {
+        format!("action '{}'", self.action)
+    }
+}
+
+/// Boolean and numeric effects collected from one effect formula
+#[derive(Debug, Default)]
+struct EffectBucket {
+    effects: Vec<Effect>,
+    numeric_effects: Vec<NumericEffect>,
+    conditional_effects: Vec<ConditionalEffect>,
+}
+
+/// Boolean and numeric conditions collected from one condition formula
+#[derive(Debug, Clone, Default)]
+struct ConditionBucket {
+    conditions: Vec<Condition>,
+    numeric_conditions: Vec<NumericCondition>,
+}
+
+impl TemporalTask {
+    pub fn new() -> Self {
+        Self {
+            requirements: Vec::new(),
+            initial_state: State {
+                facts: FactSet::default(),
+                numeric_values: HashMap::new(),
+            },
+            timed_literals: Vec::new(),
+            goal_conditions: Vec::new(),
+            numeric_goal_conditions: Vec::new(),
+            metric: None,
+            always_constraints: Vec::new(),
+            actions: Vec::new(),
+            ground_actions: Vec::new(),
+            axioms: Vec::new(),
+            predicates: Vec::new(),
+            functions: Vec::new(),
+            objects: Vec::new(),
+            type_parents: HashMap::new(),
+            fact_names: Vec::new(),
+            fact_ids: HashMap::new(),
+            mutex_groups: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Parse a domain and problem, rejecting unbalanced parentheses, unknown sections, stray
+    /// tokens, predicates that the domain does not declare or that are used with a different
+    /// number of arguments, negated or disjunctive conditions without the requirement that allows
+    /// them, conditions the task cannot represent (`imply`, `forall`, negated compound conditions,
+    /// `exists` outside derived predicates, and disjunctive goals), durative-action effects
+    /// outside `at start`/`at end`, strict duration inequalities, initial facts whose objects do
+    /// not have the types the predicate declares, and actions that add and delete the same atom
+    /// at the same time point
+    pub fn try_from_pddl(domain_content: &str, problem_content: &str) -> Result<Self, PddlParseError> {
+        Self::try_from_domain_and_problem(&ParsedDomain::try_parse(domain_content)?, problem_content)
+    }
+
+    /// Strict parse of a problem against a domain parsed earlier; gives the same result as
+    /// `try_from_pddl` on the domain's text and `problem_content` when the domain was read by
+    /// `ParsedDomain::try_parse`
+    pub fn try_from_domain_and_problem(parsed: &ParsedDomain, problem_content: &str) -> Result<Self, PddlParseError> {
+        let domain = &parsed.sections;
+        let problem = Self::read_define(problem_content, "problem", PROBLEM_SECTIONS)?;
+
+        let mut declared = Vec::new();
+        for section in domain {
+            match section.head() {
+                Some(":predicates") => {
+                    for declaration in &section.items()[1..] {
+                        let name = declaration.head()
+                            .filter(|name| !name.starts_with('?'))
+                            .ok_or_else(|| Self::unexpected(declaration, "domain", "a predicate declaration like (name ?arg)"))?;
+                        if let Some(arg) = declaration.items()[1..].iter().find(|arg| arg.as_atom().is_none()) {
+                            return Err(Self::unexpected(arg, "domain", "a parameter name or type"));
+                        }
+                        let arity = declaration.items()[1..].iter()
+                            .filter(|arg| arg.as_atom().is_some_and(|arg| arg.starts_with('?')))
+                            .count();
+                        declared.push((name.to_string(), arity));
+                    }
+                }
+                // Derived predicates are declared by their rule heads
+                Some(":derived") => declared.extend(section.items().get(1).and_then(|head| {
+                    let arity = head.items().iter()
+                        .filter(|arg| arg.as_atom().is_some_and(|arg| arg.starts_with('?')))
+                        .count();
+                    Some((head.head()?.to_string(), arity))
+                })),
+                _ => {}
+            }
+        }
+
+        let check = |formulas: &[SExpr], file, context: &str| {
+            Self::check_predicates_declared(formulas, &declared, file, context)
+        };
+        for field in Self::action_fields(domain).filter(|field| field.is_condition() || field.keyword == ":effect") {
+            check(std::slice::from_ref(field.body), "domain", &field.context())?;
+        }
+        for section in domain.iter().filter(|section| section.head() == Some(":constraints")) {
+            check(&section.items()[1..], "domain", "the constraints")?;
+        }
+        for section in &problem {
+            let context = match section.head() {
+                Some(":init") => "the initial state",
+                Some(":goal") => "the goal",
+                Some(":constraints") => "the constraints",
+                _ => continue,
+            };
+            check(&section.items()[1..], "problem", context)?;
+        }
+
+        let requirements: Vec<&str> = domain.iter().chain(&problem)
+            .filter(|section| section.head() == Some(":requirements"))
+            .flat_map(|section| section.items()[1..].iter().filter_map(SExpr::as_atom))
+            .collect();
+        let check = |formulas: &[SExpr], file, context: &str| {
+            Self::check_condition_requirements(formulas, &requirements, file, context)
+        };
+        for field in Self::action_fields(domain).filter(ActionField::is_condition) {
+            check(std::slice::from_ref(field.body), "domain", &field.context())?;
+        }
+        for section in domain.iter().filter(|section| section.head() == Some(":derived")) {
+            check(section.items().get(2..).unwrap_or_default(), "domain", "a derived predicate")?;
+        }
+        for field in Self::action_fields(domain).filter(|field| field.durative && field.keyword == ":effect") {
+            Self::check_effects_timed(field.body, &field.action)?;
+        }
+        for field in Self::action_fields(domain).filter(|field| field.keyword == ":duration") {
+            Self::check_duration_inclusive(field.body, &field.context())?;
+        }
+        for section in problem.iter().filter(|section| section.head() == Some(":goal")) {
+            check(&section.items()[1..], "problem", "the goal")?;
+        }
+        // Action and derived-predicate conditions are split at their disjunctions; a goal is one
+        // conjunction. Only derived-predicate rules are grounded over existential variables
+        for field in Self::action_fields(domain).filter(ActionField::is_condition) {
+            Self::check_conditions_supported(std::slice::from_ref(field.body), true, false, "domain", &field.context())?;
+        }
+        for section in domain.iter().filter(|section| section.head() == Some(":derived")) {
+            let body = section.items().get(2..).unwrap_or_default();
+            Self::check_conditions_supported(body, true, true, "domain", "a derived predicate")?;
+        }
+        for section in problem.iter().filter(|section| section.head() == Some(":goal")) {
+            Self::check_conditions_supported(&section.items()[1..], false, false, "problem", "the goal")?;
+        }
+
+        // The lenient parser resolves these delete-before-add, leaving the atom true
+        let task = Self::from_parsed_domain(parsed, &sexpr::read_lenient(problem_content));
+        if let Some((action, atom)) = task.actions.iter()
+            .find_map(|action| Some((action.name.clone(), action.contradictory_effect()?)))
+        {
+            return Err(PddlParseError::ContradictoryEffects { action, atom });
+        }
+        for section in problem.iter().filter(|section| section.head() == Some(":init")) {
+            for literal in &section.items()[1..] {
+                task.check_fact_types(literal)?;
+            }
+        }
+        Ok(task)
+    }
+
+    /// Lenient parse: malformed input yields whatever could be recovered, possibly an empty task
+    pub fn from_pddl(domain_content: &str, problem_content: &str) -> Self {
+        let domain_forms = sexpr::read_lenient(domain_content);
+        let problem_forms = sexpr::read_lenient(problem_content);
+        Self::from_forms(&domain_forms, &problem_forms)
+    }
+
+    /// Lenient parse of a problem against a domain parsed earlier; gives the same task as
+    /// `from_pddl` on the domain's text and `problem_content`
+    pub fn from_domain_and_problem(domain: &ParsedDomain, problem_content: &str) -> Self {
+        Self::from_parsed_domain(domain, &sexpr::read_lenient(problem_content))
+    }
+
+    /// Build a task from the `(define (domain ...))` and `(define (problem ...))` forms found
+    /// among the given top-level expressions
+    fn from_forms(domain_forms: &[SExpr], problem_forms: &[SExpr]) -> Self {
+        Self::from_parsed_domain(&ParsedDomain::from_forms(domain_forms), problem_forms)
+    }
+
+    /// Build a task for the `(define (problem ...))` among `problem_forms` over `parsed`
+    fn from_parsed_domain(parsed: &ParsedDomain, problem_forms: &[SExpr]) -> Self {
+        let domain = &parsed.domain;
+        let mut task = Self::new();
+        task.actions = parsed.actions.clone();
+        
+        // Parse problem file for initial state and goals
+        let problem = Self::parse_pddl_problem(problem_forms, &domain.predicates);
+        task.goal_conditions = problem.goals.conditions;
+        task.timed_literals = problem.timed_literals;
+        task.numeric_goal_conditions = problem.goals.numeric_conditions;
+        task.metric = problem.metric;
+        task.always_constraints = domain.always_constraints.clone();
+        task.always_constraints.extend(problem.always_constraints);
+        task.warnings = problem.warnings;
+        for (i, action) in domain.actions.iter().enumerate().filter(|(_, action)| action.is_durative) {
+            // The variants of a disjunctive condition are adjacent and share their effects
+            let variant = i > 0 && domain.actions[i - 1].name == action.name;
+            if !variant && action.effect.as_ref().is_some_and(Self::has_untimed_effect) {
+                task.warnings.push(format!(
+                    "durative action '{}' has effects outside (at start ...) or (at end ...); they are applied at the end",
+                    action.name,
+                ));
+            }
+            if !variant && action.strict_duration {
+                task.warnings.push(format!(
+                    "durative action '{}' bounds its duration with a strict < or >; it is read as <= or >=",
+                    action.name,
+                ));
+            }
+        }
+        task.requirements = domain.requirements.clone();
+        for requirement in problem.requirements {
+            if !task.requirements.contains(&requirement) {
+                task.requirements.push(requirement);
+            }
+        }
+
+        // Instantiate the schemas over the declared objects
+        task.type_parents = domain.type_parents.clone();
+        task.objects = domain.constants.clone();
+        task.objects.extend(problem.objects);
+        task.ground_actions = Self::ground_actions(&task.actions, &domain.actions, &task.objects, &task.type_parents);
+        task.axioms = domain.axioms.iter()
+            .flat_map(|lifted| {
+                Self::parameter_bindings(&lifted.parameters, &task.objects, &task.type_parents)
+                    .into_iter()
+                    .map(|(binding, _)| lifted.axiom.bind(&binding))
+            })
+            .collect();
+
+        task.index_facts(&problem.init_atoms);
+        task.initial_state.facts = FactSet::new(task.fact_names.len());
+        for atom in &problem.init_atoms {
+            task.initial_state.facts.set(task.fact_ids[atom], true);
+        }
+        task.initial_state.numeric_values = problem.numeric_values;
+        // Declared fluents without an `:init` value start at zero
+        for function in &domain.functions {
+            for (_, values) in Self::parameter_bindings(&function.parameters, &task.objects, &task.type_parents) {
+                task.initial_state.numeric_values.entry(atom_key(&function.name, &values)).or_insert(0.0);
+            }
+        }
+        task.predicates = domain.predicates.iter()
+            .map(|predicate| PredicateSignature {
+                name: predicate.name.clone(),
+                parameter_types: predicate.parameters.iter()
+                    .map(|param| param.type_name.clone().unwrap_or_else(|| "object".to_string()))
+                    .collect(),
+            })
+            .collect();
+        task.functions = domain.functions.iter()
+            .map(|function| FunctionSignature {
+                name: function.name.clone(),
+                parameter_types: function.parameters.iter()
+                    .map(|param| param.type_name.clone().unwrap_or_else(|| "object".to_string()))
+                    .collect(),
+            })
+            .collect();
+        let mut initial_state = task.initial_state.clone();
+        task.apply_axioms(&mut initial_state);
+        task.initial_state = initial_state;
+        task.simplify_ground_actions();
+        task.mutex_groups = task.infer_mutex_groups();
+        
+        task
+    }
+
+    /// Write the parsed and grounded task to `path` in bincode, to skip parsing on later runs
+    pub fn save_binary(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        let writer = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(writer, self)?;
+        Ok(())
+    }
+
+    /// Read a task written by `save_binary`
+    pub fn load_binary(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(bincode::deserialize_from(reader)?)
+    }
+
+    /// The parsed and grounded task as JSON, for caching or for building tasks without PDDL
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Read a task written by `to_json`
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Counts of the task's actions, predicates, objects, and goals, for reporting what was
+    /// parsed without searching
+    pub fn summary(&self) -> TaskSummary {
+        // The variants a disjunctive condition splits a schema into are adjacent
+        let mut schemas: Vec<(&str, bool)> = self.actions.iter()
+            .map(|action| (action.name.as_str(), action.is_durative))
+            .collect();
+        schemas.dedup();
+        TaskSummary {
+            actions: schemas.len(),
+            durative_actions: schemas.iter().filter(|&&(_, is_durative)| is_durative).count(),
+            ground_actions: self.ground_actions.len(),
+            predicates: self.predicates.len(),
+            functions: self.functions.len(),
+            objects: self.objects.len(),
+            facts: self.fact_names.len(),
+            goal_conditions: self.goal_conditions.len(),
+            numeric_goal_conditions: self.numeric_goal_conditions.len(),
+        }
+    }
+
+    /// Index of the ground atom `(predicate args...)` in `State::facts`
+    pub fn fact_id(&self, predicate: &str, args: &[String]) -> Option<usize> {
+        self.fact_ids.get(&atom_key(predicate, args)).copied()
+    }
+
+    /// Requirements the task declares, plus those its content shows it needs without declaring
+    /// them: numeric fluents, negative conditions, conditional effects, derived predicates, and
+    /// trajectory constraints. Sorted.
+    pub fn used_requirements(&self) -> Vec<String> {
+        let actions = &self.ground_actions;
+        let conditions = || actions.iter()
+            .flat_map(|action| action.conditions_start.iter().chain(&action.conditions_over_all).chain(&action.conditions_end))
+            .chain(&self.goal_conditions);
+        let inferred = [
+            (":numeric-fluents", !self.functions.is_empty()),
+            (":negative-preconditions", conditions().any(|condition| condition.is_negative)),
+            (
+                ":conditional-effects",
+                actions.iter().any(|action| !action.conditional_effects_start.is_empty() || !action.conditional_effects_end.is_empty()),
+            ),
+            (":derived-predicates", !self.axioms.is_empty()),
+            (":constraints", !self.always_constraints.is_empty()),
+            (":timed-initial-literals", !self.timed_literals.is_empty()),
+        ];
+
+        let mut used = self.requirements.clone();
+        used.extend(inferred.into_iter().filter(|&(_, needed)| needed).map(|(requirement, _)| requirement.to_string()));
+        used.sort();
+        used.dedup();
+        used
+    }
+
+    /// Freeze the grounded task into its index-based form for the search
+    pub fn compile(&self) -> CompiledTask {
+        CompiledTask::new(self)
+    }
+
+    /// Pack the facts of each mutex group into one finite-domain variable, for compact states
+    pub fn finite_domain_encoding(&self) -> FiniteDomainEncoding {
+        FiniteDomainEncoding::new(self)
+    }
+
+    /// Number of goal conditions, boolean and numeric, that hold in `state`
+    pub fn satisfied_goal_count(&self, state: &State) -> usize {
+        let facts = self.goal_conditions.iter()
+            .filter(|condition| resolve_condition(self, condition).holds(&state.facts))
+            .count();
+        let numeric = self.numeric_goal_conditions.iter()
+            .filter(|condition| condition.is_satisfied(&state.numeric_values))
+            .count();
+        facts + numeric
+    }
+
+    /// Recompute every derived atom in `state` from the basic facts, applying the axioms until
+    /// nothing changes. Negated conditions are read from the current values, so negating a
+    /// derived predicate is only meaningful when that predicate does not depend on this one.
+    pub fn apply_axioms(&self, state: &mut State) {
+        let derived: Vec<usize> = self.axioms.iter().map(|axiom| self.fact_ids[&axiom.key()]).collect();
+        for &id in &derived {
+            state.facts.set(id, false);
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (axiom, &id) in self.axioms.iter().zip(&derived) {
+                if state.facts[id] {
+                    continue;
+                }
+                let holds = axiom.conditions.iter().all(|condition| resolve_condition(self, condition).holds(&state.facts))
+                    && axiom.numeric_conditions.iter().all(|condition| condition.is_satisfied(&state.numeric_values));
+                if holds {
+                    state.facts.set(id, true);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    /// Drop ground actions that can never start: those with a condition on an atom that is
+    /// unreachable from the initial state even with delete effects ignored. Negative and numeric
+    /// conditions are not checked, so every plan for the task stays a plan. Idempotent; returns
+    /// the number of actions removed. Plans index `ground_actions`, so preprocess before solving.
+    pub fn preprocess(&mut self) -> usize {
+        self.preprocess_with(PreprocessOptions::default())
+    }
+
+    /// `preprocess`, then with `options.relevance` also drop the actions `relevant_actions`
+    /// rules out. Removing those from any plan leaves a plan, so solvability is unchanged.
+    pub fn preprocess_with(&mut self, options: PreprocessOptions) -> usize {
+        let reachable = self.relaxed_reachable_facts();
+        let before = self.ground_actions.len();
+        let actions = std::mem::take(&mut self.ground_actions);
+        self.ground_actions = actions.into_iter()
+            .filter(|action| self.relaxed_preconditions(action).is_some_and(|facts| facts.iter().all(|&fact| reachable[fact])))
+            .collect();
+        if options.relevance {
+            let relevant = self.relevant_actions();
+            let actions = std::mem::take(&mut self.ground_actions);
+            self.ground_actions = actions.into_iter().zip(relevant).filter_map(|(action, keep)| keep.then_some(action)).collect();
+        }
+        before - self.ground_actions.len()
+    }
+
+    /// A positive goal condition whose atom cannot be reached from the initial state even with
+    /// delete effects ignored, which proves the task unsolvable
+    pub fn unreachable_goal(&self) -> Option<&Condition> {
+        let reachable = self.relaxed_reachable_facts();
+        self.goal_conditions.iter().find(|condition| {
+            !condition.is_negative
+                && condition.equality().is_none()
+                && !self.fact_id(&condition.predicate, &condition.args).is_some_and(|fact| reachable[fact])
+        })
+    }
+
+    /// Which ground actions can matter, found backwards from the goal: an action is relevant
+    /// when one of its effects touches a relevant atom or fluent, and then everything it reads
+    /// is relevant too. Goal, constraint, and metric atoms and fluents start out relevant,
+    /// except `(total-cost)` when it is minimized, since leaving out an action never raises it.
+    pub fn relevant_actions(&self) -> Vec<bool> {
+        let mut facts: HashSet<String> = self.goal_conditions.iter().chain(&self.always_constraints).map(Condition::key).collect();
+        let mut fluents: HashSet<String> = HashSet::new();
+        for condition in &self.numeric_goal_conditions {
+            condition.collect_fluents(&mut fluents);
+        }
+        if let Some(metric) = self.metric.as_ref().filter(|metric| !(metric.is_total_cost() && metric.direction == MetricDirection::Minimize)) {
+            metric.expression.collect_fluents(&mut fluents);
+        }
+
+        let mut relevant = vec![false; self.ground_actions.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (action, relevant) in self.ground_actions.iter().zip(relevant.iter_mut()).filter(|(_, relevant)| !**relevant) {
+                let conditional = || action.conditional_effects_start.iter().chain(&action.conditional_effects_end);
+                let touches_fact = action.effects_start.iter()
+                    .chain(&action.effects_end)
+                    .chain(conditional().flat_map(|conditional| &conditional.effects))
+                    .any(|effect| facts.contains(&effect.key()));
+                let touches_fluent = action.numeric_effects_start.iter()
+                    .chain(&action.numeric_effects_end)
+                    .chain(conditional().flat_map(|conditional| &conditional.numeric_effects))
+                    .any(|effect| fluents.contains(&effect.function.key()));
+                if !touches_fact && !touches_fluent {
+                    continue;
+                }
+
+                *relevant = true;
+                changed = true;
+                let conditions = action.conditions_start.iter()
+                    .chain(&action.conditions_over_all)
+                    .chain(&action.conditions_end)
+                    .chain(conditional().flat_map(|conditional| &conditional.conditions));
+                facts.extend(conditions.map(Condition::key));
+                let numeric_conditions = action.numeric_conditions_start.iter()
+                    .chain(&action.numeric_conditions_over_all)
+                    .chain(&action.numeric_conditions_end)
+                    .chain(conditional().flat_map(|conditional| &conditional.numeric_conditions));
+                for condition in numeric_conditions {
+                    condition.collect_fluents(&mut fluents);
+                }
+                let numeric_effects = action.numeric_effects_start.iter()
+                    .chain(&action.numeric_effects_end)
+                    .chain(conditional().flat_map(|conditional| &conditional.numeric_effects));
+                for effect in numeric_effects {
+                    effect.value.collect_fluents(&mut fluents);
+                }
+                action.duration_spec.collect_fluents(&mut fluents);
+            }
+            for axiom in &self.axioms {
+                if !facts.contains(&axiom.key()) {
+                    continue;
+                }
+                let known = facts.len() + fluents.len();
+                facts.extend(axiom.conditions.iter().map(Condition::key));
+                for condition in &axiom.numeric_conditions {
+                    condition.collect_fluents(&mut fluents);
+                }
+                changed |= facts.len() + fluents.len() > known;
+            }
+        }
+        relevant
+    }
+
+    /// Whether `type_name` is `ancestor` or declared (transitively) beneath it
+    pub fn is_subtype(&self, type_name: &str, ancestor: &str) -> bool {
+        Self::is_subtype_of(&self.type_parents, type_name, ancestor)
+    }
+
+    fn is_subtype_of(type_parents: &HashMap<String, String>, type_name: &str, ancestor: &str) -> bool {
+        if ancestor == "object" {
+            return true;
+        }
+        let mut current = type_name;
+        // Bounded walk so a cyclic :types declaration cannot hang the parser
+        for _ in 0..=type_parents.len() {
+            if current == ancestor {
+                return true;
+            }
+            match type_parents.get(current) {
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Assign an index to every atom mentioned by the initial state, ground actions, goals, or constraints
+    fn index_facts(&mut self, init_atoms: &[String]) {
+        let mut keys: Vec<String> = init_atoms.to_vec();
+        for action in &self.ground_actions {
+            let conditions = action.conditions_start.iter()
+                .chain(&action.conditions_over_all)
+                .chain(&action.conditions_end);
+            keys.extend(conditions.map(Condition::key));
+            keys.extend(action.effects_start.iter().chain(&action.effects_end).map(Effect::key));
+            for conditional in action.conditional_effects_start.iter().chain(&action.conditional_effects_end) {
+                keys.extend(conditional.conditions.iter().map(Condition::key));
+                keys.extend(conditional.effects.iter().map(Effect::key));
+            }
+        }
+        for axiom in &self.axioms {
+            keys.push(axiom.key());
+            keys.extend(axiom.conditions.iter().map(Condition::key));
+        }
+        keys.extend(self.goal_conditions.iter().chain(&self.always_constraints).map(Condition::key));
+        keys.extend(self.timed_literals.iter().map(|literal| literal.effect.key()));
+
+        // Equality tests, keyed "= a b", are decided without a fact
+        for key in keys.into_iter().filter(|key| !key.starts_with("= ")) {
+            if !self.fact_ids.contains_key(&key) {
+                self.fact_ids.insert(key.clone(), self.fact_names.len());
+                self.fact_names.push(key);
+            }
+        }
+    }
+
+    /// Drop conditions on static atoms, which no action, axiom, or timed literal can change,
+    /// when they hold initially, and drop actions that require one that does not. Conditional
+    /// effect guards are left as written.
+    fn simplify_ground_actions(&mut self) {
+        let mut added: HashSet<String> = self.axioms.iter().map(Axiom::key).collect();
+        let mut deleted = added.clone();
+        let action_effects = self.ground_actions.iter().flat_map(|action| {
+            let conditional = action.conditional_effects_start.iter()
+                .chain(&action.conditional_effects_end)
+                .flat_map(|conditional| &conditional.effects);
+            action.effects_start.iter().chain(&action.effects_end).chain(conditional)
+        });
+        for effect in action_effects.chain(self.timed_literals.iter().map(|literal| &literal.effect)) {
+            if effect.is_delete {
+                deleted.insert(effect.key());
+            } else {
+                added.insert(effect.key());
+            }
+        }
+
+        // `Some(value)` for a condition whose truth is fixed for the whole plan
+        let static_value = |condition: &Condition| -> Option<bool> {
+            if let Some(equal) = condition.equality() {
+                return Some(equal != condition.is_negative);
+            }
+            let key = condition.key();
+            let holds = self.fact_ids.get(&key).is_some_and(|&id| self.initial_state.facts[id]);
+            let fixed = if holds { !deleted.contains(&key) } else { !added.contains(&key) };
+            fixed.then_some(holds != condition.is_negative)
+        };
+        // `None` when some condition can never hold
+        let simplify = |conditions: &[Condition]| -> Option<Vec<Condition>> {
+            let mut kept = Vec::new();
+            for condition in conditions {
+                match static_value(condition) {
+                    Some(true) => {}
+                    Some(false) => return None,
+                    None => kept.push(condition.clone()),
+                }
+            }
+            Some(kept)
+        };
+        let simplified = self.ground_actions.iter()
+            .filter_map(|action| {
+                Some(TemporalAction {
+                    conditions_start: simplify(&action.conditions_start)?,
+                    conditions_over_all: simplify(&action.conditions_over_all)?,
+                    conditions_end: simplify(&action.conditions_end)?,
+                    ..action.clone()
+                })
+            })
+            .collect();
+        self.ground_actions = simplified;
+    }
+
+    /// Facts that hold initially or can be added by some sequence of actions, axioms, and timed
+    /// literals when delete effects are ignored, indexed like `State::facts`
+    pub(crate) fn relaxed_reachable_facts(&self) -> Vec<bool> {
+        let mut reached: Vec<bool> = self.initial_state.facts.iter().collect();
+        let timed_adds = self.timed_literals.iter().filter(|literal| !literal.effect.is_delete);
+        for fact in timed_adds.filter_map(|literal| self.fact_id(&literal.effect.predicate, &literal.effect.args)) {
+            reached[fact] = true;
+        }
+
+        let holds = |reached: &[bool], conditions: &[Condition]| {
+            self.positive_facts(conditions).is_some_and(|facts| facts.iter().all(|&fact| reached[fact]))
+        };
+        let mut changed = true;
+        while changed {
+            let mut added: Vec<usize> = Vec::new();
+            for action in &self.ground_actions {
+                if !self.relaxed_preconditions(action).is_some_and(|facts| facts.iter().all(|&fact| reached[fact])) {
+                    continue;
+                }
+                let conditional = action.conditional_effects_start.iter()
+                    .chain(&action.conditional_effects_end)
+                    .filter(|conditional| holds(&reached, &conditional.conditions))
+                    .flat_map(|conditional| &conditional.effects);
+                let effects = action.effects_start.iter().chain(&action.effects_end).chain(conditional);
+                added.extend(effects.filter(|effect| !effect.is_delete).filter_map(|effect| self.fact_id(&effect.predicate, &effect.args)));
+            }
+            for axiom in self.axioms.iter().filter(|axiom| holds(&reached, &axiom.conditions)) {
+                added.extend(self.fact_id(&axiom.predicate, &axiom.args));
+            }
+
+            changed = false;
+            for fact in added {
+                changed |= !reached[fact];
+                reached[fact] = true;
+            }
+        }
+        reached
+    }
+
+    /// Facts of the positive conditions an action needs to start, counting over-all and end
+    /// conditions its own start effects may provide, directly or through an axiom, as met;
+    /// `None` if one is outside the fact table
+    fn relaxed_preconditions(&self, action: &TemporalAction) -> Option<Vec<usize>> {
+        let mut facts = self.positive_facts(&action.conditions_start)?;
+        let provided = |condition: &Condition| {
+            let key = condition.key();
+            action.effects_start.iter().any(|effect| !effect.is_delete && effect.key() == key)
+                || self.axioms.iter().any(|axiom| axiom.key() == key)
+        };
+        let later: Vec<Condition> = action.conditions_over_all.iter()
+            .chain(&action.conditions_end)
+            .filter(|condition| !provided(condition))
+            .cloned()
+            .collect();
+        facts.extend(self.positive_facts(&later)?);
+        Some(facts)
+    }
+
+    /// Fact indices of the positive, non-equality `conditions`; `None` if one is outside the
+    /// fact table and so never holds
+    fn positive_facts(&self, conditions: &[Condition]) -> Option<Vec<usize>> {
+        conditions.iter()
+            .filter(|condition| !condition.is_negative && condition.equality().is_none())
+            .map(|condition| self.fact_id(&condition.predicate, &condition.args))
+            .collect()
+    }
+
+    /// Find groups of atoms of which at most one ever holds, in the manner of Fast Downward's
+    /// invariant synthesis. Candidates start as one predicate with all arguments but one fixed,
+    /// like `(at robot1 ?l)`; when a schema adds a member without deleting one it requires, the
+    /// candidate grows by a part for an atom that schema does delete, so `(free-hands ?r)` and
+    /// `(holding ?r ?p)` end up in one group. Each grounding of a candidate is kept when at most
+    /// one of its atoms holds initially and every action adding one of them also deletes, no
+    /// later, a member it requires at its start; groups inside a larger kept group are dropped.
+    /// Derived atoms and conditionally added atoms are never grouped.
+    fn infer_mutex_groups(&self) -> Vec<MutexGroup> {
+        let derived: HashSet<&str> = self.axioms.iter().map(|axiom| axiom.predicate.as_str()).collect();
+        let mut facts_of: BTreeMap<&str, Vec<(usize, Vec<&str>)>> = BTreeMap::new();
+        for (id, name) in self.fact_names.iter().enumerate() {
+            let mut parts = name.split(' ');
+            let predicate = parts.next().unwrap_or_default();
+            if !derived.contains(predicate) {
+                facts_of.entry(predicate).or_default().push((id, parts.collect()));
+            }
+        }
+
+        let mut queue: VecDeque<Vec<InvariantPart>> = VecDeque::new();
+        for (predicate, facts) in &facts_of {
+            let arity = facts[0].1.len();
+            let counted = std::iter::once(None).chain((0..arity).map(Some));
+            for counted in counted {
+                let order = (0..arity).filter(|&position| Some(position) != counted).collect();
+                queue.push_back(vec![InvariantPart { predicate: predicate.to_string(), order, counted }]);
+            }
+        }
+        let mut seen: HashSet<Vec<InvariantPart>> = queue.iter().cloned().collect();
+        let mut candidates = Vec::new();
+        while let Some(candidate) = queue.pop_front() {
+            for refined in self.refine_invariant(&candidate) {
+                if seen.len() < MAX_INVARIANT_CANDIDATES && seen.insert(refined.clone()) {
+                    queue.push_back(refined);
+                }
+            }
+            candidates.push(candidate);
+        }
+
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut checked: HashSet<Vec<usize>> = HashSet::new();
+        for candidate in &candidates {
+            let mut instances: BTreeMap<Vec<&str>, Vec<usize>> = BTreeMap::new();
+            for part in candidate {
+                let facts = facts_of.get(part.predicate.as_str()).into_iter().flatten();
+                for (id, args) in facts.filter(|(_, args)| args.len() == part.arity()) {
+                    instances.entry(part.parameters(args)).or_default().push(*id);
+                }
+            }
+            for mut facts in instances.into_values() {
+                facts.sort_unstable();
+                if facts.len() > 1 && checked.insert(facts.clone()) && self.is_mutex_group(&facts) {
+                    groups.push(facts);
+                }
+            }
+        }
+
+        let contained = |group: &Vec<usize>| groups.iter()
+            .any(|other| other.len() > group.len() && group.iter().all(|fact| other.contains(fact)));
+        groups.iter()
+            .filter(|group| !contained(group))
+            .map(|facts| MutexGroup { facts: facts.clone() })
+            .collect()
+    }
+
+    /// Candidates growing `candidate` by one part, for each schema adding a member without
+    /// deleting, no later, a member with the same parameters that it requires at its start
+    fn refine_invariant(&self, candidate: &[InvariantPart]) -> Vec<Vec<InvariantPart>> {
+        let mut refined = Vec::new();
+        for action in &self.actions {
+            let required = |effect: &&Effect| effect.is_delete && action.conditions_start.iter().any(|condition| {
+                !condition.is_negative && condition.predicate == effect.predicate && condition.args == effect.args
+            });
+            let deletes_start: Vec<&Effect> = action.effects_start.iter().filter(required).collect();
+            let mut deletes_end = deletes_start.clone();
+            deletes_end.extend(action.effects_end.iter().filter(required));
+
+            for (effects, deletes) in [(&action.effects_start, &deletes_start), (&action.effects_end, &deletes_end)] {
+                for add in effects.iter().filter(|effect| !effect.is_delete) {
+                    let Some(part) = candidate.iter().find(|part| part.matches(&add.predicate, &add.args)) else {
+                        continue;
+                    };
+                    let terms = part.parameters(&add.args);
+                    let balanced = deletes.iter().any(|delete| candidate.iter().any(|other| {
+                        other.matches(&delete.predicate, &delete.args) && other.parameters(&delete.args) == terms
+                    }));
+                    if balanced {
+                        continue;
+                    }
+                    for delete in deletes.iter().filter(|delete| candidate.iter().all(|other| other.predicate != delete.predicate)) {
+                        if let Some(part) = InvariantPart::covering(&delete.predicate, &delete.args, &terms) {
+                            let mut grown = candidate.to_vec();
+                            grown.push(part);
+                            grown.sort();
+                            refined.push(grown);
+                        }
+                    }
+                }
+            }
+        }
+        refined
+    }
+
+    fn is_mutex_group(&self, facts: &[usize]) -> bool {
+        let members: HashSet<&str> = facts.iter().map(|&id| self.fact_names[id].as_str()).collect();
+        if facts.iter().filter(|&&id| self.initial_state.facts[id]).count() > 1 {
+            return false;
+        }
+        // A timed literal adds its atom regardless of what else holds
+        if self.timed_literals.iter().any(|literal| !literal.effect.is_delete && members.contains(literal.effect.key().as_str())) {
+            return false;
+        }
+
+        self.ground_actions.iter().all(|action| {
+            let conditionally_adds = action.conditional_effects_start.iter()
+                .chain(&action.conditional_effects_end)
+                .flat_map(|conditional| &conditional.effects)
+                .any(|effect| !effect.is_delete && members.contains(effect.key().as_str()));
+            if conditionally_adds {
+                return false;
+            }
+
+            let adds_in = |effects: &[Effect]| -> HashSet<String> {
+                effects.iter()
+                    .filter(|effect| !effect.is_delete && members.contains(effect.key().as_str()))
+                    .map(Effect::key)
+                    .collect()
+            };
+            let deletes_in = |effects: &[Effect]| -> HashSet<String> {
+                effects.iter().filter(|effect| effect.is_delete).map(Effect::key).collect()
+            };
+            let (adds_start, adds_end) = (adds_in(&action.effects_start), adds_in(&action.effects_end));
+            if adds_start.len() + adds_end.len() == 0 {
+                return true;
+            }
+            if adds_start.union(&adds_end).count() > 1 {
+                return false;
+            }
+
+            // The delete must come with or before the add, so the group never holds two atoms
+            let mut deletes = deletes_in(&action.effects_start);
+            if adds_start.is_empty() {
+                deletes.extend(deletes_in(&action.effects_end));
+            }
+            action.conditions_start.iter().any(|condition| {
+                let key = condition.key();
+                !condition.is_negative && members.contains(key.as_str()) && deletes.contains(&key)
+            })
+        })
+    }
+
+    /// Each of `mutex_groups` as the names of its atoms, e.g. `["at robot1 depot", "at robot1 office"]`
+    pub fn mutex_groups_named(&self) -> Vec<Vec<String>> {
+        self.mutex_groups.iter()
+            .map(|group| group.facts.iter().map(|&id| self.fact_names[id].clone()).collect())
+            .collect()
+    }
+
+    fn ground_actions(
+        schemas: &[TemporalAction],
+        pddl_actions: &[PDDLAction],
+        objects: &[TypedObject],
+        type_parents: &HashMap<String, String>,
+    ) -> Vec<TemporalAction> {
+        schemas.iter()
+            .zip(pddl_actions)
+            .flat_map(|(schema, pddl_action)| {
+                Self::parameter_bindings(&pddl_action.parameters, objects, type_parents)
+                    .into_iter()
+                    .map(|(binding, values)| schema.instantiate(&binding, &values))
+            })
+            .collect()
+    }
+
+    /// Every assignment of type-compatible objects to `parameters`, as a binding map and the
+    /// bound values in parameter order
+    fn parameter_bindings(
+        parameters: &[PDDLParameter],
+        objects: &[TypedObject],
+        type_parents: &HashMap<String, String>,
+    ) -> Vec<(HashMap<String, String>, Vec<String>)> {
+        let candidates: Vec<Vec<&str>> = parameters.iter().map(|param| {
+            let param_type = param.type_name.as_deref().unwrap_or("object");
+            objects.iter()
+                .filter(|object| Self::is_subtype_of(type_parents, &object.type_name, param_type))
+                .map(|object| object.name.as_str())
+                .collect()
+        }).collect();
+
+        if candidates.iter().any(|c| c.is_empty()) {
+            return Vec::new();
+        }
+
+        // Enumerate the cartesian product of candidates like an odometer
+        let mut bindings = Vec::new();
+        let mut choice = vec![0; candidates.len()];
+        loop {
+            let values: Vec<String> = choice.iter()
+                .zip(&candidates)
+                .map(|(&i, options)| options[i].to_string())
+                .collect();
+            let binding: HashMap<String, String> = parameters.iter()
+                .map(|param| param.name.clone())
+                .zip(values.iter().cloned())
+                .collect();
+            bindings.push((binding, values));
+
+            // Advance the rightmost parameter that still has candidates left
+            let Some(position) = (0..choice.len()).rev().find(|&p| choice[p] + 1 < candidates[p].len()) else {
+                break;
+            };
+            choice[position] += 1;
+            choice[position + 1..].iter_mut().for_each(|later| *later = 0);
+        }
+
+        bindings
+    }
+
+    /// Parse a single file containing both the `(define (domain ...))` and
+    /// `(define (problem ...))` blocks, in either order.
+    pub fn from_pddl_combined(content: &str) -> Self {
+        let forms = sexpr::read_lenient(content);
+        Self::from_forms(&forms, &forms)
+    }
+
+    /// Smallest non-zero action duration, a natural scale for choosing epsilon. Actions whose
+    /// duration depends on fluents, and so has no value before grounding, are skipped, as are
+    /// durations that are not finite.
+    pub fn min_action_duration(&self) -> Option<f64> {
+        self.actions
+            .iter()
+            .filter(|action| action.duration_spec.evaluate(&HashMap::new()).is_some())
+            .map(|action| action.duration)
+            .filter(|&duration| duration > 0.0 && duration.is_finite())
+            .min_by(f64::total_cmp)
+    }
+
+    /// Groups of two or more objects that are interchangeable: they share a type, and swapping
+    /// any two of them maps the initial state, timed initial literals, goal, and constraints onto
+    /// themselves. Objects
+    /// named in action schemas (domain constants) are never grouped.
+    pub fn object_symmetries(&self) -> Vec<Vec<String>> {
+        let mentioned_in_schemas: HashSet<&str> = self.actions.iter().flat_map(TemporalAction::arguments).collect();
+
+        // Atoms and fluents are compared as (name, args), so only arguments are ever swapped
+        let split = |key: &str| -> (String, Vec<String>) {
+            let mut tokens = key.split(' ').map(str::to_string);
+            (tokens.next().unwrap_or_default(), tokens.collect())
+        };
+        let condition = |c: &Condition| (c.predicate.clone(), c.args.clone(), c.is_negative);
+        let init: HashSet<(String, Vec<String>)> = self.fact_names.iter()
+            .zip(self.initial_state.facts.iter())
+            .filter(|&(_, holds)| holds)
+            .map(|(name, _)| split(name))
+            .collect();
+        let numeric: HashSet<((String, Vec<String>), u64)> = self.initial_state.numeric_values.iter()
+            .map(|(key, value)| (split(key), value.to_bits()))
+            .collect();
+        let goal: HashSet<(String, Vec<String>, bool)> = self.goal_conditions.iter().map(condition).collect();
+        let constraints: HashSet<(String, Vec<String>, bool)> = self.always_constraints.iter().map(condition).collect();
+        let timed: HashSet<(u64, String, Vec<String>, bool)> = self.timed_literals.iter()
+            .map(|literal| (literal.time.to_bits(), literal.effect.predicate.clone(), literal.effect.args.clone(), literal.effect.is_delete))
+            .collect();
+
+        let is_symmetric = |a: &str, b: &str| {
+            let swap = |args: &[String]| -> Vec<String> {
+                args.iter()
+                    .map(|arg| if arg == a { b } else if arg == b { a } else { arg.as_str() }.to_string())
+                    .collect()
+            };
+            let swap_condition = |(predicate, args, negative): &(String, Vec<String>, bool)| {
+                (predicate.clone(), swap(args), *negative)
+            };
+            init.iter().all(|(predicate, args)| init.contains(&(predicate.clone(), swap(args))))
+                && numeric.iter().all(|((name, args), value)| numeric.contains(&((name.clone(), swap(args)), *value)))
+                && goal.iter().all(|c| goal.contains(&swap_condition(c)))
+                && constraints.iter().all(|c| constraints.contains(&swap_condition(c)))
+                && timed.iter().all(|(time, predicate, args, delete)| {
+                    timed.contains(&(*time, predicate.clone(), swap(args), *delete))
+                })
+        };
+
+        // Swaps compose, so comparing against one representative per group is enough
+        let mut groups: Vec<Vec<&TypedObject>> = Vec::new();
+        for object in self.objects.iter().filter(|object| !mentioned_in_schemas.contains(object.name.as_str())) {
+            let group = groups.iter_mut().find(|group| {
+                group[0].type_name == object.type_name && is_symmetric(&group[0].name, &object.name)
+            });
+            match group {
+                Some(group) => group.push(object),
+                None => groups.push(vec![object]),
+            }
+        }
+
+        groups.into_iter()
+            .filter(|group| group.len() > 1)
+            .map(|group| group.into_iter().map(|object| object.name.clone()).collect())
+            .collect()
+    }
+
+    fn parse_pddl_domain(sections: &[SExpr]) -> PDDLDomain {
+        let type_list = Self::find_section(sections, ":types").unwrap_or_default();
+
+        PDDLDomain {
+            requirements: Self::extract_requirements(sections),
+            type_parents: Self::parse_typed_list(type_list)
+                .into_iter()
+                .filter_map(|(name, parent)| parent.filter(|p| *p != name).map(|p| (name, p)))
+                .collect(),
+            constants: Self::extract_typed_objects(sections, ":constants"),
+            predicates: Self::extract_declarations(sections, ":predicates"),
+            functions: Self::extract_declarations(sections, ":functions"),
+            actions: Self::extract_actions(sections),
+            axioms: Self::extract_axioms(sections),
+            always_constraints: Self::extract_always_constraints(sections),
+        }
+    }
+
+    /// The first `(define (<kind> name) ...)` form, where `kind` is `domain` or `problem`
+    fn find_define<'a>(forms: &'a [SExpr], kind: &str) -> Option<&'a SExpr> {
+        forms.iter().find(|form| {
+            form.head().is_some_and(|head| head.eq_ignore_ascii_case("define"))
+                && form.items().get(1).and_then(SExpr::head) == Some(kind)
+        })
+    }
+
+    /// Contents of the `(<name> ...)` section, without the name itself; if the section is
+    /// repeated, the last one wins
+    fn find_section<'a>(sections: &'a [SExpr], name: &str) -> Option<&'a [SExpr]> {
+        sections.iter()
+            .rfind(|section| section.head() == Some(name))
+            .map(|section| &section.items()[1..])
+    }
+
+    /// Sections of the single `(define ...)` form in `content`, each checked against `known`
+    fn read_define(content: &str, file: &'static str, known: &[&str]) -> Result<Vec<SExpr>, PddlParseError> {
+        let forms = sexpr::read_all(content).map_err(|error| match error {
+            ReadError::UnmatchedClose(span) => PddlParseError::UnmatchedCloseParen { file, line: span.line, column: span.column },
+            ReadError::Unclosed(span) => PddlParseError::UnclosedParen { file, line: span.line, column: span.column },
+        })?;
+
+        let mut forms = forms.into_iter();
+        let define = match forms.next() {
+            Some(define) if define.head().is_some_and(|head| head.eq_ignore_ascii_case("define")) => define,
+            Some(other) => return Err(Self::unexpected(&other, file, "(define ...)")),
+            None => return Ok(Vec::new()),
+        };
+        if let Some(extra) = forms.next() {
+            return Err(Self::unexpected(&extra, file, "end of file after (define ...)"));
+        }
+
+        // (define (domain name) sections...)
+        let mut sections = Vec::new();
+        for item in define.items().iter().skip(2) {
+            let Some(section) = item.head().filter(|head| head.starts_with(':')) else {
+                return Err(Self::unexpected(item, file, "a (:section ...)"));
+            };
+            if !known.contains(&section) {
+                let span = item.items()[0].span();
+                return Err(PddlParseError::UnknownSection {
+                    file,
+                    section: section.to_string(),
+                    line: span.line,
+                    column: span.column,
+                });
+            }
+            sections.push(item.clone());
+        }
+        Ok(sections)
+    }
+
+    /// Every keyword and its value, such as `:effect` and the effect formula, in the `:action`
+    /// and `:durative-action` sections of a domain read by `read_define`
+    fn action_fields(domain: &[SExpr]) -> impl Iterator<Item = ActionField<'_>> {
+        domain.iter()
+            .filter(|section| matches!(section.head(), Some(":action" | ":durative-action")))
+            .flat_map(|section| {
+                let action = section.items().get(1).map(|name| name.to_string()).unwrap_or_default();
+                let durative = section.head() == Some(":durative-action");
+                section.items().windows(2).filter_map(move |pair| {
+                    Some(ActionField {
+                        action: action.clone(),
+                        durative,
+                        keyword: pair[0].as_atom().filter(|keyword| keyword.starts_with(':'))?,
+                        body: &pair[1],
+                    })
+                })
+            })
+    }
+
+    fn unexpected(expr: &SExpr, file: &'static str, expected: &'static str) -> PddlParseError {
+        let span = expr.span();
+        PddlParseError::UnexpectedToken {
+            file,
+            token: expr.to_string(),
+            expected,
+            line: span.line,
+            column: span.column,
+        }
+    }
+
+    /// Walk the formulas in `exprs` and report the first atom whose predicate is not among the
+    /// `declared` names and arities
+    fn check_predicates_declared(
+        exprs: &[SExpr],
+        declared: &[(String, usize)],
+        file: &'static str,
+        context: &str,
+    ) -> Result<(), PddlParseError> {
+        for expr in exprs {
+            let items = expr.items();
+            let Some(head) = expr.head() else {
+                continue;
+            };
+            let nested = match head {
+                "and" | "or" | "not" | "imply" | "when" | "always" | "sometime" | "at-most-once" => &items[1..],
+                // Quantifiers bind variables first
+                "forall" | "exists" => items.get(2..).unwrap_or_default(),
+                // (at start ...), (at end ...), and timed literals (at 10 ...)
+                "at" if items.len() == 3
+                    && items[1].as_atom().is_some_and(|t| t == "start" || t == "end" || t.parse::<f64>().is_ok()) => &items[2..],
+                "over" if items.get(1).and_then(SExpr::as_atom) == Some("all") => &items[2..],
+                // Numeric comparisons and effects mention functions, not predicates
+                "=" | "<" | "<=" | ">" | ">=" | "increase" | "decrease" | "assign" | "scale-up" | "scale-down" => continue,
+                predicate => {
+                    let span = items[0].span();
+                    let found = items.len() - 1;
+                    return match declared.iter().find(|(name, _)| name == predicate) {
+                        Some(&(_, expected)) if expected == found => continue,
+                        Some(&(_, expected)) => Err(PddlParseError::ArityMismatch {
+                            file,
+                            predicate: predicate.to_string(),
+                            context: context.to_string(),
+                            expected,
+                            found,
+                            line: span.line,
+                            column: span.column,
+                        }),
+                        None => Err(PddlParseError::UndeclaredPredicate {
+                            file,
+                            predicate: predicate.to_string(),
+                            context: context.to_string(),
+                            line: span.line,
+                            column: span.column,
+                        }),
+                    };
+                }
+            };
+            Self::check_predicates_declared(nested, declared, file, context)?;
+        }
+        Ok(())
+    }
+
+    /// Report the first effect in a durative action's `effect` that no `at start` or `at end`
+    /// wraps, looking through `and`, `forall`, and the consequent of `when`
+    fn check_effects_timed(effect: &SExpr, action: &str) -> Result<(), PddlParseError> {
+        let items = effect.items();
+        match effect.head() {
+            Some("and") => items[1..].iter().try_for_each(|nested| Self::check_effects_timed(nested, action)),
+            Some("forall") => items.get(2..).unwrap_or_default().iter().try_for_each(|nested| Self::check_effects_timed(nested, action)),
+            Some("when") => items.get(2).map_or(Ok(()), |consequent| Self::check_effects_timed(consequent, action)),
+            Some("at") if items.get(1).and_then(SExpr::as_atom).is_some_and(|t| t == "start" || t == "end") => Ok(()),
+            _ => {
+                let span = effect.span();
+                Err(PddlParseError::UntimedEffect {
+                    action: action.to_string(),
+                    effect: effect.to_string(),
+                    line: span.line,
+                    column: span.column,
+                })
+            }
+        }
+    }
+
+    /// Report a strict `(< ?duration ...)` or `(> ?duration ...)` in a duration constraint, whose
+    /// excluded endpoint `DurationSpec::Bounded` would let the search pick
+    fn check_duration_inclusive(constraint: &SExpr, context: &str) -> Result<(), PddlParseError> {
+        let Some(op) = Self::strict_duration_bound(constraint) else {
+            return Ok(());
+        };
+        let span = op.span();
+        Err(PddlParseError::UnsupportedConstruct {
+            file: "domain",
+            construct: "strict duration inequality",
+            context: context.to_string(),
+            line: span.line,
+            column: span.column,
+        })
+    }
+
+    /// The operator of the first strict `<` or `>` bound on `?duration` in a duration constraint
+    fn strict_duration_bound(constraint: &SExpr) -> Option<&SExpr> {
+        let constraints = match constraint.head() {
+            Some("and") => &constraint.items()[1..],
+            _ => std::slice::from_ref(constraint),
+        };
+        constraints.iter().find_map(|constraint| match constraint.items() {
+            [op, variable, _] if matches!(op.as_atom(), Some("<" | ">")) && variable.as_atom() == Some("?duration") => Some(op),
+            _ => None,
+        })
+    }
+
+    /// Walk the conditions in `exprs` and report the first construct the parser would misread:
+    /// an implication, a universal quantifier, a negation of anything but an atom, equality or
+    /// comparison, or, unless `disjunctions` and `existentials` allow them, a disjunction or an
+    /// existential quantifier
+    fn check_conditions_supported(
+        exprs: &[SExpr],
+        disjunctions: bool,
+        existentials: bool,
+        file: &'static str,
+        context: &str,
+    ) -> Result<(), PddlParseError> {
+        for expr in exprs {
+            let items = expr.items();
+            let Some(head) = expr.head() else {
+                continue;
+            };
+            let unsupported = match head {
+                "imply" => Some("implication"),
+                "forall" => Some("universal condition"),
+                "exists" if !existentials => Some("existential condition"),
+                "or" if !disjunctions => Some("disjunction"),
+                "not" if items.get(1).and_then(SExpr::head)
+                    .is_some_and(|inner| matches!(inner, "and" | "or" | "not" | "imply" | "exists" | "forall")) =>
+                {
+                    Some("negated compound condition")
+                }
+                _ => None,
+            };
+            if let Some(construct) = unsupported {
+                let span = items[0].span();
+                return Err(PddlParseError::UnsupportedConstruct {
+                    file,
+                    construct,
+                    context: context.to_string(),
+                    line: span.line,
+                    column: span.column,
                 });
             }
+            let nested = match head {
+                "and" | "or" => &items[1..],
+                "exists" => items.get(2..).unwrap_or_default(),
+                "at" if items.len() == 3 && items[1].as_atom().is_some_and(|t| t == "start" || t == "end") => &items[2..],
+                "over" if items.get(1).and_then(SExpr::as_atom) == Some("all") => &items[2..],
+                _ => continue,
+            };
+            Self::check_conditions_supported(nested, disjunctions, existentials, file, context)?;
         }
-        
-        actions
+        Ok(())
     }
-    
-    fn extract_balanced_expression(content: &str) -> String {
-        let mut result = String::new();
-        let mut depth = 0;
-        let mut started = false;
-        
-        for ch in content.chars() {
-            if ch == '(' {
-                depth += 1;
-                started = true;
-            } else if ch == ')' {
-                depth -= 1;
-            }
-            
-            if started {
-                result.push(ch);
-                if depth == 0 {
-                    break;
+
+    /// Walk the conditions in `exprs` and report the first negation or disjunction that the
+    /// declared `requirements` do not allow; `:adl` allows both. Negated equality only needs
+    /// `:equality`, as most planners accept it.
+    fn check_condition_requirements(
+        exprs: &[SExpr],
+        requirements: &[&str],
+        file: &'static str,
+        context: &str,
+    ) -> Result<(), PddlParseError> {
+        for expr in exprs {
+            let items = expr.items();
+            let Some(head) = expr.head() else {
+                continue;
+            };
+            let needed = match head {
+                "not" if items.get(1).and_then(SExpr::head) != Some("=") => Some((":negative-preconditions", "negated condition")),
+                "or" => Some((":disjunctive-preconditions", "disjunction")),
+                "imply" => Some((":disjunctive-preconditions", "implication")),
+                _ => None,
+            };
+            if let Some((requirement, construct)) = needed {
+                if !requirements.iter().any(|&declared| declared == requirement || declared == ":adl") {
+                    let span = items[0].span();
+                    return Err(PddlParseError::UndeclaredRequirement {
+                        file,
+                        requirement,
+                        construct,
+                        context: context.to_string(),
+                        line: span.line,
+                        column: span.column,
+                    });
                 }
             }
+            let nested = match head {
+                "and" | "or" | "not" | "imply" => &items[1..],
+                "forall" | "exists" => items.get(2..).unwrap_or_default(),
+                "at" if items.len() == 3 && items[1].as_atom().is_some_and(|t| t == "start" || t == "end") => &items[2..],
+                "over" if items.get(1).and_then(SExpr::as_atom) == Some("all") => &items[2..],
+                _ => continue,
+            };
+            Self::check_condition_requirements(nested, requirements, file, context)?;
         }
-        
-        result
+        Ok(())
     }
-    
-    fn parse_parameters(params_str: &str) -> Vec<PDDLParameter> {
-        let mut parameters = Vec::new();
-        let tokens: Vec<&str> = params_str.split_whitespace().collect();
+
+    fn extract_typed_objects(sections: &[SExpr], section: &str) -> Vec<TypedObject> {
+        Self::parse_typed_list(Self::find_section(sections, section).unwrap_or_default())
+            .into_iter()
+            .map(|(name, type_name)| TypedObject {
+                name,
+                type_name: type_name.unwrap_or_else(|| "object".to_string()),
+            })
+            .collect()
+    }
+
+    /// Parse `a b - t c` style lists; a type applies to every name since the previous type
+    fn parse_typed_list(list: &[SExpr]) -> Vec<(String, Option<String>)> {
+        let mut entries: Vec<(String, Option<String>)> = Vec::new();
+        let mut untyped_from = 0;
         let mut i = 0;
-        
-        while i < tokens.len() {
-            let token = tokens[i];
-            if token.starts_with('?') {
-                let mut param = PDDLParameter {
-                    name: token.to_string(),
-                    type_name: None,
-                };
-                
-                // Check if there's a type specification
-                if i + 2 < tokens.len() && tokens[i + 1] == "-" {
-                    param.type_name = Some(tokens[i + 2].to_string());
-                    i += 3;
-                } else {
-                    i += 1;
+
+        while i < list.len() {
+            if list[i].as_atom() == Some("-") && i + 1 < list.len() {
+                // `(either a b)` types are kept as written
+                for entry in &mut entries[untyped_from..] {
+                    entry.1 = Some(list[i + 1].to_string());
                 }
-                
-                parameters.push(param);
+                untyped_from = entries.len();
+                i += 2;
             } else {
+                entries.push((list[i].to_string(), None));
                 i += 1;
             }
         }
-        
-        parameters
+
+        entries
     }
-    
-    fn extract_action_parameters(action_content: &str) -> Vec<PDDLParameter> {
-        let regex = Regex::new(r":parameters\s+\(([^)]*)\)").unwrap();
-        if let Some(caps) = regex.captures(action_content) {
-            if let Some(params_str) = caps.get(1) {
-                return Self::parse_parameters(params_str.as_str());
-            }
-        }
-        Vec::new()
+
+    /// Flags of the `:requirements` section, lowercased
+    fn extract_requirements(sections: &[SExpr]) -> Vec<String> {
+        Self::find_section(sections, ":requirements")
+            .unwrap_or_default()
+            .iter()
+            .filter_map(SExpr::as_atom)
+            .map(str::to_ascii_lowercase)
+            .collect()
     }
-    
-    fn extract_action_precondition(action_content: &str) -> Option<PDDLFormula> {
-        if let Some(start) = action_content.find(":precondition") {
-            let after_precond = &action_content[start + ":precondition".len()..];
-            let formula_str = Self::extract_balanced_expression(after_precond.trim_start());
-            return Self::parse_formula(&formula_str);
+
+    fn extract_always_constraints(sections: &[SExpr]) -> Vec<Condition> {
+        let mut constraints = Vec::new();
+        let formula = Self::find_section(sections, ":constraints")
+            .and_then(<[SExpr]>::first)
+            .and_then(Self::parse_formula);
+        if let Some(formula) = formula {
+            Self::collect_always_recursive(&formula, &mut constraints);
         }
-        None
+        constraints
     }
-    
-    fn extract_action_condition(action_content: &str) -> Option<PDDLFormula> {
-        if let Some(start) = action_content.find(":condition") {
-            let after_cond = &action_content[start + ":condition".len()..];
-            let formula_str = Self::extract_balanced_expression(after_cond.trim_start());
-            return Self::parse_formula(&formula_str);
+
+    fn collect_always_recursive(formula: &PDDLFormula, constraints: &mut Vec<Condition>) {
+        match formula {
+            PDDLFormula::And(formulas) => {
+                for f in formulas {
+                    Self::collect_always_recursive(f, constraints);
+                }
+            },
+            PDDLFormula::Always(inner) => {
+                let mut bucket = ConditionBucket::default();
+                Self::collect_conditions_recursive(inner, &mut bucket);
+                constraints.extend(bucket.conditions);
+            },
+            _ => {
+                // Other trajectory operators (sometime, at-most-once, ...) are not supported yet
+            }
         }
-        None
+    }
+
+    /// `(name ?arg - type ...)` declarations from `:predicates` or `:functions`; the
+    /// `- number` result types of functions are skipped
+    fn extract_declarations(sections: &[SExpr], section: &str) -> Vec<PDDLPredicate> {
+        Self::find_section(sections, section)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|declaration| {
+                Some(PDDLPredicate {
+                    name: declaration.head()?.to_string(),
+                    parameters: Self::parse_parameters(&declaration.items()[1..]),
+                })
+            })
+            .collect()
     }
     
-    fn extract_action_duration(action_content: &str) -> PDDLDuration {
-        if let Some(start) = action_content.find(":duration") {
-            let after_duration = &action_content[start + ":duration".len()..];
-            let duration_str = Self::extract_balanced_expression(after_duration.trim_start());
-            
-            // Parse duration expression
-            let cleaned = duration_str.trim().trim_start_matches('(').trim_end_matches(')');
-            
-            if cleaned.starts_with("= ?duration") {
-                // Extract the duration value
-                let tokens: Vec<&str> = cleaned.split_whitespace().collect();
-                if tokens.len() >= 3 {
-                    if let Ok(duration_val) = tokens[2].parse::<f64>() {
-                        return PDDLDuration::Fixed(duration_val);
+    fn extract_actions(sections: &[SExpr]) -> Vec<PDDLAction> {
+        sections.iter()
+            .filter_map(|section| {
+                let is_durative = match section.head()? {
+                    ":action" => false,
+                    ":durative-action" => true,
+                    _ => return None,
+                };
+                let items = section.items();
+                let mut action = PDDLAction {
+                    name: items.get(1)?.as_atom()?.to_string(),
+                    parameters: Vec::new(),
+                    precondition: None,
+                    effect: None,
+                    // Default to 1.0 if the duration is missing or cannot be parsed
+                    duration: is_durative.then_some(DurationSpec::Exact(NumericExpr::Number(1.0))),
+                    strict_duration: false,
+                    is_durative,
+                };
+
+                // The body is a list of `:keyword value` pairs
+                for pair in items[2..].chunks(2) {
+                    let [keyword, value] = pair else {
+                        continue;
+                    };
+                    match (keyword.as_atom(), is_durative) {
+                        (Some(":parameters"), _) => action.parameters = Self::parse_parameters(value.items()),
+                        // Some tools write `:precondition` on durative actions too
+                        (Some(":precondition"), _) | (Some(":condition"), true) => {
+                            action.precondition = Self::parse_formula(value);
+                        }
+                        (Some(":effect"), _) => action.effect = Self::parse_formula(value),
+                        (Some(":duration"), true) => {
+                            action.duration = Some(Self::parse_duration(value));
+                            action.strict_duration = Self::strict_duration_bound(value).is_some();
+                        }
+                        _ => {}
                     }
                 }
-            }
-            
-            // Default to 1.0 if parsing fails
-            PDDLDuration::Fixed(1.0)
-        } else {
-            PDDLDuration::Fixed(1.0)
-        }
+                Some(action)
+            })
+            .flat_map(Self::split_disjunctive_action)
+            .collect()
+    }
+
+    /// One copy of `action` per disjunct of its condition, all sharing its name
+    fn split_disjunctive_action(action: PDDLAction) -> Vec<PDDLAction> {
+        let Some(formula) = &action.precondition else {
+            return vec![action];
+        };
+        Self::condition_variants(formula).into_iter()
+            .map(|variant| PDDLAction { precondition: Some(variant), ..action.clone() })
+            .collect()
     }
     
-    fn extract_action_effect(action_content: &str) -> Option<PDDLFormula> {
-        if let Some(start) = action_content.find(":effect") {
-            let after_effect = &action_content[start + ":effect".len()..];
-            let formula_str = Self::extract_balanced_expression(after_effect.trim_start());
-            return Self::parse_formula(&formula_str);
+    fn extract_axioms(sections: &[SExpr]) -> Vec<PDDLAxiom> {
+        sections.iter()
+            .filter(|section| section.head() == Some(":derived"))
+            .filter_map(|section| {
+                let [_, head, body] = section.items() else {
+                    return None;
+                };
+                let parameters = Self::parse_parameters(&head.items()[1..]);
+                let predicate = head.head()?.to_string();
+                let args: Vec<String> = parameters.iter().map(|param| param.name.clone()).collect();
+
+                // One rule per disjunct, grounded over the head's parameters and its own
+                // existential variables
+                let rules = match Self::parse_formula(body) {
+                    Some(formula) => Self::axiom_rules(&formula),
+                    None => vec![(Vec::new(), ConditionBucket::default())],
+                };
+                Some(rules.into_iter().map(move |(variables, conditions)| PDDLAxiom {
+                    axiom: Axiom {
+                        predicate: predicate.clone(),
+                        args: args.clone(),
+                        conditions: conditions.conditions,
+                        numeric_conditions: conditions.numeric_conditions,
+                    },
+                    parameters: parameters.iter().cloned().chain(variables).collect(),
+                }))
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// `formula` in disjunctive normal form: a conjunction of conditions per disjunct, with the
+    /// existential variables it introduces. An empty result never holds.
+    fn axiom_rules(formula: &PDDLFormula) -> Vec<(Vec<PDDLParameter>, ConditionBucket)> {
+        match formula {
+            PDDLFormula::Or(formulas) => formulas.iter().flat_map(Self::axiom_rules).collect(),
+            PDDLFormula::And(formulas) => {
+                formulas.iter().fold(vec![(Vec::new(), ConditionBucket::default())], |rules, formula| {
+                    let disjuncts = Self::axiom_rules(formula);
+                    rules.iter()
+                        .flat_map(|(variables, conditions)| disjuncts.iter().map(move |(more, extra)| {
+                            let mut conditions = conditions.clone();
+                            conditions.conditions.extend(extra.conditions.iter().cloned());
+                            conditions.numeric_conditions.extend(extra.numeric_conditions.iter().cloned());
+                            (variables.iter().chain(more).cloned().collect(), conditions)
+                        }))
+                        .collect()
+                })
+            },
+            PDDLFormula::Exists(variables, inner) => Self::axiom_rules(inner)
+                .into_iter()
+                .map(|(more, conditions)| (variables.iter().cloned().chain(more).collect(), conditions))
+                .collect(),
+            _ => {
+                let mut conditions = ConditionBucket::default();
+                Self::collect_conditions_recursive(formula, &mut conditions);
+                vec![(Vec::new(), conditions)]
+            },
         }
-        None
+    }
+
+    fn parse_parameters(list: &[SExpr]) -> Vec<PDDLParameter> {
+        Self::parse_typed_list(list)
+            .into_iter()
+            .filter(|(name, _)| name.starts_with('?'))
+            .map(|(name, type_name)| PDDLParameter { name, type_name })
+            .collect()
     }
     
-    fn parse_formula(formula_str: &str) -> Option<PDDLFormula> {
-        let trimmed = formula_str.trim();
-        
-        if !trimmed.starts_with('(') || !trimmed.ends_with(')') {
-            return None;
+    fn parse_duration(constraint: &SExpr) -> DurationSpec {
+        // Parse duration constraint: (= ?duration <expr>), or bounds (>= ?duration <expr>)
+        // and (<= ?duration <expr>), possibly joined by `and`
+        let constraints = match constraint.head() {
+            Some("and") => &constraint.items()[1..],
+            _ => std::slice::from_ref(constraint),
+        };
+
+        let mut lower = None;
+        let mut upper = None;
+        for constraint in constraints {
+            let [op, variable, value] = constraint.items() else {
+                continue;
+            };
+            if variable.as_atom() != Some("?duration") {
+                continue;
+            }
+            let Some(expr) = Self::parse_numeric_expression(value) else {
+                continue;
+            };
+            match op.as_atom() {
+                Some("=") => return DurationSpec::Exact(expr),
+                Some(">=" | ">") => lower = Some(expr),
+                Some("<=" | "<") => upper = Some(expr),
+                _ => {}
+            }
         }
-        
-        let inner = &trimmed[1..trimmed.len()-1];
-        let tokens = Self::tokenize_formula(inner);
-        
-        if tokens.is_empty() {
-            return None;
+        if lower.is_some() || upper.is_some() {
+            return DurationSpec::Bounded { lower, upper };
         }
-        
-        match tokens[0].as_str() {
-            "and" => {
-                let mut formulas = Vec::new();
-                let remaining_tokens = &tokens[1..];
-                let sub_formulas = Self::extract_sub_formulas(remaining_tokens);
-                
-                for sub_formula in sub_formulas {
-                    if let Some(parsed) = Self::parse_formula(&sub_formula) {
-                        formulas.push(parsed);
-                    }
-                }
-                Some(PDDLFormula::And(formulas))
-            },
-            "or" => {
-                let mut formulas = Vec::new();
-                let remaining_tokens = &tokens[1..];
-                let sub_formulas = Self::extract_sub_formulas(remaining_tokens);
-                
-                for sub_formula in sub_formulas {
-                    if let Some(parsed) = Self::parse_formula(&sub_formula) {
-                        formulas.push(parsed);
-                    }
-                }
-                Some(PDDLFormula::Or(formulas))
-            },
-            "not" => {
-                let remaining_tokens = &tokens[1..];
-                if !remaining_tokens.is_empty() {
-                    let sub_formula = remaining_tokens.join(" ");
-                    if let Some(parsed) = Self::parse_formula(&format!("({})", sub_formula)) {
-                        Some(PDDLFormula::Not(Box::new(parsed)))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            },
-            "at" => {
-                // Handle temporal operators: (at start ...), (at end ...)
-                if tokens.len() >= 2 {
-                    match tokens[1].as_str() {
-                        "start" => {
-                            let remaining_tokens = &tokens[2..];
-                            let sub_formula = remaining_tokens.join(" ");
-                            if let Some(parsed) = Self::parse_formula(&format!("({})", sub_formula)) {
-                                Some(PDDLFormula::AtStart(Box::new(parsed)))
-                            } else {
-                                None
-                            }
-                        },
-                        "end" => {
-                            let remaining_tokens = &tokens[2..];
-                            let sub_formula = remaining_tokens.join(" ");
-                            if let Some(parsed) = Self::parse_formula(&format!("({})", sub_formula)) {
-                                Some(PDDLFormula::AtEnd(Box::new(parsed)))
-                            } else {
-                                None
-                            }
-                        },
-                        _ => None
-                    }
-                } else {
-                    None
-                }
-            },
-            "over" => {
-                // Handle temporal operator: (over all ...)
-                if tokens.len() >= 2 && tokens[1] == "all" {
-                    let remaining_tokens = &tokens[2..];
-                    let sub_formula = remaining_tokens.join(" ");
-                    if let Some(parsed) = Self::parse_formula(&format!("({})", sub_formula)) {
-                        Some(PDDLFormula::OverAll(Box::new(parsed)))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
+
+        DurationSpec::Exact(NumericExpr::Number(1.0))
+    }
+    
+    fn parse_formula(expr: &SExpr) -> Option<PDDLFormula> {
+        let items = match expr {
+            // A bare name such as `ready` in `(and ready (at ?r ?l))` is a nullary atom
+            SExpr::Atom { text, .. } => {
+                return Some(PDDLFormula::Predicate { name: text.clone(), args: Vec::new(), negated: false });
+            }
+            SExpr::List { items, .. } => items,
+        };
+
+        if let Some(condition) = Self::parse_numeric_comparison(items) {
+            return Some(PDDLFormula::NumericComparison(condition));
+        }
+
+        let parse_all = |formulas: &[SExpr]| -> Vec<PDDLFormula> {
+            formulas.iter().filter_map(Self::parse_formula).collect()
+        };
+        let parse_boxed = |formula: &SExpr| Self::parse_formula(formula).map(Box::new);
+
+        match (expr.head()?, items.len()) {
+            ("and", _) => Some(PDDLFormula::And(parse_all(&items[1..]))),
+            ("or", _) => Some(PDDLFormula::Or(parse_all(&items[1..]))),
+            ("not", 2) => Some(PDDLFormula::Not(parse_boxed(&items[1])?)),
+            // Temporal operators: (at start ...), (at end ...); anything else is an `at` predicate
+            ("at", 3) if items[1].as_atom() == Some("start") => Some(PDDLFormula::AtStart(parse_boxed(&items[2])?)),
+            ("at", 3) if items[1].as_atom() == Some("end") => Some(PDDLFormula::AtEnd(parse_boxed(&items[2])?)),
+            ("over", 3) if items[1].as_atom() == Some("all") => Some(PDDLFormula::OverAll(parse_boxed(&items[2])?)),
+            // Trajectory constraint: (always <condition>)
+            ("always", 2) => Some(PDDLFormula::Always(parse_boxed(&items[1])?)),
+            // Conditional effect: (when <antecedent> <consequent>)
+            ("when", 3) => Some(PDDLFormula::When(parse_boxed(&items[1])?, parse_boxed(&items[2])?)),
+            ("exists", 3) => Some(PDDLFormula::Exists(Self::parse_parameters(items[1].items()), parse_boxed(&items[2])?)),
+            // Numeric effect: (<op> (function args) expression)
+            (op @ ("increase" | "decrease" | "assign" | "scale-up" | "scale-down"), 3) => {
+                let op = match op {
+                    "increase" => NumericOp::Increase,
+                    "decrease" => NumericOp::Decrease,
+                    "assign" => NumericOp::Assign,
+                    "scale-up" => NumericOp::ScaleUp,
+                    _ => NumericOp::ScaleDown,
+                };
+                let function = Self::parse_function_term(&items[1])?;
+                let value = Self::parse_numeric_expression(&items[2])?;
+                Some(PDDLFormula::NumericEffect(NumericEffect { function, op, value }))
             },
-            _ => {
+            (name, _) => {
                 // Simple predicate
-                let name = tokens[0].clone();
-                let args = tokens[1..].to_vec();
                 Some(PDDLFormula::Predicate {
-                    name,
-                    args,
+                    name: name.to_string(),
+                    args: Self::atom_args(&items[1..])?,
                     negated: false,
                 })
             }
         }
     }
-    
-    fn tokenize_formula(formula: &str) -> Vec<String> {
-        let mut tokens = Vec::new();
-        let mut current_token = String::new();
-        let mut depth = 0;
-        let mut in_token = false;
-        
-        for ch in formula.chars() {
-            match ch {
-                '(' => {
-                    if depth > 0 || in_token {
-                        current_token.push(ch);
-                    }
-                    depth += 1;
-                },
-                ')' => {
-                    depth -= 1;
-                    if depth > 0 || in_token {
-                        current_token.push(ch);
-                    }
-                    if depth == 0 && in_token {
-                        tokens.push(format!("({})", current_token));
-                        current_token.clear();
-                        in_token = false;
-                    }
-                },
-                ' ' | '\t' | '\n' | '\r' => {
-                    if depth > 0 {
-                        current_token.push(ch);
-                    } else if !current_token.is_empty() {
-                        tokens.push(current_token.clone());
-                        current_token.clear();
-                        in_token = false;
-                    }
-                },
-                _ => {
-                    current_token.push(ch);
-                    if depth == 0 {
-                        in_token = true;
-                    }
-                }
-            }
-        }
-        
-        if !current_token.is_empty() {
-            if depth > 0 {
-                tokens.push(format!("({})", current_token));
-            } else {
-                tokens.push(current_token);
-            }
+
+    /// Names of `args`, or `None` if any of them is a list
+    fn atom_args(args: &[SExpr]) -> Option<Vec<String>> {
+        args.iter().map(|arg| arg.as_atom().map(str::to_string)).collect()
+    }
+
+    fn parse_function_term(term: &SExpr) -> Option<FunctionTerm> {
+        // `(fuel ?v)`, or a bare name for a nullary function
+        let (name, args) = match term {
+            SExpr::Atom { text, .. } => (text.as_str(), &[][..]),
+            SExpr::List { items, .. } => (items.first()?.as_atom()?, &items[1..]),
+        };
+        if name.parse::<f64>().is_ok() || name.starts_with('?') {
+            return None;
         }
-        
-        tokens
+        Some(FunctionTerm {
+            name: name.to_string(),
+            args: Self::atom_args(args)?,
+        })
     }
-    
-    fn extract_sub_formulas(tokens: &[String]) -> Vec<String> {
-        let mut sub_formulas = Vec::new();
-        let mut current_formula = String::new();
-        let mut depth = 0;
-        
-        for token in tokens {
-            if token.starts_with('(') {
-                if depth > 0 {
-                    current_formula.push(' ');
-                    current_formula.push_str(token);
-                } else {
-                    current_formula = token.clone();
-                }
-                depth += token.chars().filter(|&c| c == '(').count();
-                depth -= token.chars().filter(|&c| c == ')').count();
-                
-                if depth == 0 {
-                    sub_formulas.push(current_formula.clone());
-                    current_formula.clear();
-                }
-            } else if depth > 0 {
-                current_formula.push(' ');
-                current_formula.push_str(token);
-            } else {
-                // Single token predicate
-                sub_formulas.push(format!("({})", token));
-            }
+
+    fn parse_numeric_expression(expr: &SExpr) -> Option<NumericExpr> {
+        if let Some(value) = expr.as_atom().and_then(|text| text.parse::<f64>().ok()) {
+            return Some(NumericExpr::Number(value));
         }
-        
-        if !current_formula.is_empty() {
-            sub_formulas.push(current_formula);
+
+        let items = expr.items();
+        match (expr.head(), items.len()) {
+            (Some(op @ ("+" | "-" | "*" | "/")), 3) => {
+                let lhs = Box::new(Self::parse_numeric_expression(&items[1])?);
+                let rhs = Box::new(Self::parse_numeric_expression(&items[2])?);
+                Some(match op {
+                    "+" => NumericExpr::Add(lhs, rhs),
+                    "-" => NumericExpr::Subtract(lhs, rhs),
+                    "*" => NumericExpr::Multiply(lhs, rhs),
+                    _ => NumericExpr::Divide(lhs, rhs),
+                })
+            },
+            (Some("-"), 2) => {
+                // Unary minus
+                let operand = Self::parse_numeric_expression(&items[1])?;
+                Some(NumericExpr::Subtract(Box::new(NumericExpr::Number(0.0)), Box::new(operand)))
+            },
+            _ => Self::parse_function_term(expr).map(NumericExpr::Function),
         }
-        
-        sub_formulas
+    }
+
+    fn parse_numeric_comparison(items: &[SExpr]) -> Option<NumericCondition> {
+        let [op, left, right] = items else {
+            return None;
+        };
+        let comparison = match op.as_atom()? {
+            "<" => Comparison::Less,
+            "<=" => Comparison::LessEqual,
+            "=" => Comparison::Equal,
+            ">=" => Comparison::GreaterEqual,
+            ">" => Comparison::Greater,
+            _ => return None,
+        };
+
+        // `(= a b)` over bare names is object equality rather than a fluent comparison
+        let is_numeric = |operand: &SExpr| operand.as_atom().is_none_or(|text| text.parse::<f64>().is_ok());
+        if comparison == Comparison::Equal && !is_numeric(left) && !is_numeric(right) {
+            return None;
+        }
+
+        Some(NumericCondition {
+            comparison,
+            left: Self::parse_numeric_expression(left)?,
+            right: Self::parse_numeric_expression(right)?,
+        })
     }
     
     fn convert_pddl_actions(pddl_actions: &[PDDLAction], _predicates: &[PDDLPredicate]) -> Vec<TemporalAction> {
         pddl_actions.iter().map(|action| {
-            let duration = match &action.duration {
-                Some(PDDLDuration::Fixed(d)) => *d,
-                _ => 1.0, // Default duration
-            };
+            let duration_spec = action.duration.clone()
+                .unwrap_or(DurationSpec::Exact(NumericExpr::Number(1.0)));
+            // Fluent-dependent durations are only known once the action is applied
+            let duration = duration_spec.evaluate(&HashMap::new()).unwrap_or(1.0);
             
             if action.is_durative {
                 // For durative actions, separate conditions and effects by time
@@ -651,32 +2737,81 @@ impl TemporalTask {
                 
                 TemporalAction {
                     name: action.name.clone(),
+                    is_durative: action.is_durative,
                     duration,
-                    conditions_start,
-                    conditions_over_all,
-                    conditions_end,
-                    effects_start,
-                    effects_end,
+                    duration_spec,
+                    conditions_start: conditions_start.conditions,
+                    conditions_over_all: conditions_over_all.conditions,
+                    conditions_end: conditions_end.conditions,
+                    effects_start: effects_start.effects,
+                    effects_end: effects_end.effects,
+                    numeric_effects_start: effects_start.numeric_effects,
+                    numeric_effects_end: effects_end.numeric_effects,
+                    numeric_conditions_start: conditions_start.numeric_conditions,
+                    numeric_conditions_over_all: conditions_over_all.numeric_conditions,
+                    numeric_conditions_end: conditions_end.numeric_conditions,
+                    conditional_effects_start: effects_start.conditional_effects,
+                    conditional_effects_end: effects_end.conditional_effects,
                 }
             } else {
                 // Regular actions - all conditions at start, all effects at end
+                let conditions_start = Self::extract_conditions_from_formula(&action.precondition);
+                let effects_end = Self::extract_effects_from_formula(&action.effect);
                 TemporalAction {
                     name: action.name.clone(),
+                    is_durative: action.is_durative,
                     duration,
-                    conditions_start: Self::extract_conditions_from_formula(&action.precondition),
+                    duration_spec,
+                    conditions_start: conditions_start.conditions,
                     conditions_over_all: Vec::new(),
                     conditions_end: Vec::new(),
                     effects_start: Vec::new(),
-                    effects_end: Self::extract_effects_from_formula(&action.effect),
+                    effects_end: effects_end.effects,
+                    numeric_effects_start: Vec::new(),
+                    numeric_effects_end: effects_end.numeric_effects,
+                    numeric_conditions_start: conditions_start.numeric_conditions,
+                    numeric_conditions_over_all: Vec::new(),
+                    numeric_conditions_end: Vec::new(),
+                    conditional_effects_start: Vec::new(),
+                    conditional_effects_end: effects_end.conditional_effects,
                 }
             }
         }).collect()
     }
     
-    fn extract_temporal_conditions(formula: &Option<PDDLFormula>) -> (Vec<Condition>, Vec<Condition>, Vec<Condition>) {
-        let mut conditions_start = Vec::new();
-        let mut conditions_over_all = Vec::new();
-        let mut conditions_end = Vec::new();
+    /// `formula` split at its disjunctions into conjunctions, each condition kept under its
+    /// `at start`, `over all` or `at end`. An `over all` disjunction becomes one variant per
+    /// disjunct, so it must hold through the same disjunct the whole time. An empty result
+    /// never holds.
+    fn condition_variants(formula: &PDDLFormula) -> Vec<PDDLFormula> {
+        let timed = |inner: &PDDLFormula, wrap: fn(Box<PDDLFormula>) -> PDDLFormula| {
+            Self::condition_variants(inner).into_iter().map(|variant| wrap(Box::new(variant))).collect()
+        };
+        match formula {
+            PDDLFormula::Or(formulas) => formulas.iter().flat_map(Self::condition_variants).collect(),
+            PDDLFormula::And(formulas) => {
+                formulas.iter().fold(vec![Vec::new()], |variants: Vec<Vec<PDDLFormula>>, formula| {
+                    let disjuncts = Self::condition_variants(formula);
+                    variants.iter()
+                        .flat_map(|conjuncts| disjuncts.iter().map(move |disjunct| {
+                            let mut conjuncts = conjuncts.clone();
+                            conjuncts.push(disjunct.clone());
+                            conjuncts
+                        }))
+                        .collect()
+                }).into_iter().map(PDDLFormula::And).collect()
+            },
+            PDDLFormula::AtStart(inner) => timed(inner, PDDLFormula::AtStart),
+            PDDLFormula::OverAll(inner) => timed(inner, PDDLFormula::OverAll),
+            PDDLFormula::AtEnd(inner) => timed(inner, PDDLFormula::AtEnd),
+            _ => vec![formula.clone()],
+        }
+    }
+
+    fn extract_temporal_conditions(formula: &Option<PDDLFormula>) -> (ConditionBucket, ConditionBucket, ConditionBucket) {
+        let mut conditions_start = ConditionBucket::default();
+        let mut conditions_over_all = ConditionBucket::default();
+        let mut conditions_end = ConditionBucket::default();
         
         if let Some(formula) = formula {
             Self::collect_temporal_conditions_recursive(formula, &mut conditions_start, &mut conditions_over_all, &mut conditions_end);
@@ -685,9 +2820,9 @@ impl TemporalTask {
         (conditions_start, conditions_over_all, conditions_end)
     }
     
-    fn extract_temporal_effects(formula: &Option<PDDLFormula>) -> (Vec<Effect>, Vec<Effect>) {
-        let mut effects_start = Vec::new();
-        let mut effects_end = Vec::new();
+    fn extract_temporal_effects(formula: &Option<PDDLFormula>) -> (EffectBucket, EffectBucket) {
+        let mut effects_start = EffectBucket::default();
+        let mut effects_end = EffectBucket::default();
         
         if let Some(formula) = formula {
             Self::collect_temporal_effects_recursive(formula, &mut effects_start, &mut effects_end);
@@ -698,9 +2833,9 @@ impl TemporalTask {
     
     fn collect_temporal_conditions_recursive(
         formula: &PDDLFormula, 
-        conditions_start: &mut Vec<Condition>,
-        conditions_over_all: &mut Vec<Condition>, 
-        conditions_end: &mut Vec<Condition>
+        conditions_start: &mut ConditionBucket,
+        conditions_over_all: &mut ConditionBucket, 
+        conditions_end: &mut ConditionBucket
     ) {
         match formula {
             PDDLFormula::AtStart(inner) => {
@@ -724,10 +2859,32 @@ impl TemporalTask {
         }
     }
     
+    /// Whether `formula` has an `or` outside any quantifier
+    fn has_disjunction(formula: &PDDLFormula) -> bool {
+        match formula {
+            PDDLFormula::Or(_) => true,
+            PDDLFormula::And(formulas) => formulas.iter().any(Self::has_disjunction),
+            PDDLFormula::Not(inner) | PDDLFormula::AtStart(inner) | PDDLFormula::AtEnd(inner) | PDDLFormula::OverAll(inner) => {
+                Self::has_disjunction(inner)
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether `collect_temporal_effects_recursive` has to guess the time point of some effect
+    fn has_untimed_effect(formula: &PDDLFormula) -> bool {
+        match formula {
+            PDDLFormula::AtStart(_) | PDDLFormula::AtEnd(_) => false,
+            PDDLFormula::And(formulas) => formulas.iter().any(Self::has_untimed_effect),
+            PDDLFormula::When(_, consequent) => Self::has_untimed_effect(consequent),
+            _ => true,
+        }
+    }
+
     fn collect_temporal_effects_recursive(
         formula: &PDDLFormula, 
-        effects_start: &mut Vec<Effect>,
-        effects_end: &mut Vec<Effect>
+        effects_start: &mut EffectBucket,
+        effects_end: &mut EffectBucket
     ) {
         match formula {
             PDDLFormula::AtStart(inner) => {
@@ -741,15 +2898,25 @@ impl TemporalTask {
                     Self::collect_temporal_effects_recursive(f, effects_start, effects_end);
                 }
             },
+            PDDLFormula::When(antecedent, consequent) => {
+                // (when <cond> (at start <effect>)) fires at start; anything else at end
+                let conditional = Self::conditional_effect(antecedent, consequent);
+                if matches!(consequent.as_ref(), PDDLFormula::AtStart(_)) {
+                    effects_start.conditional_effects.push(conditional);
+                } else {
+                    effects_end.conditional_effects.push(conditional);
+                }
+            },
             _ => {
-                // Default to end effects for non-temporal formulas
+                // Untimed effects are malformed; `try_from_pddl` rejects them and `from_pddl`
+                // warns, then applies them at the end like instantaneous actions do
                 Self::collect_effects_recursive(formula, effects_end);
             }
         }
     }
     
-    fn extract_conditions_from_formula(formula: &Option<PDDLFormula>) -> Vec<Condition> {
-        let mut conditions = Vec::new();
+    fn extract_conditions_from_formula(formula: &Option<PDDLFormula>) -> ConditionBucket {
+        let mut conditions = ConditionBucket::default();
         
         if let Some(formula) = formula {
             Self::collect_conditions_recursive(formula, &mut conditions);
@@ -758,10 +2925,10 @@ impl TemporalTask {
         conditions
     }
     
-    fn collect_conditions_recursive(formula: &PDDLFormula, conditions: &mut Vec<Condition>) {
+    fn collect_conditions_recursive(formula: &PDDLFormula, conditions: &mut ConditionBucket) {
         match formula {
             PDDLFormula::Predicate { name, args, negated } => {
-                conditions.push(Condition {
+                conditions.conditions.push(Condition {
                     predicate: name.clone(),
                     args: args.clone(),
                     is_negative: *negated,
@@ -773,19 +2940,29 @@ impl TemporalTask {
                 }
             },
             PDDLFormula::Or(formulas) => {
-                // For simplicity, treat OR as AND for now
+                // Action conditions are split at their disjunctions by `condition_variants` and
+                // axioms by `axiom_rules`. Elsewhere, as in goals, requiring every disjunct is
+                // the closest sound reading; `parse_pddl_problem` warns about it
                 for f in formulas {
                     Self::collect_conditions_recursive(f, conditions);
                 }
             },
-            PDDLFormula::Not(formula) => {
-                if let PDDLFormula::Predicate { name, args, .. } = formula.as_ref() {
-                    conditions.push(Condition {
+            PDDLFormula::Not(formula) => match formula.as_ref() {
+                PDDLFormula::Predicate { name, args, .. } => {
+                    conditions.conditions.push(Condition {
                         predicate: name.clone(),
                         args: args.clone(),
                         is_negative: true,
                     });
-                }
+                },
+                PDDLFormula::NumericComparison(condition) => {
+                    conditions.numeric_conditions.push(NumericCondition {
+                        comparison: condition.comparison.negated(),
+                        ..condition.clone()
+                    });
+                },
+                // `try_from_pddl` rejects negated compound conditions
+                _ => {},
             },
             PDDLFormula::AtStart(formula) => {
                 Self::collect_conditions_recursive(formula, conditions);
@@ -795,12 +2972,22 @@ impl TemporalTask {
             },
             PDDLFormula::OverAll(formula) => {
                 Self::collect_conditions_recursive(formula, conditions);
+            },
+            PDDLFormula::NumericEffect(_) | PDDLFormula::Always(_) | PDDLFormula::When(_, _) => {
+                // Effects and trajectory constraints never appear as conditions
+            },
+            PDDLFormula::Exists(_, _) => {
+                // Quantified conditions are only expanded for axioms, by `axiom_rules`;
+                // `try_from_pddl` rejects them elsewhere
+            },
+            PDDLFormula::NumericComparison(condition) => {
+                conditions.numeric_conditions.push(condition.clone());
             }
         }
     }
     
-    fn extract_effects_from_formula(formula: &Option<PDDLFormula>) -> Vec<Effect> {
-        let mut effects = Vec::new();
+    fn extract_effects_from_formula(formula: &Option<PDDLFormula>) -> EffectBucket {
+        let mut effects = EffectBucket::default();
         
         if let Some(formula) = formula {
             Self::collect_effects_recursive(formula, &mut effects);
@@ -809,10 +2996,10 @@ impl TemporalTask {
         effects
     }
     
-    fn collect_effects_recursive(formula: &PDDLFormula, effects: &mut Vec<Effect>) {
+    fn collect_effects_recursive(formula: &PDDLFormula, effects: &mut EffectBucket) {
         match formula {
             PDDLFormula::Predicate { name, args, negated } => {
-                effects.push(Effect {
+                effects.effects.push(Effect {
                     predicate: name.clone(),
                     args: args.clone(),
                     is_delete: *negated,
@@ -825,7 +3012,7 @@ impl TemporalTask {
             },
             PDDLFormula::Not(formula) => {
                 if let PDDLFormula::Predicate { name, args, .. } = formula.as_ref() {
-                    effects.push(Effect {
+                    effects.effects.push(Effect {
                         predicate: name.clone(),
                         args: args.clone(),
                         is_delete: true,
@@ -838,87 +3025,186 @@ impl TemporalTask {
             PDDLFormula::AtEnd(formula) => {
                 Self::collect_effects_recursive(formula, effects);
             },
+            PDDLFormula::NumericEffect(effect) => {
+                effects.numeric_effects.push(effect.clone());
+            },
+            PDDLFormula::When(antecedent, consequent) => {
+                effects.conditional_effects.push(Self::conditional_effect(antecedent, consequent));
+            },
             _ => {}
         }
     }
     
-    fn parse_pddl_problem(problem_content: &str, predicates: &[PDDLPredicate]) -> (State, Vec<Condition>) {
-        let cleaned_content = Self::clean_pddl_content(problem_content);
-        
-        // Parse initial state
-        let initial_state = Self::parse_initial_state(&cleaned_content, predicates);
-        
-        // Parse goal conditions
-        let goal_conditions = Self::parse_goal_conditions(&cleaned_content);
+    fn conditional_effect(antecedent: &PDDLFormula, consequent: &PDDLFormula) -> ConditionalEffect {
+        let mut conditions = ConditionBucket::default();
+        Self::collect_conditions_recursive(antecedent, &mut conditions);
+        let mut effects = EffectBucket::default();
+        Self::collect_effects_recursive(consequent, &mut effects);
+        ConditionalEffect {
+            conditions: conditions.conditions,
+            numeric_conditions: conditions.numeric_conditions,
+            effects: effects.effects,
+            numeric_effects: effects.numeric_effects,
+        }
+    }
+
+    fn parse_pddl_problem(forms: &[SExpr], predicates: &[PDDLPredicate]) -> PDDLProblem {
+        let sections = Self::find_define(forms, "problem")
+            .and_then(|define| define.items().get(2..))
+            .unwrap_or_default();
+        let (init_atoms, numeric_values, timed_literals) = Self::parse_initial_state(sections, predicates);
+
+        let mut warnings = Vec::new();
+        for name in [":init", ":goal", ":metric"] {
+            let repeats: Vec<&SExpr> = sections.iter().filter(|section| section.head() == Some(name)).collect();
+            if let Some(last) = repeats.last().filter(|_| repeats.len() > 1) {
+                let span = last.span();
+                warnings.push(format!(
+                    "problem has {} ({} ...) sections; using the last one, at line {}, column {}",
+                    repeats.len(), name, span.line, span.column
+                ));
+            }
+        }
+        let goal = Self::find_section(sections, ":goal")
+            .and_then(<[SExpr]>::first)
+            .and_then(Self::parse_formula);
+        if goal.as_ref().is_some_and(Self::has_disjunction) {
+            warnings.push("the goal has a disjunction; every disjunct is required".to_string());
+        }
         
-        (initial_state, goal_conditions)
+        PDDLProblem {
+            requirements: Self::extract_requirements(sections),
+            objects: Self::extract_typed_objects(sections, ":objects"),
+            init_atoms,
+            numeric_values,
+            timed_literals,
+            goals: Self::parse_goal_conditions(sections),
+            metric: Self::parse_metric(sections),
+            always_constraints: Self::extract_always_constraints(sections),
+            warnings,
+        }
     }
     
-    fn parse_initial_state(content: &str, predicates: &[PDDLPredicate]) -> State {
-        let mut state = State {
-            facts: vec![false; predicates.len()],
-            numeric_values: HashMap::new(),
-        };
+    fn parse_initial_state(sections: &[SExpr], predicates: &[PDDLPredicate]) -> (Vec<String>, HashMap<String, f64>, Vec<TimedLiteral>) {
+        let mut init_atoms: Vec<String> = Vec::new();
+        let mut numeric_values = HashMap::new();
+        let mut timed_literals = Vec::new();
         
-        // Find the init section
-        if let Some(start) = content.find("(:init") {
-            let after_init = &content[start..];
-            let init_section = Self::extract_balanced_expression(after_init);
-            
-            // Parse individual facts from the init section
-            let fact_regex = Regex::new(r"\(([a-zA-Z0-9_-]+)([^)]*)\)").unwrap();
-            
-            for cap in fact_regex.captures_iter(&init_section) {
-                if let Some(pred_name) = cap.get(1) {
-                    let name = pred_name.as_str();
-                    if name == "init" {
-                        continue;
-                    }
-                    
-                    let args_str = cap.get(2).map(|m| m.as_str()).unwrap_or("").trim();
-                    let args: Vec<String> = if args_str.is_empty() {
-                        Vec::new()
-                    } else {
-                        args_str.split_whitespace().map(|s| s.to_string()).collect()
-                    };
-                    
-                    // Find the predicate index
-                    if let Some(pred_index) = Self::find_predicate_index(predicates, name, &args) {
-                        if pred_index < state.facts.len() {
-                            state.facts[pred_index] = true;
-                        }
-                    }
-                    
-                    // Handle numeric values (= (function args) value)
-                    if name == "=" && args.len() >= 2 {
-                        if let Ok(value) = args[1].parse::<f64>() {
-                            state.numeric_values.insert(args[0].clone(), value);
-                        }
+        for literal in Self::find_section(sections, ":init").unwrap_or_default() {
+            let items = literal.items();
+            let Some(name) = literal.head() else {
+                continue;
+            };
+
+            // Numeric values: (= (function args) value)
+            if name == "=" {
+                if let [_, function, value] = items {
+                    let function = Self::parse_function_term(function);
+                    let value = value.as_atom().and_then(|text| text.parse::<f64>().ok());
+                    if let (Some(function), Some(value)) = (function, value) {
+                        numeric_values.insert(function.key(), value);
                     }
                 }
+                continue;
+            }
+
+            // Timed literals: (at 10 (door-open)) or (at 10 (not (door-open)))
+            if let [_, time, literal] = items {
+                let time = time.as_atom().and_then(|text| text.parse::<f64>().ok());
+                if let (Some(time), Some(formula)) = (time, Self::parse_formula(literal)) {
+                    let mut effects = EffectBucket::default();
+                    Self::collect_effects_recursive(&formula, &mut effects);
+                    timed_literals.extend(effects.effects.into_iter()
+                        .filter(|effect| Self::find_predicate_index(predicates, &effect.predicate, &effect.args).is_some())
+                        .map(|effect| TimedLiteral { time, effect }));
+                    continue;
+                }
+            }
+
+            // Only declared predicates become facts
+            let Some(args) = Self::atom_args(&items[1..]) else {
+                continue;
+            };
+            if Self::find_predicate_index(predicates, name, &args).is_some() {
+                let atom = atom_key(name, &args);
+                if !init_atoms.contains(&atom) {
+                    init_atoms.push(atom);
+                }
             }
         }
         
-        state
+        (init_atoms, numeric_values, timed_literals)
     }
     
-    fn parse_goal_conditions(content: &str) -> Vec<Condition> {
-        let mut goal_conditions = Vec::new();
-        
-        // Find the goal section
-        if let Some(start) = content.find("(:goal") {
-            let after_goal = &content[start + 6..];
-            let goal_section = Self::extract_balanced_expression(after_goal.trim_start());
-            
-            // Parse the goal formula
-            if let Some(formula) = Self::parse_formula(&goal_section) {
-                Self::collect_conditions_recursive(&formula, &mut goal_conditions);
-            }
+    fn parse_goal_conditions(sections: &[SExpr]) -> ConditionBucket {
+        let mut goal_conditions = ConditionBucket::default();
+        let formula = Self::find_section(sections, ":goal")
+            .and_then(<[SExpr]>::first)
+            .and_then(Self::parse_formula);
+        if let Some(formula) = formula {
+            Self::collect_conditions_recursive(&formula, &mut goal_conditions);
         }
-        
         goal_conditions
     }
     
+    /// `(:metric minimize (total-time))`; `None` without a metric or with one that does not parse
+    fn parse_metric(sections: &[SExpr]) -> Option<Metric> {
+        let [direction, expression] = Self::find_section(sections, ":metric")? else {
+            return None;
+        };
+        let direction = match direction.as_atom()? {
+            "minimize" => MetricDirection::Minimize,
+            "maximize" => MetricDirection::Maximize,
+            _ => return None,
+        };
+        Some(Metric {
+            direction,
+            expression: Self::parse_numeric_expression(expression)?,
+        })
+    }
+    
+    /// Report the first argument of the `:init` entry `literal` whose object is not of the type
+    /// its predicate declares there. Objects the task does not know are left to other checks.
+    fn check_fact_types(&self, literal: &SExpr) -> Result<(), PddlParseError> {
+        let items = literal.items();
+        match literal.head() {
+            // Fluent values
+            Some("=") | None => Ok(()),
+            Some("at") if items.len() == 3 && items[1].as_atom().is_some_and(|time| time.parse::<f64>().is_ok()) => {
+                self.check_fact_types(&items[2])
+            }
+            Some("not") => items.get(1).map_or(Ok(()), |atom| self.check_fact_types(atom)),
+            Some(name) => {
+                let Some(predicate) = self.predicates.iter()
+                    .find(|predicate| predicate.name == name && predicate.parameter_types.len() == items.len() - 1)
+                else {
+                    return Ok(());
+                };
+                for (arg, expected) in items[1..].iter().zip(&predicate.parameter_types) {
+                    let Some(object) = self.objects.iter().find(|object| arg.as_atom() == Some(object.name.as_str())) else {
+                        continue;
+                    };
+                    // `(either a b)` accepts any of its types
+                    let allowed = expected.strip_prefix("(either ")
+                        .and_then(|types| types.strip_suffix(')'))
+                        .map_or(vec![expected.as_str()], |types| types.split_whitespace().collect());
+                    if !allowed.iter().any(|allowed| self.is_subtype(&object.type_name, allowed)) {
+                        let span = arg.span();
+                        return Err(PddlParseError::ArgumentTypeMismatch {
+                            predicate: name.to_string(),
+                            argument: object.name.clone(),
+                            expected: expected.clone(),
+                            found: object.type_name.clone(),
+                            line: span.line,
+                            column: span.column,
+                        });
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
     fn find_predicate_index(predicates: &[PDDLPredicate], name: &str, args: &[String]) -> Option<usize> {
         for (index, predicate) in predicates.iter().enumerate() {
             if predicate.name == name && predicate.parameters.len() == args.len() {
@@ -927,4 +3213,10 @@ impl TemporalTask {
         }
         None
     }
-}
\ No newline at end of file
+}
+
+impl Default for TemporalTask {
+    fn default() -> Self {
+        Self::new()
+    }
+}
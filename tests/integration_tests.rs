@@ -1,5 +1,15 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
 use std::fs;
-use temporal_planner::{TemporalTask, TemporalAStarSearch, TemporalSearchEngine, SearchResult};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use temporal_planner::heuristics::{GoalCountHeuristic, TemporalAddHeuristic, TemporalAdmissibleHeuristic, TemporalFFHeuristic, TemporalHeuristic};
+use temporal_planner::search::DEFAULT_SEPARATION;
+use temporal_planner::state_space::{StateId, StateSpace, TemporalState};
+use temporal_planner::temporal_task::Comparison;
+use temporal_planner::{create_engine, CancellationToken, Condition, CostModel, MetricDirection, EngineKind, FactSet, Optimality, ParsedDomain, PddlParseError, Plan, PlannerConfig, PreprocessOptions, SearchLimits, State, StripsError, TemporalGreedySearch, TemporalPlanner, TemporalTask, TemporalAStarSearch, TemporalSearchEngine, SearchResult, SUPPORTED_REQUIREMENTS};
 
 #[test]
 fn test_simple_robot_domain_parsing() {
@@ -95,7 +105,6 @@ fn test_factory_automation_complex_parsing() {
 }
 
 #[test]
-#[ignore] // Ignore until search is fully implemented
 fn test_simple_robot_planning() {
     let domain_path = "tests/fixtures/domains/simple_robot.pddl";
     let problem_path = "tests/fixtures/problems/simple_delivery.pddl";
@@ -119,7 +128,12 @@ fn test_simple_robot_planning() {
             println!("   - Plan length: {}", plan.actions.len());
             println!("   - Plan cost: {}", plan.cost);
         }
-        SearchResult::Failure => {
+        SearchResult::Failure
+        | SearchResult::Unsolvable(_)
+        | SearchResult::ResourceLimit(_)
+        | SearchResult::Cancelled
+        | SearchResult::InvalidTask(_)
+        | SearchResult::Partial { .. } => {
             panic!("Planning should have found a solution");
         }
     }
@@ -196,10 +210,3370 @@ fn test_error_handling_invalid_domain() {
     println!("   - Actions parsed from invalid domain: {}", task.actions.len());
 }
 
+#[test]
+fn test_combined_domain_and_problem_file() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+
+    // Problem first, domain second: the split must not depend on ordering
+    let combined_content = format!("; combined file\n{}\n{}", problem_content, domain_content);
+
+    let separate = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let combined = TemporalTask::from_pddl_combined(&combined_content);
+
+    let names = |task: &TemporalTask| task.actions.iter()
+        .map(|a| (a.name.clone(), a.duration))
+        .collect::<Vec<_>>();
+    assert_eq!(names(&combined), names(&separate), "Actions should match the separate-file parse");
+    assert_eq!(combined.initial_state, separate.initial_state, "Initial state should match");
+    assert_eq!(combined.goal_conditions.len(), separate.goal_conditions.len(), "Goals should match");
+    assert_eq!(combined.goal_conditions.len(), 2);
+
+    println!("✅ Combined domain/problem file test passed");
+}
+
+#[test]
+fn test_min_action_duration() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+    // Regular actions default to 1.0, below the 2.0 of the durative 'deliver'
+    assert_eq!(task.min_action_duration(), Some(1.0));
+
+    let mut zero_duration_task = task.clone();
+    zero_duration_task.actions.retain(|a| a.name == "deliver" || a.name == "move");
+    zero_duration_task.actions.iter_mut()
+        .filter(|a| a.name == "move")
+        .for_each(|a| a.duration = 0.0);
+    assert_eq!(zero_duration_task.min_action_duration(), Some(2.0), "Zero durations are ignored");
+
+    assert_eq!(TemporalTask::new().min_action_duration(), None);
+
+    let domain = r#"(define (domain drive)
+      (:requirements :durative-actions :numeric-fluents)
+      (:predicates (arrived) (parked))
+      (:functions (distance) (speed))
+      (:durative-action drive :parameters ()
+        :duration (= ?duration (/ (distance) (speed)))
+        :condition (at start (parked)) :effect (at end (arrived)))
+      (:durative-action stall :parameters ()
+        :duration (= ?duration (/ 0 0))
+        :condition (at start (parked)) :effect (at end (arrived)))
+      (:durative-action park :parameters ()
+        :duration (= ?duration 3)
+        :condition (at start (arrived)) :effect (at end (parked))))"#;
+    let problem = r#"(define (problem drive-1) (:domain drive)
+      (:init (parked) (= (distance) 1) (= (speed) 5)) (:goal (arrived)))"#;
+    let task = TemporalTask::from_pddl(domain, problem);
+    assert_eq!(task.min_action_duration(), Some(3.0), "Fluent-dependent and NaN durations are skipped");
+}
+
+#[test]
+fn test_grounding_respects_type_hierarchy() {
+    let domain_content = r#"
+(define (domain transport)
+  (:requirements :strips :typing)
+  (:types truck bike - vehicle vehicle place - object)
+  (:predicates (at ?v - vehicle ?p - place) (parked ?t - truck))
+  (:action drive
+    :parameters (?v - vehicle ?from ?to - place)
+    :precondition (at ?v ?from)
+    :effect (and (not (at ?v ?from)) (at ?v ?to)))
+  (:action park
+    :parameters (?t - truck ?p - place)
+    :precondition (at ?t ?p)
+    :effect (parked ?t))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem transport-problem)
+  (:domain transport)
+  (:objects truck1 - truck bike1 - bike home work - place)
+  (:init (at truck1 home) (at bike1 home))
+  (:goal (and (at bike1 work) (parked truck1)))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+
+    assert_eq!(task.actions.len(), 2, "Schemas stay lifted");
+    assert!(task.is_subtype("truck", "vehicle"));
+    assert!(!task.is_subtype("place", "vehicle"));
+
+    // drive: 2 vehicles x 2 places x 2 places; park: 1 truck x 2 places
+    assert_eq!(task.ground_actions.len(), 10);
+    assert!(task.ground_actions.iter().any(|a| a.name == "drive bike1 home work"));
+    assert!(task.ground_actions.iter().all(|a| a.name != "park bike1 home"), "Bikes are not trucks");
+
+    let at_truck_home = task.fact_id("at", &["truck1".to_string(), "home".to_string()])
+        .expect("Initial atom should be indexed");
+    assert!(task.initial_state.facts[at_truck_home]);
+    assert_eq!(task.initial_state.facts.len(), task.fact_names.len());
+}
+
+#[test]
+fn test_always_constraint_reported_as_blocker() {
+    let domain_content = r#"
+(define (domain corridor)
+  (:requirements :strips :constraints)
+  (:predicates (at ?r) (connected ?from ?to))
+  (:action move
+    :parameters (?from ?to)
+    :precondition (and (at ?from) (connected ?from ?to))
+    :effect (and (not (at ?from)) (at ?to)))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem corridor-problem)
+  (:domain corridor)
+  (:objects hall lobby office)
+  (:init (at hall) (connected hall lobby) (connected lobby office))
+  (:goal (at office))
+  (:constraints (always (not (at lobby))))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    assert_eq!(task.always_constraints.len(), 1);
+
+    let mut unconstrained = task.clone();
+    unconstrained.always_constraints.clear();
+    let mut search_engine = TemporalAStarSearch::new();
+    assert!(matches!(search_engine.search(&unconstrained), SearchResult::Solution(_)),
+        "Without the constraint the goal is reachable");
+
+    match search_engine.search(&task) {
+        SearchResult::Unsolvable(reason) => {
+            assert!(reason.contains("(always (not (at lobby)))"), "Reason should name the constraint: {}", reason);
+        }
+        other => panic!("Expected Unsolvable, got {:?}", other),
+    }
+
+    // The diagnosis searches the unconstrained task on its own, out of sight of the caller. It
+    // passes through the lobby, where one of these goals holds.
+    let two_goals = problem_content.replace("(:goal (at office))", "(:goal (and (at office) (not (at hall))))");
+    let task = TemporalTask::from_pddl(domain_content, &two_goals);
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&reports);
+    let mut search_engine = TemporalAStarSearch::new()
+        .with_progress_callback(move |progress| seen.lock().unwrap().push(progress));
+    assert!(matches!(search_engine.search(&task), SearchResult::Unsolvable(_)));
+    let reports = reports.lock().unwrap();
+    assert!(reports.is_empty(), "Partial plans through the lobby were reported: {:?}", reports);
+    assert_eq!(search_engine.stats().nodes_expanded, 1, "Only the initial state, as moving on breaks the constraint");
+}
+
+#[test]
+fn test_simple_robot_compiles_to_strips() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let strips = task.to_strips().expect("Simple robot domain should compile to STRIPS");
+
+    assert_eq!(strips.facts, task.fact_names);
+    assert_eq!(strips.actions.len(), task.ground_actions.len());
+
+    // Breadth-first search over the compiled task
+    let mut visited = HashSet::from([strips.initial_state.clone()]);
+    let mut queue = VecDeque::from([strips.initial_state.clone()]);
+    let mut goal_state = None;
+    while let Some(state) = queue.pop_front() {
+        if strips.is_goal(&state) {
+            goal_state = Some(state);
+            break;
+        }
+        for action in strips.actions.iter().filter(|a| strips.is_applicable(a, &state)) {
+            let next = strips.apply(action, &state);
+            if visited.insert(next.clone()) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    let goal_state = goal_state.expect("Compiled task should reach the goal");
+    for goal in &task.goal_conditions {
+        let id = task.fact_id(&goal.predicate, &goal.args).unwrap();
+        assert!(goal_state[id], "Goal {} should hold", goal);
+    }
+}
+
+#[test]
+fn test_strips_rejects_timed_initial_literals() {
+    let domain_content = r#"
+(define (domain doors)
+  (:requirements :strips :timed-initial-literals)
+  (:predicates (open) (through))
+  (:action pass :parameters () :precondition (open) :effect (through))
+)
+"#;
+    let problem_content = r#"
+(define (problem doors-problem)
+  (:domain doors)
+  (:init (at 10 (open)))
+  (:goal (through))
+)
+"#;
+
+    // Dropping the literal would leave the door shut forever
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    assert_eq!(task.to_strips(), Err(StripsError::TimedLiteral { literal: "(at 10 (open))".to_string() }));
+}
+
+#[test]
+fn test_strips_splits_conditional_effects() {
+    let domain_content = r#"
+(define (domain lights)
+  (:requirements :strips :conditional-effects)
+  (:predicates (powered) (lit) (switched))
+  (:action flip
+    :parameters ()
+    :precondition (and)
+    :effect (and (switched) (when (powered) (lit))))
+)
+"#;
+    let problem_content = r#"
+(define (problem lights-problem)
+  (:domain lights)
+  (:init (powered))
+  (:goal (lit))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let strips = task.to_strips().expect("Conditional effects should compile to STRIPS");
+    assert_eq!(strips.actions.len(), 2, "One variant fires the effect, one is blocked by (not (powered))");
+
+    let fact = |name: &str| strips.facts.iter().position(|f| f == name).unwrap();
+    let fires = strips.actions.iter().find(|a| a.add_effects.contains(&fact("lit"))).unwrap();
+    assert!(fires.preconditions.contains(&fact("powered")));
+    let blocked = strips.actions.iter().find(|a| !a.add_effects.contains(&fact("lit"))).unwrap();
+    assert_eq!(blocked.negative_preconditions, vec![fact("powered")]);
+
+    // In any state exactly one variant applies
+    let state = strips.initial_state.clone();
+    let applicable: Vec<_> = strips.actions.iter().filter(|a| strips.is_applicable(a, &state)).collect();
+    assert_eq!(applicable.len(), 1);
+    assert!(strips.is_goal(&strips.apply(applicable[0], &state)));
+}
+
+#[test]
+fn test_try_from_pddl_reports_errors() {
+    let problem = r#"
+(define (problem p)
+  (:domain d)
+  (:init (ready))
+  (:goal (ready))
+)
+"#;
+
+    let unbalanced = "(define (domain d)\n  (:predicates (ready)\n  (:action wait :parameters () :effect (ready))\n)\n";
+    assert_eq!(
+        TemporalTask::try_from_pddl(unbalanced, problem).unwrap_err(),
+        PddlParseError::UnclosedParen { file: "domain", line: 1, column: 1 }
+    );
+    let extra_close = "(define (domain d)\n  (:predicates (ready)))\n)\n";
+    assert_eq!(
+        TemporalTask::try_from_pddl(extra_close, problem).unwrap_err(),
+        PddlParseError::UnmatchedCloseParen { file: "domain", line: 3, column: 1 }
+    );
+
+    let unknown_section = "(define (domain d) (:predicates (ready)) (:predicate (done)))";
+    assert_eq!(
+        TemporalTask::try_from_pddl(unknown_section, problem).unwrap_err(),
+        PddlParseError::UnknownSection { file: "domain", section: ":predicate".to_string(), line: 1, column: 43 }
+    );
+
+    let undeclared = "(define (domain d) (:predicates (ready)) (:action finish :parameters () :precondition (ready) :effect (done)))";
+    let error = TemporalTask::try_from_pddl(undeclared, problem).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "domain line 1, column 104: predicate 'done' used in action 'finish' is not declared in :predicates"
+    );
+
+    let domain = "(define (domain d)
+  (:predicates (ready) (at-position ?e ?p))
+  (:action move :parameters (?e) :precondition (at-position ?e) :effect (ready)))";
+    let error = TemporalTask::try_from_pddl(domain, problem).unwrap_err();
+    assert!(matches!(error, PddlParseError::ArityMismatch { expected: 2, found: 1, .. }));
+    assert_eq!(
+        error.to_string(),
+        "domain line 3, column 49: predicate 'at-position' used in action 'move' with 1 argument(s), but declared with 2"
+    );
+    let problem = problem.replace("(:init (ready))", "(:init (ready extra))");
+    let error = TemporalTask::try_from_pddl("(define (domain d) (:predicates (ready)))", &problem).unwrap_err();
+    assert!(matches!(error, PddlParseError::ArityMismatch { file: "problem", expected: 0, found: 1, .. }), "{}", error);
+}
+
+#[test]
+fn test_parse_errors_report_source_position() {
+    let domain = r#"(define (domain robots)
+  (:requirements :strips :typing)
+  (:types robot location)
+
+  ; a declaration missing its opening parenthesis
+  (:predicates (at ?r - robot ?l - location)
+               robot-ready ?r - robot
+               (idle ?r - robot))
+
+  (:action wait
+    :parameters (?r - robot)
+    :precondition (idle ?r)
+    :effect (idle ?r))
+)
+"#;
+    let problem = "(define (problem p) (:domain robots) (:objects r1 - robot) (:init (idle r1)) (:goal (idle r1)))";
+
+    match TemporalTask::try_from_pddl(domain, problem).unwrap_err() {
+        PddlParseError::UnexpectedToken { token, line, column, .. } => {
+            assert_eq!(token, "robot-ready");
+            assert_eq!((line, column), (7, 16));
+        }
+        other => panic!("Expected an unexpected-token error, got {}", other),
+    }
+
+    let garbage = "(define (domain invalid)\n  this is not valid PDDL\n)";
+    let error = TemporalTask::try_from_pddl(garbage, problem).unwrap_err();
+    assert_eq!(error.to_string(), "domain line 2, column 3: unexpected token 'this', expected a (:section ...)");
+}
+
+#[test]
+fn test_try_from_pddl_accepts_fixtures() {
+    let pairs = [
+        ("simple_robot", "simple_delivery"),
+        ("blocks_world", "stack_blocks"),
+        ("factory_automation", "factory_production"),
+    ];
+    for (domain, problem) in pairs {
+        let domain_content = fs::read_to_string(format!("tests/fixtures/domains/{}.pddl", domain))
+            .expect("Failed to read domain file");
+        let problem_content = fs::read_to_string(format!("tests/fixtures/problems/{}.pddl", problem))
+            .expect("Failed to read problem file");
+        if let Err(error) = TemporalTask::try_from_pddl(&domain_content, &problem_content) {
+            panic!("{} / {} should parse: {}", domain, problem, error);
+        }
+    }
+}
+
+#[test]
+fn test_identical_packages_are_symmetric() {
+    let domain_content = r#"
+(define (domain delivery)
+  (:requirements :strips :typing)
+  (:types package truck location)
+  (:predicates (at ?o - object ?l - location) (in ?p - package ?t - truck))
+  (:action load
+    :parameters (?p - package ?t - truck ?l - location)
+    :precondition (and (at ?p ?l) (at ?t ?l))
+    :effect (and (not (at ?p ?l)) (in ?p ?t)))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem delivery-problem)
+  (:domain delivery)
+  (:objects pkg1 pkg2 pkg3 - package truck1 - truck depot office - location)
+  (:init (at pkg1 depot) (at pkg2 depot) (at pkg3 office) (at truck1 depot))
+  (:goal (and (at pkg1 office) (at pkg2 office) (at pkg3 office)))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    assert_eq!(
+        task.object_symmetries(),
+        vec![vec!["pkg1".to_string(), "pkg2".to_string()]],
+        "pkg3 starts elsewhere and the locations play different roles"
+    );
+}
+
+#[test]
+fn test_symmetries_see_every_constant_and_only_swap_arguments() {
+    let domain_content = r#"
+(define (domain crates)
+  (:requirements :strips :typing :numeric-fluents :conditional-effects)
+  (:types item)
+  (:constants c1 c2 c3 c4 - item)
+  (:predicates (p ?x - item) (done) (b))
+  (:functions (weight ?x - item))
+  (:action lift
+    :parameters ()
+    :precondition (>= (weight c1) 0)
+    :effect (when (p c2) (done)))
+)
+"#;
+    let problem_content = r#"
+(define (problem crates-problem)
+  (:domain crates)
+  (:objects a b - item)
+  (:init (p c1) (p c2) (p c3) (p c4) (p a) (p b) (b)
+         (= (weight c1) 1) (= (weight c2) 1) (= (weight c3) 1) (= (weight c4) 1)
+         (= (weight a) 1) (= (weight b) 1))
+  (:goal (done))
+)
+"#;
+
+    // c1 and c2 appear only in a numeric condition and a conditional effect; swapping a and b
+    // leaves the predicate (b) alone
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    assert_eq!(
+        task.object_symmetries(),
+        vec![vec!["c3".to_string(), "c4".to_string(), "a".to_string(), "b".to_string()]],
+    );
+}
+
+#[test]
+fn test_timed_literals_break_symmetry() {
+    let domain_content = r#"
+(define (domain doors)
+  (:requirements :strips :typing :timed-initial-literals)
+  (:types door)
+  (:predicates (open ?d - door) (through ?d - door))
+  (:action pass :parameters (?d - door) :precondition (open ?d) :effect (through ?d))
+)
+"#;
+    let problem = |init: &str| format!(r#"
+(define (problem doors-problem)
+  (:domain doors)
+  (:objects a b - door)
+  (:init {init})
+  (:goal (and (through a) (through b)))
+)
+"#);
+
+    let task = TemporalTask::from_pddl(domain_content, &problem("(at 10 (open a))"));
+    assert!(task.object_symmetries().is_empty(), "Only door a opens");
+    let task = TemporalTask::from_pddl(domain_content, &problem("(at 10 (open a)) (at 10 (open b))"));
+    assert_eq!(task.object_symmetries(), vec![vec!["a".to_string(), "b".to_string()]]);
+    let task = TemporalTask::from_pddl(domain_content, &problem("(at 10 (open a)) (at 20 (open b))"));
+    assert!(task.object_symmetries().is_empty(), "The doors open at different times");
+}
+
+#[test]
+fn test_nested_temporal_formulas_are_parsed() {
+    let domain_content = r#"
+(define (domain survey)
+  (:requirements :durative-actions :typing)
+  (:types robot room)
+  (:predicates (at ?r - robot ?x - room)
+               (door-open ?from ?to - room)
+               (visited ?x - room))
+  (:durative-action go
+    :parameters (?r - robot
+                 ?from ?to - room) ; split over two lines
+    :duration (= ?duration 4)
+    :condition (and (at start (at ?r ?from))
+                    (over all (and (door-open ?from ?to)
+                                   (door-open ?to ?from))))
+    :effect (and (at start (not (at ?r ?from)))
+                 (at end (and (at ?r ?to) (visited ?to)))))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem survey-problem)
+  (:domain survey)
+  (:objects r1 - robot hall lab - room)
+  (:init (at r1 hall) (door-open hall lab) (door-open lab hall))
+  (:goal (visited lab))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let go = &task.actions[0];
+    assert_eq!(go.duration, 4.0);
+    assert_eq!(go.conditions_over_all.len(), 2, "Conjunctions nest inside (over all ...)");
+    assert_eq!(go.effects_end.len(), 2, "Conjunctions nest inside (at end ...)");
+    assert!(task.ground_actions.iter().any(|action| action.name == "go r1 hall lab"));
+
+    let mut search_engine = TemporalAStarSearch::new();
+    assert!(matches!(search_engine.search(&task), SearchResult::Solution(_)));
+}
+
+#[test]
+fn test_commented_out_action_is_not_parsed() {
+    let domain_content = r#"
+(define (domain switches)
+  (:requirements :strips :negative-preconditions)
+  (:predicates (on) (broken)) ; (:predicates (off))
+  (:action turn-on
+    :parameters ()
+    :precondition (not (on)) ; the switch must be off;
+    :effect (on))
+  ; (:action smash
+  ;   :parameters ()
+  ;   :precondition (on)
+  ;   :effect (and (broken)
+  ;                (not (on))))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem switches-problem)
+  (:domain switches)
+  (:init) ; (on)
+  (:goal (on))
+)
+"#;
+
+    let task = TemporalTask::try_from_pddl(domain_content, problem_content)
+        .expect("Comments should not unbalance the parentheses");
+    let names: Vec<&str> = task.actions.iter().map(|action| action.name.as_str()).collect();
+    assert_eq!(names, vec!["turn-on"]);
+    assert!(task.fact_id("on", &[]).is_some_and(|id| !task.initial_state.facts[id]));
+    assert_eq!(task.goal_conditions.len(), 1);
+}
+
+#[test]
+fn test_max_makespan_bounds_the_plan() {
+    let domain_content = r#"
+(define (domain hallway)
+  (:requirements :durative-actions)
+  (:predicates (at ?x) (next ?from ?to))
+  (:durative-action walk
+    :parameters (?from ?to)
+    :duration (= ?duration 5)
+    :condition (and (at start (at ?from)) (over all (next ?from ?to)))
+    :effect (and (at start (not (at ?from))) (at end (at ?to))))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem hallway-problem)
+  (:domain hallway)
+  (:objects a b c)
+  (:init (at a) (next a b) (next b c))
+  (:goal (at c))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+
+    // Two walks of 5 are needed, so no plan finishes by 9
+    let mut tight = TemporalAStarSearch::new().with_max_makespan(9.0);
+    assert!(matches!(tight.search(&task), SearchResult::Unsolvable(_)));
+
+    let mut generous = TemporalAStarSearch::new().with_max_makespan(20.0);
+    match generous.search(&task) {
+        SearchResult::Solution(plan) => {
+            assert_eq!(plan.cost, 10.0);
+            assert_eq!(plan.start_times[0], 0.0);
+            assert!(
+                (plan.start_times[1] - 5.001).abs() < 1e-9,
+                "The second walk starts when the first ends, plus the default separation"
+            );
+        }
+        other => panic!("Expected a plan within the horizon, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_max_makespan_keeps_plans_of_short_fluent_durations_and_timed_literals() {
+    let domain_content = r#"
+(define (domain shuttle)
+  (:requirements :durative-actions :numeric-fluents)
+  (:predicates (at ?x) (next ?from ?to))
+  (:functions (distance) (speed))
+  (:durative-action drive
+    :parameters (?from ?to)
+    :duration (= ?duration (/ (distance) (speed)))
+    :condition (and (at start (at ?from)) (over all (next ?from ?to)))
+    :effect (and (at start (not (at ?from))) (at end (at ?to))))
+)
+"#;
+    let problem_content = r#"
+(define (problem shuttle-problem)
+  (:domain shuttle)
+  (:objects a b c)
+  (:init (at a) (next a b) (next b c) (= (distance) 1) (= (speed) 5))
+  (:goal (at c))
+)
+"#;
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+
+    // Each drive takes 0.2, not the 1.0 a duration that depends on fluents stands in for
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().with_max_makespan(0.5).search(&task) else {
+        panic!("Two drives of 0.2 fit within 0.5");
+    };
+    assert!((plan.makespan() - 0.401).abs() < 1e-9, "Two drives and the separation between them");
+    let result = TemporalAStarSearch::new().with_max_makespan(0.3).search(&task);
+    assert!(matches!(result, SearchResult::Unsolvable(_)));
+
+    let domain_content = r#"
+(define (domain bakery)
+  (:requirements :durative-actions :timed-initial-literals)
+  (:predicates (ready) (open))
+  (:durative-action prepare
+    :parameters ()
+    :duration (= ?duration 2.5)
+    :condition (at start (not (ready)))
+    :effect (at end (ready)))
+)
+"#;
+    let problem_content = r#"
+(define (problem bakery-problem)
+  (:domain bakery)
+  (:init (at 3 (open)))
+  (:goal (and (ready) (open)))
+)
+"#;
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+
+    // After preparing, the goal only waits for the shop to open at 3; no other action is needed
+    let result = TemporalAStarSearch::new().with_max_makespan(3.0).search(&task);
+    assert!(matches!(result, SearchResult::Solution(_)), "Expected a plan within the horizon, got {:?}", result);
+}
+
+#[test]
+fn test_goals_achieved_tracks_progress() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let planner = TemporalPlanner::new();
+    let goal_count = task.goal_conditions.len() + task.numeric_goal_conditions.len();
+    assert!(goal_count > 0);
+
+    assert_eq!(planner.goals_achieved(&task, &task.initial_state), 0);
+
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    let state_space = StateSpace::new(task.clone());
+    let mut state = TemporalState {
+        classical_state: task.initial_state.clone(),
+        scheduled_effects: Vec::new(),
+        scheduled_numeric_effects: Vec::new(),
+        invariants: Vec::new(),
+        time: 0.0,
+    };
+    for &action_idx in &plan.actions {
+        state = state_space.apply_action_to_completion(&state, action_idx)
+            .expect("Plan steps should be executable");
+    }
+    assert_eq!(planner.goals_achieved(&task, &state.classical_state), goal_count);
+}
+
+#[test]
+fn test_numeric_init_values_use_function_keys() {
+    let domain_content = r#"
+(define (domain logistics)
+  (:requirements :typing :numeric-fluents)
+  (:types truck)
+  (:predicates (at-depot ?t - truck))
+  (:functions (fuel ?t - truck) (total-fuel-used))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem logistics-problem)
+  (:domain logistics)
+  (:objects truck1 - truck)
+  (:init (at-depot truck1)
+         (= (fuel truck1) 50)
+         (= (total-fuel-used) -2.5))
+  (:goal (at-depot truck1))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let values = &task.initial_state.numeric_values;
+    assert_eq!(values["fuel truck1"], 50.0);
+    assert_eq!(values["total-fuel-used"], -2.5);
+    assert!(task.fact_id("=", &[]).is_none(), "Assignments are not facts");
+}
+
+#[test]
+fn test_tie_breaker_changes_expansion_order() {
+    let domain_content = r#"
+(define (domain commute)
+  (:requirements :durative-actions)
+  (:predicates (at ?x) (road ?from ?to) (rail ?from ?to))
+  (:durative-action drive
+    :parameters (?from ?to)
+    :duration (= ?duration 2)
+    :condition (at start (and (at ?from) (road ?from ?to)))
+    :effect (and (at start (not (at ?from))) (at end (at ?to))))
+  (:durative-action train
+    :parameters (?from ?to)
+    :duration (= ?duration 4)
+    :condition (at start (and (at ?from) (rail ?from ?to)))
+    :effect (and (at start (not (at ?from))) (at end (at ?to))))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem commute-problem)
+  (:domain commute)
+  (:objects home town office)
+  (:init (at home) (road home town) (road town office) (rail home office))
+  (:goal (at office))
+)
+"#;
+
+    // Both plans reach the goal at time 4, so only the tie-breaker decides which is found
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let plan_length = |mut search_engine: TemporalAStarSearch| match search_engine.search(&task) {
+        SearchResult::Solution(plan) => plan.actions.len(),
+        other => panic!("Expected a plan, got {:?}", other),
+    };
+
+    let shallow_first = TemporalAStarSearch::new().with_tie_breaker(|a, b| a.depth.cmp(&b.depth));
+    let deep_first = TemporalAStarSearch::new().with_tie_breaker(|a, b| b.depth.cmp(&a.depth));
+    assert_eq!(plan_length(shallow_first), 1, "Taking the train is one action");
+    assert_eq!(plan_length(deep_first), 2, "Driving takes two actions");
+}
+
+#[test]
+fn test_duplicate_goal_sections_use_the_last() {
+    let domain_content = r#"
+(define (domain rooms)
+  (:requirements :strips)
+  (:predicates (lit ?r))
+  (:action switch-on
+    :parameters (?r)
+    :precondition (not (lit ?r))
+    :effect (lit ?r))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem rooms-problem)
+  (:domain rooms)
+  (:objects kitchen hall)
+  (:init)
+  (:goal (lit kitchen))
+  (:goal (lit hall))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let goals: Vec<String> = task.goal_conditions.iter().map(|goal| goal.to_string()).collect();
+    assert_eq!(goals, vec!["(lit hall)".to_string()]);
+    assert_eq!(task.warnings.len(), 1);
+    assert!(task.warnings[0].contains("2 (:goal ...) sections"), "Unexpected warning: {}", task.warnings[0]);
+    assert!(task.warnings[0].contains("line 7"), "The warning should point at the section used: {}", task.warnings[0]);
+}
+
+#[test]
+fn test_schedule_equals_ignores_step_order() {
+    let plan = Plan {
+        actions: vec![0, 1, 2],
+        start_times: vec![0.0, 2.0, 2.0],
+        end_times: vec![2.0, 4.0, 3.0],
+        cost: 4.0,
+        sequential_cost: 4.0,
+        optimality: Optimality::default(),
+    };
+    let reordered = Plan {
+        actions: vec![2, 0, 1],
+        start_times: vec![2.0, 0.0, 2.0 + 1e-9],
+        end_times: vec![3.0, 2.0, 4.0],
+        cost: 4.0,
+        sequential_cost: 4.0,
+        optimality: Optimality::default(),
+    };
+    assert!(plan.schedule_equals(&reordered, 1e-6));
+    assert!(reordered.schedule_equals(&plan, 1e-6));
+
+    let mut shifted = reordered.clone();
+    shifted.start_times[0] = 2.5;
+    assert!(!plan.schedule_equals(&shifted, 0.1), "Action 2 now starts 0.5 later");
+    assert!(plan.schedule_equals(&shifted, 0.5));
+
+    let shorter = Plan {
+        actions: vec![0, 1],
+        start_times: vec![0.0, 2.0],
+        end_times: vec![2.0, 4.0],
+        cost: 4.0,
+        sequential_cost: 4.0,
+        optimality: Optimality::default(),
+    };
+    assert!(!plan.schedule_equals(&shorter, 1.0));
+}
+
+#[test]
+fn test_search_limits_stop_the_search() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    let search_with = |limits: SearchLimits| TemporalAStarSearch::new().with_limits(limits).search(&task);
+
+    let one_expansion = SearchLimits { max_expansions: Some(1), ..SearchLimits::default() };
+    match search_with(one_expansion) {
+        SearchResult::ResourceLimit(reason) => assert!(reason.contains("expanded 1 nodes"), "Unexpected reason: {}", reason),
+        other => panic!("Expected the expansion limit to trigger, got {:?}", other),
+    }
+
+    let no_time = SearchLimits { max_time: Some(Duration::ZERO), ..SearchLimits::default() };
+    assert!(matches!(search_with(no_time), SearchResult::ResourceLimit(_)));
+
+    let generous = SearchLimits { max_expansions: Some(100_000), max_time: Some(Duration::from_secs(60)) };
+    assert!(matches!(search_with(generous), SearchResult::Solution(_)));
+}
+
+#[test]
+fn test_progress_callback_reports_partial_plans() {
+    let domain_content = r#"
+(define (domain chores)
+  (:predicates (dishes-done) (laundry-done))
+  (:durative-action wash-dishes
+    :parameters ()
+    :duration (= ?duration 2)
+    :condition (and)
+    :effect (at end (dishes-done)))
+  (:durative-action do-laundry
+    :parameters ()
+    :duration (= ?duration 5)
+    :condition (and)
+    :effect (at end (laundry-done)))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem chores-problem)
+  (:domain chores)
+  (:init)
+  (:goal (and (dishes-done) (laundry-done)))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&reports);
+    let result = TemporalAStarSearch::new()
+        .with_progress_callback(move |progress| {
+            let SearchResult::Partial { plan, goals_met } = progress else {
+                panic!("Progress reports should be partial plans, got {:?}", progress);
+            };
+            recorder.lock().unwrap().push((plan.actions.len(), goals_met));
+        })
+        .search(&task);
+
+    let SearchResult::Solution(plan) = result else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(plan.actions.len(), 2);
+    // The one-goal state is reported as it is expanded; the goal state itself comes back as the solution
+    assert_eq!(*reports.lock().unwrap(), vec![(1, 1)]);
+}
+
+#[test]
+fn test_compiled_search_matches_string_search() {
+    let fixtures = [
+        ("tests/fixtures/domains/simple_robot.pddl", "tests/fixtures/problems/simple_delivery.pddl"),
+        ("tests/fixtures/domains/blocks_world.pddl", "tests/fixtures/problems/stack_blocks.pddl"),
+    ];
+    for (domain_path, problem_path) in fixtures {
+        let domain_content = fs::read_to_string(domain_path).expect("Failed to read domain file");
+        let problem_content = fs::read_to_string(problem_path).expect("Failed to read problem file");
+        let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+        let compiled = task.compile();
+
+        assert_eq!(compiled.facts.len(), task.fact_names.len());
+        assert_eq!(compiled.actions.len(), task.ground_actions.len());
+        for (fact, name) in task.fact_names.iter().enumerate() {
+            assert_eq!(&compiled.atom_name(fact as u32), name, "Interned atoms must spell the original fact");
+        }
+
+        let by_name = TemporalAStarSearch::new().search(&task);
+        let by_index = TemporalAStarSearch::new().search_compiled(&task, &compiled);
+        match (by_name, by_index) {
+            (SearchResult::Solution(expected), SearchResult::Solution(plan)) => {
+                assert_eq!(plan.actions, expected.actions, "{}", problem_path);
+                assert_eq!(plan.cost, expected.cost);
+            }
+            (SearchResult::Failure, SearchResult::Failure) => {}
+            (expected, actual) => panic!("{}: string search gave {:?}, compiled search {:?}", problem_path, expected, actual),
+        }
+    }
+}
+
+#[test]
+fn test_search_reports_statistics() {
+    let domain_content = r#"
+(define (domain minimal)
+  (:predicates (p) (q))
+  (:action make-q
+    :parameters ()
+    :precondition (p)
+    :effect (q))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem minimal-problem)
+  (:domain minimal)
+  (:init (p))
+  (:goal (q))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let mut search = TemporalAStarSearch::new();
+    assert!(matches!(search.search(&task), SearchResult::Solution(_)));
+
+    let stats = search.stats();
+    assert!(stats.nodes_expanded > 0);
+    assert!(stats.nodes_generated > stats.nodes_expanded, "The goal node is generated but not expanded");
+    assert!(stats.peak_open_size >= 1);
+    assert_eq!(stats.goal_h, Some(0.0));
+}
+
+#[test]
+fn test_plan_json_names_actions_in_order() {
+    let domain_content = r#"
+(define (domain minimal)
+  (:predicates (p) (q) (r))
+  (:action make-q
+    :parameters ()
+    :precondition (p)
+    :effect (q))
+  (:action make-r
+    :parameters ()
+    :precondition (q)
+    :effect (r))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem minimal-problem)
+  (:domain minimal)
+  (:init (p))
+  (:goal (r))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+
+    let json: serde_json::Value = serde_json::from_str(&plan.to_json(&task)).expect("Plans serialize to valid JSON");
+    let steps = json["steps"].as_array().expect("The plan lists its steps");
+    let names: Vec<&str> = steps.iter().map(|step| step["action"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["make-q", "make-r"]);
+    assert_eq!(json["makespan"].as_f64(), Some(plan.makespan()));
+    for (step, (&start, &end)) in steps.iter().zip(plan.start_times.iter().zip(&plan.end_times)) {
+        assert_eq!(step["start"].as_f64(), Some(start));
+        assert_eq!(step["end"].as_f64(), Some(end));
+    }
+}
+
+#[test]
+fn test_robot_positions_form_a_mutex_group() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    let groups = task.mutex_groups_named();
+    let mut positions = groups.iter()
+        .find(|group| group.iter().all(|atom| atom.starts_with("at robot1 ")))
+        .expect("The robot's positions should form a mutex group")
+        .clone();
+    positions.sort();
+    assert_eq!(positions, vec!["at robot1 depot", "at robot1 kitchen", "at robot1 office"]);
+}
+
+#[test]
+fn test_mutex_groups_span_predicates() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    let mut groups: Vec<Vec<String>> = task.mutex_groups_named();
+    for group in &mut groups {
+        group.sort();
+    }
+    // pick-up trades free-hands for holding, and drop and deliver trade it back
+    assert!(groups.contains(&vec![
+        "free-hands robot1".to_string(),
+        "holding robot1 package1".to_string(),
+        "holding robot1 package2".to_string(),
+    ]));
+    // A package is at one place, held, or delivered
+    assert!(groups.contains(&vec![
+        "delivered package1".to_string(),
+        "holding robot1 package1".to_string(),
+        "package-at package1 depot".to_string(),
+        "package-at package1 kitchen".to_string(),
+        "package-at package1 office".to_string(),
+    ]));
+    assert!(
+        groups.iter().all(|group| !group.iter().all(|atom| atom.starts_with("holding"))),
+        "holding atoms alone are covered by the larger groups that contain them"
+    );
+}
+
+#[test]
+fn test_mutex_group_encodes_to_one_finite_domain_variable() {
+    let domain = r#"(define (domain corridor)
+      (:requirements :strips :typing)
+      (:types robot position)
+      (:predicates (at ?r - robot ?p - position) (adjacent ?a - position ?b - position))
+      (:action step
+        :parameters (?r - robot ?from - position ?to - position)
+        :precondition (and (at ?r ?from) (adjacent ?from ?to))
+        :effect (and (not (at ?r ?from)) (at ?r ?to))))"#;
+    let problem = r#"(define (problem corridor-5)
+      (:domain corridor)
+      (:objects r1 - robot p1 p2 p3 p4 p5 - position)
+      (:init (at r1 p3) (adjacent p1 p2) (adjacent p2 p3) (adjacent p3 p4) (adjacent p4 p5)
+             (adjacent p2 p1) (adjacent p3 p2) (adjacent p4 p3) (adjacent p5 p4))
+      (:goal (at r1 p5)))"#;
+    let task = TemporalTask::from_pddl(domain, problem);
+    let encoding = task.finite_domain_encoding();
+
+    let position_of = |position: &str| task.fact_id("at", &["r1".to_string(), position.to_string()]).unwrap();
+    let (variable, _) = encoding.fact_values[position_of("p1")];
+    let positions = &encoding.variables[variable as usize];
+    let mut facts = positions.facts.to_vec();
+    facts.sort_unstable();
+    let mut expected: Vec<usize> = ["p1", "p2", "p3", "p4", "p5"].into_iter().map(position_of).collect();
+    expected.sort_unstable();
+    assert_eq!(facts, expected, "The robot's five positions share one variable");
+    assert_eq!(positions.domain_size(), 5, "The robot is always somewhere, so no \"none\" value is needed");
+
+    let encoded = encoding.encode(&task.initial_state).expect("The initial state respects the mutex groups");
+    let (_, value) = encoding.fact_values[position_of("p3")];
+    assert_eq!(encoded.values[variable as usize], value);
+    assert!(encoded.values.len() < task.initial_state.facts.len());
+    assert_eq!(encoding.decode(&encoded), task.initial_state);
+
+    let mut broken = task.initial_state.clone();
+    broken.facts.set(position_of("p5"), true);
+    assert!(encoding.encode(&broken).is_none(), "Two positions at once cannot be encoded");
+}
+
+#[test]
+fn test_greedy_search_finds_a_valid_plan() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    let mut planner = TemporalPlanner::new().with_search_engine(TemporalGreedySearch::new());
+    assert_eq!(planner.get_info().search_algorithm, "Temporal greedy best-first");
+    let SearchResult::Solution(plan) = planner.solve(&task) else {
+        panic!("Greedy search should have found a solution");
+    };
+
+    // Replay the plan to check every step is executable and the goal holds at the end
+    let state_space = StateSpace::new(task.clone());
+    let mut state = TemporalState {
+        classical_state: task.initial_state.clone(),
+        scheduled_effects: Vec::new(),
+        scheduled_numeric_effects: Vec::new(),
+        invariants: Vec::new(),
+        time: 0.0,
+    };
+    for &action_idx in &plan.actions {
+        assert!(state_space.get_applicable_actions(&state).iter().any(|&(idx, _)| idx == action_idx));
+        state = state_space.apply_action_to_completion(&state, action_idx)
+            .expect("Plan steps should be executable");
+    }
+    assert!(state_space.is_goal(&state));
+    assert_eq!(plan.cost, state.time);
+}
+
+#[test]
+fn test_heuristic_cache_hits_on_reconvergent_paths() {
+    // Both orders of the two chores reach the same classical state, at different times
+    let domain_content = r#"
+(define (domain chores)
+  (:requirements :durative-actions)
+  (:predicates (dishes-done) (laundry-done) (ironing-done))
+  (:durative-action wash-dishes
+    :parameters ()
+    :duration (= ?duration 2)
+    :condition (and)
+    :effect (at end (dishes-done)))
+  (:durative-action do-laundry
+    :parameters ()
+    :duration (= ?duration 5)
+    :condition (and)
+    :effect (at end (laundry-done)))
+  (:durative-action iron
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (at start (and (dishes-done) (laundry-done)))
+    :effect (at end (ironing-done)))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem chores-problem)
+  (:domain chores)
+  (:init)
+  (:goal (ironing-done))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let mut cached = TemporalAStarSearch::new().with_heuristic_cache(true);
+    let SearchResult::Solution(plan) = cached.search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(plan.cost, 8.0);
+    assert!(cached.stats().heuristic_cache_hits > 0);
+    assert!(cached.stats().heuristic_cache_misses > 0);
+
+    let mut uncached = TemporalAStarSearch::new();
+    uncached.search(&task);
+    assert_eq!(uncached.stats().heuristic_cache_hits, 0);
+    assert_eq!(uncached.stats().heuristic_cache_misses, 0, "The cache is off by default");
+}
+
+#[test]
+fn test_search_with_admissible_heuristic() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    let mut search_engine = TemporalAStarSearch::new().with_heuristic(Box::new(TemporalAdmissibleHeuristic::new()));
+    let SearchResult::Solution(plan) = search_engine.search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    let SearchResult::Solution(default_plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(plan.cost, default_plan.cost, "Both heuristics are admissible, so both plans are optimal");
+}
+
+#[test]
+fn test_snapped_plan_still_validates() {
+    let domain_content = r#"
+(define (domain chores)
+  (:requirements :durative-actions)
+  (:predicates (dishes-done) (laundry-done))
+  (:durative-action wash-dishes
+    :parameters ()
+    :duration (= ?duration 1.3)
+    :condition (and)
+    :effect (at end (dishes-done)))
+  (:durative-action do-laundry
+    :parameters ()
+    :duration (= ?duration 2.25)
+    :condition (at start (dishes-done))
+    :effect (at end (laundry-done)))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem chores-problem)
+  (:domain chores)
+  (:init)
+  (:goal (laundry-done))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().with_separation(0.0).search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(plan.start_times, vec![0.0, 1.3]);
+    let snapped = plan.snap_times(0.5).expect("Snapping should keep the steps in order");
+    for time in &snapped.start_times {
+        assert_eq!((time / 0.5).fract(), 0.0, "{} is not a multiple of 0.5", time);
+    }
+    assert!(snapped.schedule_equals(&plan, 0.25), "Each time moves by at most half the grid");
+
+    // Replay the steps in snapped-time order; each must be applicable and the goal must hold
+    let state_space = StateSpace::new(task.clone());
+    let mut steps: Vec<(f64, usize)> = snapped.start_times.iter().copied().zip(snapped.actions.iter().copied()).collect();
+    steps.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut state = TemporalState {
+        classical_state: task.initial_state.clone(),
+        scheduled_effects: Vec::new(),
+        scheduled_numeric_effects: Vec::new(),
+        invariants: Vec::new(),
+        time: 0.0,
+    };
+    for (_, action_idx) in steps {
+        state = state_space.apply_action_to_completion(&state, action_idx)
+            .expect("Snapped plan steps should be executable");
+    }
+    assert!(state_space.is_goal(&state));
+}
+
+#[test]
+fn test_solution_depth_matches_plan_length() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    let mut search_engine = TemporalAStarSearch::new();
+    let SearchResult::Solution(plan) = search_engine.search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(search_engine.stats().solution_depth, Some(plan.actions.len()));
+
+    let unreachable = TemporalTask::from_pddl(&domain_content, &problem_content.replace("(delivered package2)", "(delivered robot1)"));
+    search_engine.search(&unreachable);
+    assert_eq!(search_engine.stats().solution_depth, None);
+}
+
+#[test]
+fn test_hmax_is_a_lower_bound() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let heuristic = TemporalAdmissibleHeuristic::new();
+
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    let state_space = StateSpace::new(task.clone());
+    let mut state = TemporalState {
+        classical_state: task.initial_state.clone(),
+        scheduled_effects: Vec::new(),
+        scheduled_numeric_effects: Vec::new(),
+        invariants: Vec::new(),
+        time: 0.0,
+    };
+
+    // Picking up a package (1) then delivering it (2) is the longest relaxed chain
+    assert_eq!(heuristic.compute(&state, &task), 3.0);
+    for &action_idx in &plan.actions {
+        let h = heuristic.compute(&state, &task);
+        assert!(h > 0.0 && h <= plan.cost - state.time, "h = {} at time {}", h, state.time);
+        state = state_space.apply_action_to_completion(&state, action_idx)
+            .expect("Plan steps should be executable");
+    }
+    assert_eq!(heuristic.compute(&state, &task), 0.0, "h is zero at the goal");
+}
+
+#[test]
+fn test_durative_action_accepts_precondition_keyword() {
+    let domain_content = r#"
+(define (domain test-temporal)
+  (:requirements :strips :durative-actions)
+  (:predicates (p) (q) (r))
+  (:durative-action test-action
+    :parameters ()
+    :duration (= ?duration 2.0)
+    :precondition (and (at start (p))
+                       (over all (q)))
+    :effect (at end (r)))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem test-problem)
+  (:domain test-temporal)
+  (:init (p) (q))
+  (:goal (r))
+)
+"#;
+
+    let task = TemporalTask::try_from_pddl(domain_content, problem_content)
+        .expect("A durative :precondition should parse");
+    let action = &task.actions[0];
+    assert_eq!(action.conditions_start.len(), 1);
+    assert_eq!(action.conditions_start[0].predicate, "p");
+    assert_eq!(action.conditions_over_all.len(), 1);
+    assert_eq!(action.conditions_over_all[0].predicate, "q");
+    assert!(matches!(TemporalAStarSearch::new().search(&task), SearchResult::Solution(_)));
+}
+
+#[test]
+fn test_heuristics_keep_the_relaxation_they_were_prepared_with() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let mut task = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let state = TemporalState {
+        classical_state: task.initial_state.clone(),
+        scheduled_effects: Vec::new(),
+        scheduled_numeric_effects: Vec::new(),
+        invariants: Vec::new(),
+        time: 0.0,
+    };
+
+    let heuristic = TemporalAdmissibleHeuristic::new();
+    assert_eq!(heuristic.compute(&state, &task), 3.0, "Unprepared, the relaxation is built per call");
+    heuristic.prepare(&task);
+    assert_eq!(heuristic.compute(&state, &task), 3.0, "Same estimate from the prepared relaxation");
+
+    // Changing an action in place, without changing how many there are, is seen once prepared again
+    for action in task.ground_actions.iter_mut().filter(|action| action.name.contains("package2")) {
+        action.effects_end.clear();
+    }
+    heuristic.prepare(&task);
+    assert_eq!(heuristic.compute(&state, &task), f64::INFINITY);
+    assert_eq!(TemporalFFHeuristic::new().compute(&state, &task), f64::INFINITY);
+
+    // Each search prepares its heuristic for the task it is given
+    let shared = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let mut search_engine = TemporalAStarSearch::new().with_heuristic(Box::new(TemporalAdmissibleHeuristic::new()));
+    assert!(matches!(search_engine.search(&shared), SearchResult::Solution(_)));
+    assert!(!matches!(search_engine.search(&task), SearchResult::Solution(_)));
+}
+
+#[test]
+fn test_hadd_dominates_hmax() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let state = TemporalState {
+        classical_state: task.initial_state.clone(),
+        scheduled_effects: Vec::new(),
+        scheduled_numeric_effects: Vec::new(),
+        invariants: Vec::new(),
+        time: 0.0,
+    };
+
+    let h_max = TemporalAdmissibleHeuristic::new().compute(&state, &task);
+    let h_add = TemporalAddHeuristic::new().compute(&state, &task);
+    // Each package needs a pick-up (1) and a delivery (2); h^add counts both packages
+    assert_eq!(h_max, 3.0);
+    assert_eq!(h_add, 6.0);
+    assert!(h_add >= h_max);
+
+    let mut search_engine = TemporalGreedySearch::new().with_heuristic(Box::new(TemporalAddHeuristic::new()));
+    assert!(matches!(search_engine.search(&task), SearchResult::Solution(_)));
+}
+
+#[test]
+fn test_action_count_cost_model_ignores_durations() {
+    // Flying takes one long action, walking two short ones
+    let domain_content = r#"
+(define (domain travel)
+  (:requirements :durative-actions)
+  (:predicates (at-home) (at-station) (at-office))
+  (:durative-action fly
+    :parameters ()
+    :duration (= ?duration 10)
+    :condition (at start (at-home))
+    :effect (and (at start (not (at-home))) (at end (at-office))))
+  (:durative-action walk-to-station
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (at start (at-home))
+    :effect (and (at start (not (at-home))) (at end (at-station))))
+  (:durative-action walk-to-office
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (at start (at-station))
+    :effect (and (at start (not (at-station))) (at end (at-office))))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem travel-problem)
+  (:domain travel)
+  (:init (at-home))
+  (:goal (at-office))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let action_names = |plan: &Plan| -> Vec<String> {
+        plan.actions.iter().map(|&idx| task.ground_actions[idx].name.clone()).collect()
+    };
+
+    let SearchResult::Solution(fastest) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(action_names(&fastest), vec!["walk-to-station", "walk-to-office"]);
+    assert_eq!(fastest.cost, 2.0);
+
+    let mut by_count = TemporalAStarSearch::new().with_cost_model(CostModel::ActionCount);
+    let SearchResult::Solution(fewest) = by_count.search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(action_names(&fewest), vec!["fly"]);
+    assert_eq!(fewest.cost, 1.0, "Every action costs the default of 1");
+
+    let mut by_count = TemporalAStarSearch::new()
+        .with_cost_model(CostModel::ActionCount)
+        .with_default_action_cost(3.0);
+    let SearchResult::Solution(plan) = by_count.search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(plan.cost, 3.0);
+}
+
+#[test]
+fn test_ff_heuristic_shrinks_towards_the_goal() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let heuristic = TemporalFFHeuristic::new();
+
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    let state_space = StateSpace::new(task.clone());
+    let mut state = TemporalState {
+        classical_state: task.initial_state.clone(),
+        scheduled_effects: Vec::new(),
+        scheduled_numeric_effects: Vec::new(),
+        invariants: Vec::new(),
+        time: 0.0,
+    };
+
+    // Relaxed plan: pick up both packages (1 each) and deliver both (2 each)
+    let mut estimates = vec![heuristic.compute(&state, &task)];
+    for &action_idx in &plan.actions {
+        state = state_space.apply_action_to_completion(&state, action_idx)
+            .expect("Plan steps should be executable");
+        estimates.push(heuristic.compute(&state, &task));
+    }
+    assert_eq!(estimates[0], 6.0);
+    assert_eq!(estimates.last(), Some(&0.0), "h is zero at the goal");
+    assert!(
+        estimates.windows(2).all(|pair| pair[0] > pair[1]),
+        "Each step of the plan should bring the estimate down: {:?}",
+        estimates
+    );
+}
+
+#[test]
+fn test_goal_count_heuristic() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let heuristic = GoalCountHeuristic::new();
+
+    let mut state = TemporalState {
+        classical_state: task.initial_state.clone(),
+        scheduled_effects: Vec::new(),
+        scheduled_numeric_effects: Vec::new(),
+        invariants: Vec::new(),
+        time: 0.0,
+    };
+    assert_eq!(heuristic.compute(&state, &task), task.goal_conditions.len() as f64);
+
+    let mut search_engine = TemporalAStarSearch::new().with_heuristic(Box::new(GoalCountHeuristic::new()));
+    let SearchResult::Solution(plan) = search_engine.search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    let state_space = StateSpace::new(task.clone());
+    for &action_idx in &plan.actions {
+        state = state_space.apply_action_to_completion(&state, action_idx)
+            .expect("Plan steps should be executable");
+    }
+    assert_eq!(heuristic.compute(&state, &task), 0.0);
+}
+
+#[test]
+fn test_static_conditions_are_simplified_away() {
+    let domain_content = r#"
+(define (domain roads)
+  (:requirements :strips :typing :durative-actions)
+  (:types place)
+  (:predicates (at ?p - place) (connected ?from ?to - place))
+  (:durative-action drive
+    :parameters (?from ?to - place)
+    :duration (= ?duration 3)
+    :condition (and (at start (at ?from)) (over all (connected ?from ?to)))
+    :effect (and (at start (not (at ?from))) (at end (at ?to))))
+)
+"#;
+    let problem_content = r#"
+(define (problem one-way)
+  (:domain roads)
+  (:objects a b - place)
+  (:init (at a) (connected a b))
+  (:goal (at b))
+)
+"#;
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+
+    let drive = task.ground_actions.iter()
+        .find(|action| action.name == "drive a b")
+        .expect("The connected road should stay drivable");
+    assert!(drive.conditions_over_all.is_empty(), "(connected a b) always holds");
+    assert_eq!(drive.conditions_start.len(), 1, "(at a) can change");
+    assert!(
+        task.ground_actions.iter().all(|action| action.name != "drive b a"),
+        "(connected b a) never holds"
+    );
+
+    let mut search_engine = TemporalAStarSearch::new();
+    let SearchResult::Solution(plan) = search_engine.search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(plan.cost, 3.0);
+}
+
+#[test]
+fn test_contradictory_effects_resolve_delete_before_add() {
+    let domain = r#"
+(define (domain toggles)
+  (:requirements :strips :durative-actions)
+  (:predicates (on) (done))
+  (:durative-action flicker
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (at start (on))
+    :effect (and (at end (not (on))) (at end (on)) (at end (done))))
+)
+"#;
+    let problem = "(define (problem p) (:domain toggles) (:init (on)) (:goal (done)))";
+
+    let task = TemporalTask::from_pddl(domain, problem);
+    assert_eq!(task.actions[0].contradictory_effect(), Some("on".to_string()));
+    let state_space = StateSpace::new(task.clone());
+    let initial = TemporalState {
+        classical_state: task.initial_state.clone(),
+        scheduled_effects: Vec::new(),
+        scheduled_numeric_effects: Vec::new(),
+        invariants: Vec::new(),
+        time: 0.0,
+    };
+    let finished = state_space.apply_action_to_completion(&initial, 0)
+        .expect("flicker should run to completion");
+    let on = task.fact_id("on", &[]).unwrap();
+    assert!(finished.classical_state.facts[on], "The add wins over the delete");
+
+    match TemporalTask::try_from_pddl(domain, problem).unwrap_err() {
+        PddlParseError::ContradictoryEffects { action, atom } => {
+            assert_eq!((action.as_str(), atom.as_str()), ("flicker", "on"));
+        }
+        other => panic!("Expected a contradictory-effects error, got {}", other),
+    }
+}
+
+#[test]
+fn test_every_engine_kind_solves_a_trivial_problem() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let config = PlannerConfig::default();
+
+    for kind in EngineKind::ALL {
+        assert_eq!(kind.name().parse::<EngineKind>(), Ok(kind));
+        let mut engine = create_engine(kind, &config);
+        assert!(
+            matches!(engine.search(&task), SearchResult::Solution(_)),
+            "{} should solve the delivery problem",
+            engine.algorithm_name()
+        );
+    }
+    assert!("dijkstra".parse::<EngineKind>().is_err());
+
+    let planner = TemporalPlanner::new().with_engine_kind(EngineKind::Greedy, &config);
+    assert_eq!(planner.get_info().search_algorithm, "Temporal greedy best-first");
+}
+
+#[test]
+fn test_builder_configures_the_planner() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    assert_eq!(TemporalPlanner::builder().build().get_info().search_algorithm, "Temporal A*");
+
+    let mut planner = TemporalPlanner::builder()
+        .heuristic(Box::new(TemporalAdmissibleHeuristic::new()))
+        .build();
+    assert_eq!(planner.get_info().search_algorithm, "Temporal A* (h^max)");
+    let SearchResult::Solution(plan) = planner.solve(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(plan.cost, 6.0);
+
+    let weighted = TemporalPlanner::builder()
+        .weight(2.0)
+        .heuristic(Box::new(GoalCountHeuristic::new()))
+        .build();
+    assert_eq!(weighted.get_info().search_algorithm, "Temporal A* (w = 2, goal count)");
+
+    let greedy = TemporalPlanner::builder()
+        .engine(EngineKind::Greedy)
+        .heuristic(Box::new(TemporalAddHeuristic::new()))
+        .build();
+    assert_eq!(greedy.get_info().search_algorithm, "Temporal greedy best-first (h^add)");
+
+    let mut limited = TemporalPlanner::builder()
+        .max_expansions(1)
+        .max_time(Duration::from_secs(60))
+        .build();
+    assert!(matches!(limited.solve(&task), SearchResult::ResourceLimit(_)));
+}
+
+#[test]
+fn test_check_support_flags_unsupported_requirements() {
+    let planner = TemporalPlanner::new();
+
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let report = planner.check_support(&task);
+    assert_eq!(report.requirements, vec![":durative-actions", ":strips", ":typing"]);
+    assert!(report.is_supported());
+
+    let domain = r#"(define (domain timed)
+      (:requirements :strips :fluents :continuous-effects)
+      (:predicates (open))
+      (:functions (level))
+      (:action wait :parameters () :precondition (not (open)) :effect (open)))"#;
+    let problem = r#"(define (problem timed-problem) (:domain timed) (:init) (:goal (open)))"#;
+    let report = planner.check_support(&TemporalTask::from_pddl(domain, problem));
+    assert_eq!(report.unsupported, vec![":continuous-effects", ":fluents"]);
+    assert!(!report.is_supported());
+    assert!(report.requirements.contains(&":negative-preconditions".to_string()), "Used without being declared");
+    assert!(report.requirements.contains(&":numeric-fluents".to_string()));
+
+    let info = planner.get_info();
+    assert_eq!(info.supports_numeric_fluents, SUPPORTED_REQUIREMENTS.contains(&":numeric-fluents"));
+    assert_eq!(info.supports_durative_actions, SUPPORTED_REQUIREMENTS.contains(&":durative-actions"));
+}
+
+#[test]
+fn test_anytime_solving_reports_improving_plans() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/factory_automation.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/factory_production.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    let mut planner = TemporalPlanner::new();
+    let mut reported = Vec::new();
+    let SearchResult::Solution(best) = planner.solve_anytime(&task, |plan| {
+        reported.push(plan.cost);
+        true
+    }) else {
+        panic!("Anytime solving should have found a plan");
+    };
+    assert!(!reported.is_empty(), "The callback fires for the first plan");
+    assert!(reported.windows(2).all(|pair| pair[1] < pair[0]), "Each reported plan improves on the last: {:?}", reported);
+    assert_eq!(best.cost, *reported.last().unwrap());
+
+    // Stopping at the first plan returns it
+    let mut calls = 0;
+    let SearchResult::Solution(first) = planner.solve_anytime(&task, |_| {
+        calls += 1;
+        false
+    }) else {
+        panic!("Anytime solving should have found a plan");
+    };
+    assert_eq!(calls, 1);
+    assert_eq!(first.cost, reported[0]);
+
+    let mut greedy = TemporalPlanner::builder().engine(EngineKind::Greedy).build();
+    let mut greedy_calls = 0;
+    assert!(matches!(greedy.solve_anytime(&task, |_| { greedy_calls += 1; true }), SearchResult::Solution(_)));
+    assert_eq!(greedy_calls, 1);
+}
+
+#[test]
+fn test_cancellation_token_stops_the_search() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/blocks_world.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/stack_blocks.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let mut search = TemporalAStarSearch::new().with_cancellation(token);
+    assert!(matches!(search.search(&task), SearchResult::Cancelled));
+    assert_eq!(search.stats().nodes_expanded, 0);
+
+    // Cancelled from the first progress report, the search stops before its next expansion
+    let token = CancellationToken::new();
+    let canceller = token.clone();
+    let mut search = TemporalAStarSearch::new()
+        .with_cancellation(token)
+        .with_progress_callback(move |_| canceller.cancel());
+    assert!(matches!(search.search(&task), SearchResult::Cancelled));
+
+    let mut planner = TemporalPlanner::builder().cancellation(CancellationToken::new()).build();
+    assert!(matches!(planner.solve(&task), SearchResult::Solution(_)));
+}
+
+#[test]
+fn test_timed_initial_literal_delays_the_goal() {
+    let domain_content = r#"
+(define (domain doorway)
+  (:requirements :durative-actions :timed-initial-literals)
+  (:predicates (door-open) (inside))
+  (:durative-action enter
+    :parameters ()
+    :duration (= ?duration 2)
+    :condition (at start (door-open))
+    :effect (at end (inside)))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem doorway-problem)
+  (:domain doorway)
+  (:init (at 10 (door-open)) (at 30 (not (door-open))))
+  (:goal (inside))
+)
+"#;
+
+    let task = TemporalTask::try_from_pddl(domain_content, problem_content).expect("Timed literals should parse");
+    assert!(task.fact_id("at", &["10".to_string()]).is_none(), "A timed literal is not an `at` atom");
+    assert_eq!(task.timed_literals.len(), 2);
+    assert_eq!(task.timed_literals[0].time, 10.0);
+    assert_eq!(task.timed_literals[0].effect.key(), "door-open");
+    assert!(task.timed_literals[1].effect.is_delete);
+    assert!(task.used_requirements().contains(&":timed-initial-literals".to_string()));
+
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("The goal should be reachable once the door opens");
+    };
+    assert_eq!(plan.actions.len(), 1);
+    assert_eq!(plan.start_times[0], 10.0, "Entering has to wait for the door");
+    assert_eq!(plan.makespan(), 12.0);
+
+    let result = TemporalAStarSearch::new().with_max_makespan(11.0).search(&task);
+    assert!(matches!(result, SearchResult::Unsolvable(_)), "Nothing reaches the goal before the door opens");
+}
+
+#[test]
+fn test_metric_selects_the_cost_model() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let with_metric = |metric: &str| {
+        let problem = problem_content.trim_end().strip_suffix(')').expect("The problem ends its define");
+        TemporalTask::from_pddl(&domain_content, &format!("{}\n  (:metric {}))", problem, metric))
+    };
+
+    let task = with_metric("minimize (total-time)");
+    let metric = task.metric.as_ref().expect("The metric should be parsed");
+    assert_eq!(metric.direction, MetricDirection::Minimize);
+    assert!(metric.is_total_time());
+    assert_eq!(CostModel::TaskMetric.resolve(&task), CostModel::Makespan);
+
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new()
+        .with_heuristic(Box::new(TemporalAdmissibleHeuristic::new()))
+        .search(&task)
+    else {
+        panic!("Planning should have found a solution");
+    };
+    assert!(plan.optimality.sequential_makespan_optimal, "total-time is optimized as the makespan");
+    assert!(!plan.optimality.cost_optimal);
+    assert_eq!(plan.cost, 6.0);
+
+    assert_eq!(CostModel::TaskMetric.resolve(&with_metric("minimize (total-cost)")), CostModel::ActionCount);
+    let task = with_metric("maximize (- 0 (total-time))");
+    assert_eq!(task.metric.as_ref().map(|metric| metric.direction), Some(MetricDirection::Maximize));
+    assert_eq!(CostModel::TaskMetric.resolve(&task), CostModel::MetricValue);
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(plan.cost, plan.sequential_cost, "Maximizing minus the time minimizes the time");
+}
+
+#[test]
+fn test_total_cost_metric_prefers_the_cheaper_route() {
+    let domain_content = r#"
+(define (domain toll-roads)
+  (:requirements :durative-actions :numeric-fluents :action-costs)
+  (:predicates (at ?l) (road ?from ?to))
+  (:functions (toll ?from ?to) (travel-time ?from ?to) (total-cost))
+  (:durative-action drive
+    :parameters (?from ?to)
+    :duration (= ?duration (travel-time ?from ?to))
+    :condition (and (at start (at ?from)) (at start (road ?from ?to)))
+    :effect (and (at start (not (at ?from)))
+                 (at end (at ?to))
+                 (at end (increase (total-cost) (toll ?from ?to)))))
+)
+"#;
+    let problem = |metric: &str| format!(r#"
+(define (problem commute)
+  (:domain toll-roads)
+  (:objects home town office)
+  (:init (at home)
+         (road home office) (= (toll home office) 10) (= (travel-time home office) 1)
+         (road home town) (= (toll home town) 2) (= (travel-time home town) 3)
+         (road town office) (= (toll town office) 2) (= (travel-time town office) 3))
+  (:goal (at office))
+  (:metric {})
+)
+"#, metric);
+
+    let task = TemporalTask::from_pddl(domain_content, &problem("minimize (total-cost)"));
+    let SearchResult::Solution(cheapest) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    let names: Vec<&str> = cheapest.actions.iter().map(|&idx| task.ground_actions[idx].name.as_str()).collect();
+    assert_eq!(names, vec!["drive home town", "drive town office"], "Two cheap tolls beat one dear one");
+    assert_eq!(cheapest.cost, 4.0, "The tolls are read from the fluents");
+
+    let task = TemporalTask::from_pddl(domain_content, &problem("minimize (total-time)"));
+    let SearchResult::Solution(fastest) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(fastest.actions.len(), 1, "The toll road is quicker");
+    assert_eq!(fastest.cost, 1.0);
+}
+
+#[test]
+fn test_negated_conditions_use_the_closed_world() {
+    let domain_content = r#"
+(define (domain switches)
+  (:requirements :strips :negative-preconditions)
+  (:predicates (p) (q))
+  (:action clear-p :parameters () :precondition (p) :effect (not (p)))
+  (:action set-q :parameters () :precondition (not (ghost)) :effect (q))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem switches-problem)
+  (:domain switches)
+  (:init (p))
+  (:goal (and (q) (not (p)) (not (ghost))))
+)
+"#;
+
+    // `ghost` is never declared; the lenient parser keeps it as an atom that never holds
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let state_space = StateSpace::new(task.clone());
+    let initial = state_space.initial_state();
+    let not_p = task.goal_conditions.iter().find(|condition| condition.predicate == "p").unwrap();
+    assert!(not_p.is_negative);
+    assert!(!state_space.check_condition(not_p, &initial.classical_state), "p holds initially");
+    let not_ghost = task.goal_conditions.iter().find(|condition| condition.predicate == "ghost").unwrap();
+    assert!(state_space.check_condition(not_ghost, &initial.classical_state), "An unset atom is false");
+    let unknown = Condition { predicate: "nowhere".to_string(), args: vec!["x".to_string()], is_negative: true };
+    assert!(state_space.check_condition(&unknown, &initial.classical_state), "Atoms outside the fact table are false");
+    assert_eq!(task.satisfied_goal_count(&initial.classical_state), 1);
+
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    let mut names: Vec<&str> = plan.actions.iter().map(|&idx| task.ground_actions[idx].name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, vec!["clear-p", "set-q"]);
+}
+
+#[test]
+fn test_equality_preconditions_compare_bound_objects() {
+    let domain_content = r#"
+(define (domain handover)
+  (:requirements :strips :typing :equality :negative-preconditions)
+  (:types agent)
+  (:predicates (holding ?a - agent) (done))
+  (:action pass
+    :parameters (?a ?b - agent)
+    :precondition (and (holding ?a) (not (= ?a ?b)))
+    :effect (and (not (holding ?a)) (holding ?b)))
+  (:action finish
+    :parameters (?a - agent)
+    :precondition (and (holding ?a) (= ?a bob))
+    :effect (done))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem handover-problem)
+  (:domain handover)
+  (:objects alice bob - agent)
+  (:init (holding alice))
+  (:goal (done))
+)
+"#;
+
+    let task = TemporalTask::try_from_pddl(domain_content, problem_content).unwrap();
+    assert!(TemporalPlanner::new().check_support(&task).is_supported());
+    let state_space = StateSpace::new(task.clone());
+    let initial = state_space.initial_state();
+    let equal = |left: &str, right: &str, is_negative| Condition {
+        predicate: "=".to_string(),
+        args: vec![left.to_string(), right.to_string()],
+        is_negative,
+    };
+    assert!(state_space.check_condition(&equal("alice", "alice", false), &initial.classical_state));
+    assert!(!state_space.check_condition(&equal("alice", "bob", false), &initial.classical_state));
+    assert!(!state_space.check_condition(&equal("alice", "alice", true), &initial.classical_state));
+    assert!(state_space.check_condition(&equal("alice", "bob", true), &initial.classical_state));
+    assert!(task.fact_names.iter().all(|name| !name.starts_with('=')), "Equality is never a fact");
+
+    // Binding both parameters to the same agent violates (not (= ?a ?b))
+    let names: Vec<&str> = task.ground_actions.iter().map(|action| action.name.as_str()).collect();
+    assert!(names.contains(&"pass alice bob"));
+    assert!(!names.contains(&"pass alice alice"));
+    assert!(!names.contains(&"finish alice"));
+
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    let names: Vec<&str> = plan.actions.iter().map(|&idx| task.ground_actions[idx].name.as_str()).collect();
+    assert_eq!(names, vec!["pass alice bob", "finish bob"]);
+}
+
+#[test]
+fn test_derived_predicates_with_disjunction_and_exists() {
+    let domain_content = r#"
+(define (domain towers)
+  (:requirements :strips :typing :derived-predicates :negative-preconditions :disjunctive-preconditions)
+  (:types block)
+  (:predicates (on ?x ?y - block) (clear ?x - block) (ontable ?x - block) (above ?x ?z - block))
+  (:derived (above ?x ?z - block)
+    (or (on ?x ?z)
+        (exists (?y - block) (and (on ?x ?y) (above ?y ?z)))))
+  (:action unstack
+    :parameters (?x ?y - block)
+    :precondition (and (on ?x ?y) (clear ?x))
+    :effect (and (not (on ?x ?y)) (clear ?y) (ontable ?x)))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem towers-problem)
+  (:domain towers)
+  (:objects a b c - block)
+  (:init (on a b) (on b c) (ontable c) (clear a))
+  (:goal (not (above a c)))
+)
+"#;
+
+    let task = TemporalTask::try_from_pddl(domain_content, problem_content).unwrap();
+    assert_eq!(task.axioms.iter().filter(|axiom| axiom.args == ["a", "c"]).count(), 4, "One rule for on, one per ?y");
+
+    let holds = |atom: &str| task.fact_ids.get(atom).is_some_and(|&id| task.initial_state.facts[id]);
+    assert!(holds("above a b"));
+    assert!(holds("above b c"));
+    assert!(holds("above a c"), "above is transitive through b");
+    assert!(!holds("above c a"));
+    assert!(!holds("above a a"));
+
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    let names: Vec<&str> = plan.actions.iter().map(|&idx| task.ground_actions[idx].name.as_str()).collect();
+    assert_eq!(names, vec!["unstack a b"]);
+}
+
+#[test]
+fn test_typed_derived_heads_declare_their_arity() {
+    // `safe` is declared only by its rule head, whose `- robot` is a type, not an argument
+    let domain_content = r#"
+(define (domain patrol)
+  (:requirements :strips :typing :derived-predicates)
+  (:types robot)
+  (:predicates (charged ?r - robot) (done ?r - robot))
+  (:derived (safe ?r - robot) (charged ?r))
+  (:action finish
+    :parameters (?r - robot)
+    :precondition (safe ?r)
+    :effect (done ?r))
+)
+"#;
+    let problem_content = r#"
+(define (problem patrol-problem)
+  (:domain patrol)
+  (:objects r1 - robot)
+  (:init (charged r1))
+  (:goal (done r1))
+)
+"#;
+
+    let task = TemporalTask::try_from_pddl(domain_content, problem_content).unwrap();
+    assert!(matches!(TemporalAStarSearch::new().search(&task), SearchResult::Solution(_)));
+    let wrong_arity = domain_content.replace(":precondition (safe ?r)", ":precondition (safe ?r ?r)");
+    assert!(matches!(
+        TemporalTask::try_from_pddl(&wrong_arity, problem_content),
+        Err(PddlParseError::ArityMismatch { expected: 1, found: 2, .. })
+    ));
+}
+
+#[test]
+fn test_preprocess_prunes_unreachable_actions() {
+    let domain_content = r#"
+(define (domain wizard)
+  (:requirements :strips :durative-actions)
+  (:predicates (at-home) (at-tower) (has-wand) (wand-charged) (teleported))
+  (:durative-action walk
+    :parameters ()
+    :duration (= ?duration 5)
+    :condition (at start (at-home))
+    :effect (and (at start (not (at-home))) (at end (at-tower))))
+  (:durative-action teleport
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (and (at start (at-home)) (at start (has-wand)))
+    :effect (and (at start (not (at-home))) (at end (at-tower)) (at end (teleported))))
+  (:durative-action charge-wand
+    :parameters ()
+    :duration (= ?duration 2)
+    :condition (at start (wand-charged))
+    :effect (and (at end (has-wand)) (at end (wand-charged))))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem wizard-problem)
+  (:domain wizard)
+  (:init (at-home))
+  (:goal (at-tower))
+)
+"#;
+
+    let original = TemporalTask::from_pddl(domain_content, problem_content);
+    let mut task = original.clone();
+    // has-wand is not static, since charge-wand adds it, but charge-wand needs a charge only it provides
+    assert_eq!(task.preprocess(), 2, "Neither charge-wand nor teleport can ever start");
+    let names: Vec<&str> = task.ground_actions.iter().map(|action| action.name.as_str()).collect();
+    assert_eq!(names, vec!["walk"]);
+    assert_eq!(task.preprocess(), 0, "Preprocessing is idempotent");
+
+    let solve = |task: &TemporalTask| {
+        let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(task) else {
+            panic!("Planning should have found a solution");
+        };
+        let names: Vec<String> = plan.actions.iter().map(|&idx| task.ground_actions[idx].name.clone()).collect();
+        (names, plan.makespan())
+    };
+    assert_eq!(solve(&task), solve(&original));
+}
+
+#[test]
+fn test_relevance_analysis_prunes_distractor_actions() {
+    let domain_content = r#"
+(define (domain courier)
+  (:requirements :strips :typing :action-costs)
+  (:types place)
+  (:predicates (at ?p - place) (road ?from ?to - place) (waved ?p - place) (parcel-at ?p - place))
+  (:functions (total-cost))
+  (:action drive
+    :parameters (?from ?to - place)
+    :precondition (and (at ?from) (road ?from ?to))
+    :effect (and (not (at ?from)) (at ?to) (increase (total-cost) 1)))
+  (:action wave
+    :parameters (?p - place)
+    :precondition (at ?p)
+    :effect (and (waved ?p) (increase (total-cost) 1)))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem courier-problem)
+  (:domain courier)
+  (:objects depot shop - place)
+  (:init (at depot) (road depot shop) (road shop depot) (= (total-cost) 0))
+  (:goal (at shop))
+  (:metric minimize (total-cost))
+)
+"#;
+
+    let original = TemporalTask::from_pddl(domain_content, problem_content);
+    let mut task = original.clone();
+    assert_eq!(task.preprocess(), 0, "Every action is reachable");
+    let relevant = task.relevant_actions();
+    let irrelevant: Vec<&str> = task.ground_actions.iter()
+        .zip(&relevant)
+        .filter(|(_, &relevant)| !relevant)
+        .map(|(action, _)| action.name.as_str())
+        .collect();
+    assert_eq!(irrelevant, vec!["wave depot", "wave shop"], "waved is never read, and minimized cost does not count");
+
+    assert_eq!(task.preprocess_with(PreprocessOptions { relevance: true }), 2);
+    assert!(task.ground_actions.iter().all(|action| action.name.starts_with("drive")));
+    assert_eq!(task.preprocess_with(PreprocessOptions { relevance: true }), 0, "Preprocessing is idempotent");
+
+    let solve = |task: &TemporalTask| {
+        let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(task) else {
+            panic!("Planning should have found a solution");
+        };
+        plan.actions.iter().map(|&idx| task.ground_actions[idx].name.clone()).collect::<Vec<_>>()
+    };
+    assert_eq!(solve(&task), vec!["drive depot shop"]);
+    assert_eq!(solve(&task), solve(&original));
+}
+
+#[test]
+fn test_unreachable_goal_is_reported_unsolvable_without_search() {
+    let domain_content = r#"
+(define (domain vault)
+  (:requirements :strips)
+  (:predicates (in-lobby) (in-hall) (has-key) (vault-open))
+  (:action walk :parameters () :precondition (in-lobby) :effect (and (not (in-lobby)) (in-hall)))
+  (:action walk-back :parameters () :precondition (in-hall) :effect (and (not (in-hall)) (in-lobby)))
+  (:action open-vault :parameters () :precondition (and (in-hall) (has-key)) :effect (vault-open))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem vault-problem)
+  (:domain vault)
+  (:init (in-lobby))
+  (:goal (and (in-hall) (vault-open)))
+)
+"#;
+
+    // Nothing adds has-key, so vault-open is out of reach
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    assert_eq!(task.unreachable_goal().map(ToString::to_string), Some("(vault-open)".to_string()));
+
+    let mut search = TemporalAStarSearch::new();
+    match search.search(&task) {
+        SearchResult::Unsolvable(reason) => assert!(reason.contains("vault-open"), "{}", reason),
+        other => panic!("Expected Unsolvable, got {:?}", other),
+    }
+    assert_eq!(search.stats().nodes_expanded, 0);
+    assert!(matches!(TemporalGreedySearch::new().search(&task), SearchResult::Unsolvable(_)));
+}
+
+#[test]
+fn test_solve_all_optimal_enumerates_distinct_schedules() {
+    let domain_content = r#"
+(define (domain two-routes)
+  (:requirements :strips :typing :durative-actions)
+  (:types robot place)
+  (:predicates (at ?r - robot ?p - place) (link ?from ?to - place) (shortcut ?from ?to - place))
+  (:durative-action step
+    :parameters (?r - robot ?from ?to - place)
+    :duration (= ?duration 2)
+    :condition (and (at start (at ?r ?from)) (at start (link ?from ?to)))
+    :effect (and (at start (not (at ?r ?from))) (at end (at ?r ?to))))
+  (:durative-action hop
+    :parameters (?r - robot ?from ?to - place)
+    :duration (= ?duration 1)
+    :condition (and (at start (at ?r ?from)) (at start (shortcut ?from ?to)))
+    :effect (and (at start (not (at ?r ?from))) (at end (at ?r ?to))))
+  (:durative-action crawl
+    :parameters (?r - robot ?from ?to - place)
+    :duration (= ?duration 5)
+    :condition (and (at start (at ?r ?from)) (at start (shortcut ?from ?to)))
+    :effect (and (at start (not (at ?r ?from))) (at end (at ?r ?to))))
+)
+"#;
+
+    // r1 reaches d through b or through c in 4; r2 hops from x to y independently
+    let problem_content = r#"
+(define (problem two-routes-problem)
+  (:domain two-routes)
+  (:objects r1 r2 - robot a b c d x y - place)
+  (:init (at r1 a) (at r2 x)
+         (link a b) (link b d) (link a c) (link c d) (shortcut x y))
+  (:goal (and (at r1 d) (at r2 y)))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let engine = TemporalAStarSearch::new().with_heuristic(Box::new(TemporalAdmissibleHeuristic::new()));
+    let mut planner = TemporalPlanner::new().with_search_engine(engine);
+
+    let plans = planner.solve_all_optimal(&task, 10);
+    // Three orders of r2's hop per route, but the scheduled plans start it at 0 every time
+    assert_eq!(plans.len(), 2, "One plan per route");
+    for plan in &plans {
+        assert!((plan.cost - 5.0).abs() < 1e-6);
+        assert!(plan.optimality.sequential_makespan_optimal);
+        assert!(plan.actions.iter().all(|&idx| !task.ground_actions[idx].name.starts_with("crawl")));
+    }
+    let via = |plan: &Plan| plan.actions.iter().any(|&idx| task.ground_actions[idx].name == "step r1 a b");
+    assert_ne!(via(&plans[0]), via(&plans[1]));
+
+    assert_eq!(planner.solve_all_optimal(&task, 1).len(), 1);
+    assert!(planner.solve_all_optimal(&task, 0).is_empty());
+}
+
+#[test]
+fn test_solve_top_k_returns_alternative_routes_by_cost() {
+    let domain_content = r#"
+(define (domain routes)
+  (:requirements :strips :typing :durative-actions :numeric-fluents)
+  (:types place)
+  (:predicates (at ?p - place) (road ?from ?to - place))
+  (:functions (length ?from ?to - place))
+  (:durative-action drive
+    :parameters (?from ?to - place)
+    :duration (= ?duration (length ?from ?to))
+    :condition (and (at start (at ?from)) (at start (road ?from ?to)))
+    :effect (and (at start (not (at ?from))) (at end (at ?to))))
+)
+"#;
+
+    // a to d: via b in 4, via c in 5, directly in 6
+    let problem_content = r#"
+(define (problem routes-problem)
+  (:domain routes)
+  (:objects a b c d - place)
+  (:init (at a)
+         (road a b) (road b d) (road a c) (road c d) (road a d)
+         (= (length a b) 2) (= (length b d) 2)
+         (= (length a c) 2) (= (length c d) 3)
+         (= (length a d) 6))
+  (:goal (at d))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let engine = TemporalAStarSearch::new().with_heuristic(Box::new(TemporalAdmissibleHeuristic::new()));
+    let mut planner = TemporalPlanner::new().with_search_engine(engine);
+    let route = |plan: &Plan| -> Vec<String> {
+        plan.actions.iter().map(|&idx| task.ground_actions[idx].name.clone()).collect()
+    };
+
+    let plans = planner.solve_top_k(&task, 3);
+    let costs: Vec<f64> = plans.iter().map(|plan| plan.cost).collect();
+    assert_eq!(costs, vec![4.0, 5.0, 6.0]);
+    assert_eq!(route(&plans[0]), vec!["drive a b", "drive b d"]);
+    assert_eq!(route(&plans[1]), vec!["drive a c", "drive c d"]);
+    assert_eq!(route(&plans[2]), vec!["drive a d"]);
+    assert!(plans[0].optimality.sequential_makespan_optimal);
+    assert_eq!(plans[1].optimality, Optimality::default());
+
+    let diverse = planner.solve_top_k_diverse(&task, 5);
+    assert_eq!(diverse.len(), 3, "Only three routes lead to d");
+    for (i, plan) in diverse.iter().enumerate() {
+        assert!(diverse[..i].iter().all(|other| route(other) != route(plan)));
+    }
+    assert_eq!(planner.solve_top_k(&task, 2).len(), 2);
+}
+
+#[test]
+fn test_partial_order_plan_leaves_independent_steps_unordered() {
+    let domain_content = r#"
+(define (domain rovers)
+  (:requirements :strips :typing :durative-actions)
+  (:types rover place)
+  (:predicates (at ?r - rover ?p - place) (path ?from ?to - place))
+  (:durative-action move
+    :parameters (?r - rover ?from ?to - place)
+    :duration (= ?duration 3)
+    :condition (and (at start (at ?r ?from)) (at start (path ?from ?to)))
+    :effect (and (at start (not (at ?r ?from))) (at end (at ?r ?to))))
+)
+"#;
+
+    let problem_content = r#"
+(define (problem rovers-problem)
+  (:domain rovers)
+  (:objects r1 r2 - rover a b c x y - place)
+  (:init (at r1 a) (at r2 x) (path a b) (path b c) (path x y))
+  (:goal (and (at r1 c) (at r2 y)))
+)
+"#;
+
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    let order = plan.partial_order(&task);
+    assert_eq!(order.actions, plan.actions);
+    assert!(order.durations.iter().all(|duration| (duration - 3.0).abs() < 1e-9), "{:?}", order.durations);
+
+    let step = |name: &str| plan.actions.iter().position(|&idx| task.ground_actions[idx].name == name).unwrap();
+    let (first_leg, second_leg, other_rover) = (step("move r1 a b"), step("move r1 b c"), step("move r2 x y"));
+    assert_eq!(order.orderings, vec![(first_leg, second_leg)], "Only r1's legs depend on each other");
+    assert_eq!(order.predecessors(second_leg), vec![first_leg]);
+    assert!(order.is_unordered(other_rover, first_leg));
+    assert!(order.is_unordered(other_rover, second_leg));
+    assert!(!order.is_unordered(first_leg, second_leg));
+}
+
+/// Domain-specific guidance as a user crate would write it: a constant estimate that counts its calls
+struct ConstantHeuristic {
+    calls: Arc<AtomicUsize>,
+}
+
+impl TemporalHeuristic for ConstantHeuristic {
+    fn compute(&self, _state: &TemporalState, _task: &TemporalTask) -> f64 {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        1.0
+    }
+
+    fn name(&self) -> String {
+        "constant".to_string()
+    }
+}
+
+#[test]
+fn test_builder_accepts_a_heuristic_defined_outside_the_crate() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut planner = TemporalPlanner::builder()
+        .heuristic(Box::new(ConstantHeuristic { calls: Arc::clone(&calls) }))
+        .build();
+    assert_eq!(planner.get_info().search_algorithm, "Temporal A* (constant)");
+    let SearchResult::Solution(plan) = planner.solve(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert!(calls.load(Ordering::Relaxed) > 0, "The search consulted the custom heuristic");
+    assert_eq!(plan.optimality, Optimality::default(), "A heuristic is not assumed admissible unless it says so");
+    assert_eq!(plan.cost, 6.0);
+}
+
+#[test]
+fn test_summary_counts_the_factory_domain() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/factory_automation.pddl").unwrap();
+    let problem_content = fs::read_to_string("tests/fixtures/problems/factory_production.pddl").unwrap();
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    let summary = task.summary();
+    assert_eq!(summary.actions, 4);
+    assert_eq!(summary.durative_actions, 2, "process-ingredient and produce-product are durative");
+    assert_eq!(summary.ground_actions, 66);
+    assert_eq!(summary.predicates, 8);
+    assert_eq!(summary.functions, 2);
+    assert_eq!(summary.objects, 12);
+    assert_eq!(summary.facts, task.fact_names.len());
+    assert_eq!(summary.goal_conditions, 4);
+    assert_eq!(summary.numeric_goal_conditions, 0);
+
+    // A dry run parses strictly and reports the same counts without searching
+    let planner = TemporalPlanner::new();
+    assert_eq!(planner.dry_run(&domain_content, &problem_content).unwrap(), summary);
+    assert!(planner.dry_run(&domain_content, "(define (problem broken)").is_err());
+}
+
+#[test]
+fn test_negative_and_disjunctive_conditions_need_their_requirements() {
+    let domain = |requirements: &str| format!(r#"
+(define (domain lamp)
+  (:requirements :strips {requirements})
+  (:predicates (lit) (plugged) (battery))
+  (:action switch-on
+    :parameters ()
+    :precondition (and (not (lit)) (or (plugged) (battery)))
+    :effect (lit))
+)
+"#);
+    let problem = r#"
+(define (problem lamp-problem)
+  (:domain lamp)
+  (:init (battery))
+  (:goal (lit))
+)
+"#;
+
+    let task = TemporalTask::try_from_pddl(&domain(":negative-preconditions :disjunctive-preconditions"), problem)
+        .expect("Declared requirements allow the constructs");
+    // One variant of switch-on per disjunct, each also requiring the lamp to be off
+    assert_eq!(task.actions.len(), 2);
+    assert_eq!(task.summary().actions, 1);
+    for action in &task.actions {
+        assert_eq!(action.conditions_start.len(), 2);
+        assert!(action.conditions_start.iter().any(|condition| condition.predicate == "lit" && condition.is_negative));
+    }
+    // Only the battery holds, which is enough; reading the disjunction as a conjunction was not
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Expected switch-on to run on battery");
+    };
+    assert_eq!(plan.actions.len(), 1);
+    let unpowered = problem.replace("(:init (battery))", "(:init)");
+    let task = TemporalTask::try_from_pddl(&domain(":negative-preconditions :disjunctive-preconditions"), &unpowered).unwrap();
+    assert!(!matches!(TemporalAStarSearch::new().search(&task), SearchResult::Solution(_)), "Neither disjunct holds");
+    // Nor does switch-on apply to a lamp that is already lit
+    let lit = problem.replace("(:init (battery))", "(:init (battery) (lit))");
+    let task = TemporalTask::try_from_pddl(&domain(":negative-preconditions :disjunctive-preconditions"), &lit).unwrap();
+    let state_space = StateSpace::new(task.clone());
+    assert!(state_space.get_applicable_actions(&state_space.initial_state()).is_empty());
+    assert!(TemporalTask::try_from_pddl(&domain(":adl"), problem).is_ok(), ":adl implies both");
+
+    // A goal is a single conjunction, so a disjunctive one is rejected rather than misread
+    let disjunctive_goal = problem.replace("(:goal (lit))", "(:goal (or (lit) (plugged)))");
+    let error = TemporalTask::try_from_pddl(&domain(":negative-preconditions :disjunctive-preconditions"), &disjunctive_goal)
+        .unwrap_err();
+    assert!(matches!(
+        &error,
+        PddlParseError::UnsupportedConstruct { file: "problem", construct: "disjunction", .. }
+    ), "{}", error);
+    let lenient = TemporalTask::from_pddl(&domain(":negative-preconditions :disjunctive-preconditions"), &disjunctive_goal);
+    assert!(lenient.warnings.iter().any(|warning| warning.contains("disjunction")), "{:?}", lenient.warnings);
+    let implication = domain(":adl").replace("(or (plugged) (battery))", "(imply (plugged) (battery))");
+    assert!(matches!(
+        TemporalTask::try_from_pddl(&implication, problem),
+        Err(PddlParseError::UnsupportedConstruct { construct: "implication", .. })
+    ));
+
+    let error = TemporalTask::try_from_pddl(&domain(":disjunctive-preconditions"), problem).unwrap_err();
+    assert!(matches!(
+        &error,
+        PddlParseError::UndeclaredRequirement { requirement: ":negative-preconditions", line: 7, .. }
+    ), "{}", error);
+    let error = TemporalTask::try_from_pddl(&domain(":negative-preconditions"), problem).unwrap_err();
+    assert!(matches!(
+        &error,
+        PddlParseError::UndeclaredRequirement { requirement: ":disjunctive-preconditions", construct: "disjunction", .. }
+    ), "{}", error);
+    assert!(error.to_string().contains("action 'switch-on'"), "{}", error);
+
+    // Negative goals are preconditions of reaching the goal, so they need the requirement too
+    let negative_goal = problem.replace("(:goal (lit))", "(:goal (not (plugged)))");
+    assert!(TemporalTask::try_from_pddl(&domain(":disjunctive-preconditions :negative-preconditions"), &negative_goal).is_ok());
+    let strips = r#"
+(define (domain lamp)
+  (:requirements :strips)
+  (:predicates (lit) (plugged) (battery))
+  (:action switch-on :parameters () :precondition (plugged) :effect (lit))
+)
+"#;
+    assert!(matches!(
+        TemporalTask::try_from_pddl(strips, &negative_goal),
+        Err(PddlParseError::UndeclaredRequirement { file: "problem", .. })
+    ));
+    // The lenient parser still reads such domains
+    assert_eq!(TemporalTask::from_pddl(&domain(""), problem).actions.len(), 2);
+}
+
+#[test]
+fn test_negative_exact_durations_make_actions_inapplicable() {
+    let domain = r#"
+(define (domain backwards)
+  (:requirements :durative-actions :numeric-fluents)
+  (:predicates (done))
+  (:functions (a) (b))
+  (:durative-action rewind
+    :parameters ()
+    :duration (= ?duration (- (a) (b)))
+    :condition (at start (not (done)))
+    :effect (at end (done)))
+)
+"#;
+    let problem = |b: u32| format!(r#"
+(define (problem backwards-problem)
+  (:domain backwards)
+  (:init (= (a) 1) (= (b) {b}))
+  (:goal (done))
+)
+"#);
+
+    let task = TemporalTask::from_pddl(domain, &problem(5));
+    assert_eq!(task.actions[0].duration_spec.choices(&task.initial_state.numeric_values, DEFAULT_SEPARATION), Vec::<f64>::new());
+    assert!(!matches!(TemporalPlanner::new().solve(&task), SearchResult::Solution(_)), "A duration of -4 is not allowed");
+    let task = TemporalTask::from_pddl(domain, &problem(0));
+    assert!(matches!(TemporalPlanner::new().solve(&task), SearchResult::Solution(_)));
+
+    // A plan built by hand with a negative step is left as it is rather than scheduled
+    let backwards = Plan {
+        actions: vec![0],
+        start_times: vec![4.0],
+        end_times: vec![0.0],
+        cost: 0.0,
+        sequential_cost: 0.0,
+        optimality: Optimality::default(),
+    };
+    let scheduled = temporal_planner::scheduler::schedule_plan(&task, &backwards);
+    assert_eq!((scheduled.start_times, scheduled.end_times), (vec![4.0], vec![0.0]));
+}
+
+#[test]
+fn test_quantified_action_conditions_and_goals_are_rejected() {
+    let domain = |precondition: &str| format!(r#"
+(define (domain things)
+  (:requirements :typing :adl)
+  (:types thing)
+  (:predicates (ok ?x - thing) (gone))
+  (:action go
+    :parameters ()
+    :precondition {precondition}
+    :effect (gone))
+)
+"#);
+    let problem = r#"
+(define (problem things-problem)
+  (:domain things)
+  (:objects a b - thing)
+  (:init)
+  (:goal (gone))
+)
+"#;
+
+    // Neither is expanded in action conditions, so go would apply without any `ok` fact
+    let error = TemporalTask::try_from_pddl(&domain("(exists (?x - thing) (ok ?x))"), problem).unwrap_err();
+    assert!(matches!(
+        &error,
+        PddlParseError::UnsupportedConstruct { file: "domain", construct: "existential condition", .. }
+    ), "{}", error);
+    assert!(error.to_string().contains("action 'go'"), "{}", error);
+    let error = TemporalTask::try_from_pddl(&domain("(forall (?x - thing) (ok ?x))"), problem).unwrap_err();
+    assert!(matches!(
+        &error,
+        PddlParseError::UnsupportedConstruct { file: "domain", construct: "universal condition", .. }
+    ), "{}", error);
+
+    let exists_goal = problem.replace("(:goal (gone))", "(:goal (exists (?x - thing) (ok ?x)))");
+    assert!(matches!(
+        TemporalTask::try_from_pddl(&domain("(gone)"), &exists_goal),
+        Err(PddlParseError::UnsupportedConstruct { file: "problem", construct: "existential condition", .. })
+    ));
+    let forall_goal = problem.replace("(:goal (gone))", "(:goal (forall (?x - thing) (ok ?x)))");
+    assert!(matches!(
+        TemporalTask::try_from_pddl(&domain("(gone)"), &forall_goal),
+        Err(PddlParseError::UnsupportedConstruct { file: "problem", construct: "universal condition", .. })
+    ));
+}
+
+#[test]
+fn test_negated_numeric_comparisons_block_actions() {
+    let domain = r#"
+(define (domain tank)
+  (:requirements :strips :numeric-fluents :negative-preconditions)
+  (:predicates (gone))
+  (:functions (fuel))
+  (:action go
+    :parameters ()
+    :precondition (not (>= (fuel) 10))
+    :effect (gone))
+)
+"#;
+    let problem = |fuel: u32| format!(r#"
+(define (problem tank-problem)
+  (:domain tank)
+  (:init (= (fuel) {fuel}))
+  (:goal (gone))
+)
+"#);
+
+    let task = TemporalTask::try_from_pddl(domain, &problem(50)).unwrap();
+    assert_eq!(task.actions[0].numeric_conditions_start.len(), 1, "The negated comparison is kept");
+    assert!(!matches!(TemporalAStarSearch::new().search(&task), SearchResult::Solution(_)), "50 >= 10, so go is blocked");
+    let task = TemporalTask::try_from_pddl(domain, &problem(5)).unwrap();
+    assert!(matches!(TemporalAStarSearch::new().search(&task), SearchResult::Solution(_)));
+
+    // A negated equality holds for every other value
+    let not_equal = domain.replace("(not (>= (fuel) 10))", "(not (= (fuel) 50))");
+    let task = TemporalTask::try_from_pddl(&not_equal, &problem(50)).unwrap();
+    assert!(!matches!(TemporalAStarSearch::new().search(&task), SearchResult::Solution(_)));
+    let task = TemporalTask::try_from_pddl(&not_equal, &problem(5)).unwrap();
+    assert!(matches!(TemporalAStarSearch::new().search(&task), SearchResult::Solution(_)));
+
+    // Comparisons use the tolerance of state identity, so equal states agree on them
+    let fuel = |value: f64| State {
+        facts: FactSet::new(0),
+        numeric_values: HashMap::from([("fuel".to_string(), value)]),
+    };
+    let (exact, drifted) = (1000.0, 1000.0 + 1e-10);
+    assert_eq!(fuel(exact), fuel(drifted));
+    assert!(Comparison::Equal.holds(drifted, exact));
+    assert!(!Comparison::NotEqual.holds(drifted, exact));
+    assert!(!Comparison::Greater.holds(drifted, exact));
+    assert!(Comparison::NotEqual.holds(1000.001, exact));
+    assert!(!Comparison::Equal.holds(f64::NAN, f64::NAN));
+}
+
+#[test]
+fn test_durative_effects_are_split_by_time_point() {
+    let domain = |effect: &str| format!(r#"
+(define (domain robotic-control)
+  (:requirements :strips :typing :durative-actions)
+  (:types entity device position)
+  (:predicates
+    (at-position ?e - entity ?p - position)
+    (device-available ?d - device)
+    (device-deployed ?e - entity))
+  (:durative-action deploy-device-durative
+    :parameters (?e - entity ?d - device ?pos - position)
+    :duration (= ?duration 3.0)
+    :condition (and (at start (at-position ?e ?pos))
+                    (at start (device-available ?d)))
+    :effect {effect})
+)
+"#);
+    let problem = r#"
+(define (problem deploy)
+  (:domain robotic-control)
+  (:objects robot1 - entity device1 - device pos1 - position)
+  (:init (at-position robot1 pos1) (device-available device1))
+  (:goal (device-deployed robot1))
+)
+"#;
+
+    let timed = domain("(and (at end (device-deployed ?e))\n                 (at start (not (device-available ?d))))");
+    let task = TemporalTask::try_from_pddl(&timed, problem).unwrap();
+    let deploy = &task.actions[0];
+    assert!(task.warnings.is_empty(), "{:?}", task.warnings);
+    assert_eq!(deploy.effects_start.len(), 1);
+    assert_eq!(deploy.effects_start[0].predicate, "device-available");
+    assert!(deploy.effects_start[0].is_delete, "The device is taken at the start");
+    assert_eq!(deploy.effects_end.len(), 1);
+    assert_eq!(deploy.effects_end[0].predicate, "device-deployed");
+    assert!(!deploy.effects_end[0].is_delete, "The deployment finishes at the end");
+
+    // An effect with no time point is rejected by the strict parser, pointing at the effect
+    let untimed = domain("(and (at end (device-deployed ?e))\n                 (not (device-available ?d)))");
+    let error = TemporalTask::try_from_pddl(&untimed, problem).unwrap_err();
+    assert!(matches!(
+        &error,
+        PddlParseError::UntimedEffect { action, line: 15, column: 18, .. } if action == "deploy-device-durative"
+    ), "{}", error);
+
+    // The lenient parser warns and applies it at the end
+    let task = TemporalTask::from_pddl(&untimed, problem);
+    assert_eq!(task.warnings.len(), 1);
+    assert!(task.warnings[0].contains("deploy-device-durative"), "{}", task.warnings[0]);
+    assert!(task.actions[0].effects_start.is_empty());
+    assert_eq!(task.actions[0].effects_end.len(), 2);
+}
+
+#[test]
+fn test_separation_keeps_conflicting_steps_apart() {
+    let domain_content = r#"
+(define (domain kitchen)
+  (:requirements :strips :durative-actions)
+  (:predicates (hot) (served) (clean))
+  (:durative-action heat
+    :parameters ()
+    :duration (= ?duration 2)
+    :effect (at end (hot)))
+  (:durative-action serve
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (at start (hot))
+    :effect (at end (served)))
+  (:durative-action sweep
+    :parameters ()
+    :duration (= ?duration 1)
+    :effect (at end (clean)))
+)
+"#;
+    let problem_content = r#"
+(define (problem dinner)
+  (:domain kitchen)
+  (:init)
+  (:goal (and (served) (clean)))
+)
+"#;
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let start_of = |plan: &Plan, name: &str| {
+        let step = plan.actions.iter().position(|&idx| task.ground_actions[idx].name == name).unwrap();
+        plan.start_times[step]
+    };
+
+    let SearchResult::Solution(touching) = TemporalAStarSearch::new().with_separation(0.0).search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(start_of(&touching, "serve"), 2.0, "Without separation serve starts as heat ends");
+
+    let SearchResult::Solution(default) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert!((start_of(&default, "serve") - 2.0 - DEFAULT_SEPARATION).abs() < 1e-9, "{:?}", default.start_times);
+
+    let config = PlannerConfig { separation: 0.01, ..PlannerConfig::default() };
+    for kind in [EngineKind::AStar, EngineKind::Greedy] {
+        let SearchResult::Solution(plan) = create_engine(kind, &config).search(&task) else {
+            panic!("Planning should have found a solution");
+        };
+        // serve reads what heat adds at its end, so the two events are forced apart
+        assert!((start_of(&plan, "serve") - 2.01).abs() < 1e-9, "{:?}", plan.start_times);
+        // heat and sweep touch nothing in common and may start together
+        assert_eq!(start_of(&plan, "heat"), 0.0);
+        assert_eq!(start_of(&plan, "sweep"), 0.0);
+    }
+}
+
+#[test]
+fn test_parsed_domain_is_reused_across_problems() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl").unwrap();
+    let delivery = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl").unwrap();
+    // A second problem over the same domain with one package fewer
+    let single = delivery
+        .replace("package1 package2 - package", "package1 - package")
+        .replace("(package-at package2 depot)", "")
+        .replace("(delivered package2)", "");
+
+    let domain = ParsedDomain::parse(&domain_content);
+    let first = TemporalTask::from_domain_and_problem(&domain, &delivery);
+    let second = TemporalTask::from_domain_and_problem(&domain, &single);
+
+    assert_eq!(first.actions, second.actions, "Both tasks share the domain's schemas");
+    assert_eq!(first.actions, domain.actions());
+    assert_eq!(first.objects.len(), 6);
+    assert_eq!(second.objects.len(), 5);
+    assert!(second.ground_actions.len() < first.ground_actions.len());
+    assert_eq!(first, TemporalTask::from_pddl(&domain_content, &delivery), "Same task as parsing both together");
+}
+
+#[test]
+fn test_solve_batch_returns_results_by_problem_file() {
+    let delivery = "tests/fixtures/problems/simple_delivery.pddl";
+    let unreachable = std::env::temp_dir().join(format!("temporal_planner_batch_{}.pddl", std::process::id()));
+    let content = fs::read_to_string(delivery).unwrap().replace("(delivered package2)", "(delivered package2) (at robot1 nowhere)");
+    fs::write(&unreachable, content).unwrap();
+    let unreachable_path = unreachable.to_str().unwrap();
+
+    let missing = "tests/fixtures/problems/missing.pddl";
+    let malformed = std::env::temp_dir().join(format!("temporal_planner_batch_malformed_{}.pddl", std::process::id()));
+    fs::write(&malformed, "(define (problem broken) (:domain simple-robot) (:init (at robot1 room1))").unwrap();
+    let malformed_path = malformed.to_str().unwrap();
+
+    let mut planner = TemporalPlanner::new();
+    let results = planner.solve_batch(
+        "tests/fixtures/domains/simple_robot.pddl",
+        &[delivery, missing, unreachable_path, malformed_path],
+    );
+    fs::remove_file(&unreachable).ok();
+    fs::remove_file(&malformed).ok();
+
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0].0, delivery);
+    assert!(matches!(&results[0].1, SearchResult::Solution(plan) if plan.cost == 6.0), "{:?}", results[0].1);
+    // A missing or malformed problem is reported in its own entry without stopping the batch
+    assert_eq!(results[1].0, missing);
+    assert!(matches!(&results[1].1, SearchResult::InvalidTask(error) if error.contains("missing.pddl")), "{:?}", results[1].1);
+    assert_eq!(results[2].0, unreachable_path);
+    assert!(!matches!(results[2].1, SearchResult::Solution(_) | SearchResult::InvalidTask(_)), "nowhere is not an object");
+    assert_eq!(results[3].0, malformed_path);
+    assert!(matches!(&results[3].1, SearchResult::InvalidTask(error) if error.contains("never closed")), "{:?}", results[3].1);
+
+    // Every problem gets the domain's error; a planner with its own engine solves on that one
+    let no_domain = planner.solve_batch("tests/fixtures/domains/missing.pddl", &[delivery, delivery]);
+    assert_eq!(no_domain.len(), 2);
+    assert!(no_domain.iter().all(|(_, result)| matches!(result, SearchResult::InvalidTask(_))));
+    let mut custom = TemporalPlanner::new().with_search_engine(TemporalAStarSearch::new());
+    let results = custom.solve_batch("tests/fixtures/domains/simple_robot.pddl", &[delivery, delivery]);
+    assert!(results.iter().all(|(_, result)| matches!(result, SearchResult::Solution(plan) if plan.cost == 6.0)));
+}
+
+#[test]
+fn test_predicate_declarations_are_read_structurally() {
+    let domain_content = r#"
+(define (domain warehouse)
+  (:requirements :strips :typing)
+  (:types robot block location)
+  (:predicates (handempty)
+    (on ?x
+        ?y - block) ; split across lines
+    (at ?r - robot ?l - location) (holding ?r - robot ?b)
+    (predicates))
+  (:action wait :parameters () :precondition (handempty) :effect (predicates))
+)
+"#;
+    let problem_content = "(define (problem p) (:domain warehouse) (:init (handempty)) (:goal (predicates)))";
+
+    let task = TemporalTask::try_from_pddl(domain_content, problem_content).unwrap();
+    let declared: Vec<(&str, Vec<&str>)> = task.predicates.iter()
+        .map(|predicate| (predicate.name.as_str(), predicate.parameter_types.iter().map(String::as_str).collect()))
+        .collect();
+    assert_eq!(declared, vec![
+        ("handempty", vec![]),
+        ("on", vec!["block", "block"]),
+        ("at", vec!["robot", "location"]),
+        ("holding", vec!["robot", "object"]),
+        ("predicates", vec![]),
+    ]);
+    assert_eq!(task.summary().predicates, 5);
+    assert!(task.fact_id("handempty", &[]).is_some(), "Nullary predicates are ordinary atoms");
+}
+
+#[test]
+fn test_initial_facts_are_type_checked() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl").unwrap();
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl").unwrap();
+    assert!(TemporalTask::try_from_pddl(&domain_content, &problem_content).is_ok());
+
+    // (at ?r - robot ?l - location) given a package where the robot belongs
+    let mistyped = problem_content.replace("(free-hands robot1)", "(free-hands robot1)\n    (at package1 office)");
+    let error = TemporalTask::try_from_pddl(&domain_content, &mistyped).unwrap_err();
+    let PddlParseError::ArgumentTypeMismatch { predicate, argument, expected, found, line, column } = &error else {
+        panic!("Expected a type mismatch, got {}", error);
+    };
+    assert_eq!((predicate.as_str(), argument.as_str()), ("at", "package1"));
+    assert_eq!((expected.as_str(), found.as_str()), ("robot", "package"));
+    assert_eq!((*line, *column), (13, 9), "The error points at the argument");
+
+    // Timed literals are checked too
+    let timed = problem_content.replace("(free-hands robot1)", "(free-hands robot1)\n    (at 5 (delivered office))");
+    assert!(matches!(
+        TemporalTask::try_from_pddl(&domain_content, &timed),
+        Err(PddlParseError::ArgumentTypeMismatch { argument, .. }) if argument == "office"
+    ));
+}
+
+#[test]
+fn test_bounded_durations_are_chosen_within_their_range() {
+    let domain = r#"
+(define (domain kitchen)
+  (:requirements :durative-actions :duration-inequalities)
+  (:predicates (cooked) (cooled) (served))
+  (:durative-action cook
+    :parameters ()
+    :duration (and (>= ?duration 2) (<= ?duration 6))
+    :condition (and)
+    :effect (at end (cooked)))
+  (:durative-action cool
+    :parameters ()
+    :duration (<= ?duration 4)
+    :condition (at start (cooked))
+    :effect (at end (cooled)))
+  (:durative-action serve
+    :parameters ()
+    :duration (>= ?duration 1)
+    :condition (at start (cooled))
+    :effect (at end (served))))
+"#;
+    let problem = r#"
+(define (problem dinner)
+  (:domain kitchen)
+  (:init)
+  (:goal (served)))
+"#;
+    let task = TemporalTask::from_pddl(domain, problem);
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Expected a plan");
+    };
+    let durations = |task: &TemporalTask, plan: &Plan| -> Vec<(String, f64)> {
+        plan.actions.iter().enumerate()
+            .map(|(step, &action)| {
+                let duration = plan.end_times[step] - plan.start_times[step];
+                (task.ground_actions[action].name.clone(), (duration * 1e6).round() / 1e6)
+            })
+            .collect()
+    };
+    // Nothing needs a longer duration, so each step takes the shortest its range allows; a
+    // range open at 0 still keeps its start and end the separation apart
+    assert_eq!(durations(&task, &plan), vec![
+        ("cook".to_string(), 2.0),
+        ("cool".to_string(), DEFAULT_SEPARATION),
+        ("serve".to_string(), 1.0),
+    ]);
+
+    let domain = r#"
+(define (domain bakery)
+  (:requirements :durative-actions :duration-inequalities :timed-initial-literals)
+  (:predicates (baked) (fresh) (open))
+  (:durative-action bake
+    :parameters ()
+    :duration (and (>= ?duration 1) (<= ?duration 5))
+    :condition (and (at start (fresh)) (at end (open)))
+    :effect (at end (baked))))
+"#;
+    let problem = r#"
+(define (problem morning)
+  (:domain bakery)
+  (:init (fresh) (at 1 (not (fresh))) (at 3 (open)))
+  (:goal (baked)))
+"#;
+    let task = TemporalTask::from_pddl(domain, problem);
+    // The dough has to go in before 1 and come out after the shop opens at 3, so the shortest
+    // bake ends too early
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("A bake long enough to end after the shop opens should be found");
+    };
+    assert_eq!(durations(&task, &plan), vec![("bake".to_string(), 5.0)]);
+
+    // A strict bound would let the search pick the excluded endpoint, so it is rejected
+    let strict = domain.replace("(<= ?duration 5)", "(< ?duration 5)");
+    let error = TemporalTask::try_from_pddl(&strict, problem).unwrap_err();
+    assert!(matches!(
+        &error,
+        PddlParseError::UnsupportedConstruct { construct: "strict duration inequality", line: 7, .. }
+    ), "{}", error);
+    assert!(error.to_string().contains("action 'bake'"), "{}", error);
+    assert!(TemporalTask::try_from_pddl(domain, problem).is_ok());
+
+    // The lenient parser reads it as inclusive, and says so
+    let lenient = TemporalTask::from_pddl(&strict, problem);
+    assert!(lenient.warnings.iter().any(|warning| warning.contains("'bake'") && warning.contains("strict")), "{:?}", lenient.warnings);
+    assert!(task.warnings.is_empty(), "{:?}", task.warnings);
+}
+
+#[test]
+fn test_sequential_makespan_optimality_is_not_parallel_optimality() {
+    let domain = r#"
+(define (domain chores)
+  (:requirements :durative-actions)
+  (:predicates (washed) (dried))
+  (:durative-action wash
+    :parameters ()
+    :duration (= ?duration 3)
+    :condition (and)
+    :effect (at end (washed)))
+  (:durative-action dry
+    :parameters ()
+    :duration (= ?duration 3)
+    :condition (and)
+    :effect (at end (dried)))
+  (:durative-action laundromat
+    :parameters ()
+    :duration (= ?duration 5)
+    :condition (and)
+    :effect (and (at end (washed)) (at end (dried)))))
+"#;
+    let problem = r#"
+(define (problem chores-problem)
+  (:domain chores)
+  (:init)
+  (:goal (and (washed) (dried))))
+"#;
+    let task = TemporalTask::from_pddl(domain, problem);
+    let mut sequential = TemporalAStarSearch::new()
+        .with_cost_model(CostModel::Makespan)
+        .with_heuristic(Box::new(TemporalAdmissibleHeuristic::new()));
+    let SearchResult::Solution(plan) = sequential.search(&task) else {
+        panic!("Expected a plan");
+    };
+    // One action at a time, the laundromat's 5 beats washing and drying for 3 + 3...
+    assert!(plan.optimality.sequential_makespan_optimal);
+    assert_eq!(plan.sequential_cost, 5.0);
+    assert_eq!(plan.makespan(), 5.0);
+
+    // ...but washing alongside drying finishes at 3
+    let action = |name: &str| task.ground_actions.iter().position(|action| action.name == name).unwrap();
+    let separate = Plan {
+        actions: vec![action("wash"), action("dry")],
+        start_times: vec![0.0, 3.0],
+        end_times: vec![3.0, 6.0],
+        cost: 6.0,
+        sequential_cost: 6.0,
+        optimality: Optimality::default(),
+    };
+    let overlapping = temporal_planner::scheduler::schedule_plan(&task, &separate);
+    assert_eq!(overlapping.makespan(), 3.0);
+    assert!(overlapping.makespan() < plan.makespan());
+}
+
+#[test]
+fn test_running_over_all_condition_without_end_effects_blocks_conflicts() {
+    // hold schedules nothing for its end, so only its invariant shows that it is running
+    let domain = r#"
+(define (domain guard)
+  (:requirements :strips :durative-actions)
+  (:predicates (ready) (holding) (knocked))
+  (:durative-action hold
+    :parameters ()
+    :duration (= ?duration 4)
+    :condition (and (at start (ready)) (over all (ready)))
+    :effect (at start (holding)))
+  (:durative-action knock
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (and)
+    :effect (and (at start (not (ready))) (at end (knocked)))))
+"#;
+    let problem = r#"
+(define (problem guard-problem)
+  (:domain guard)
+  (:init (ready))
+  (:goal (and (holding) (knocked))))
+"#;
+    let task = TemporalTask::from_pddl(domain, problem);
+    let action = |name: &str| task.ground_actions.iter().position(|action| action.name == name).unwrap();
+
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Expected a plan");
+    };
+    assert_eq!(plan.actions, vec![action("hold"), action("knock")]);
+    // The scheduler must not pull knock into hold's interval
+    assert_eq!(plan.start_times[0], 0.0);
+    assert!((plan.start_times[1] - 4.001).abs() < 1e-9, "{:?}", plan.start_times);
+
+    let state_space = StateSpace::new(task.clone());
+    let holding = state_space.apply_action(&state_space.initial_state(), action("hold"), 0.0);
+    assert!(holding.scheduled_effects.is_empty());
+    let applicable: Vec<usize> = state_space.get_applicable_actions(&holding).into_iter().map(|(idx, _)| idx).collect();
+    assert!(!applicable.contains(&action("knock")), "knock would delete (ready) while hold runs");
+}
+
+#[test]
+fn test_enabled_actions_are_updated_from_the_parent_state() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/factory_automation.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/factory_production.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let state_space = StateSpace::new(task);
+
+    // Breadth-first over reachable states, deriving each state's set from its parent's
+    let initial = state_space.initial_state();
+    let initial_enabled = state_space.enabled_actions(&initial, None);
+    let mut seen = HashSet::from([initial.classical_state.clone()]);
+    let mut queue = VecDeque::from([(initial, initial_enabled)]);
+    let mut checked = 0;
+    while let Some((state, enabled)) = queue.pop_front() {
+        assert_eq!(state_space.applicable_actions(&state, &enabled), state_space.get_applicable_actions(&state));
+        for (action_idx, _) in state_space.applicable_actions(&state, &enabled) {
+            let Some(successor) = state_space.apply_action_to_completion(&state, action_idx) else {
+                continue;
+            };
+            let updated = state_space.enabled_actions(&successor, Some(&enabled));
+            assert_eq!(updated, state_space.enabled_actions(&successor, None));
+            checked += 1;
+            if seen.insert(successor.classical_state.clone()) && seen.len() < 500 {
+                queue.push_back((successor, updated));
+            }
+        }
+    }
+    assert!(checked > 100, "only {} successors checked", checked);
+}
+
+#[test]
+fn test_stats_count_distinct_states() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    let mut search = TemporalAStarSearch::new();
+    assert!(matches!(search.search(&task), SearchResult::Solution(_)));
+    let stats = search.stats();
+    assert!(stats.distinct_states > 0);
+    assert!(stats.distinct_states <= stats.nodes_expanded, "Reopened states are expanded more than once");
+}
+
+#[test]
+fn test_greedy_rollout_finds_a_plan() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let planner = TemporalPlanner::new();
+
+    let plan = planner.greedy_rollout(&task).expect("The rollout should reach the goal");
+    assert!(!plan.actions.is_empty());
+    assert_eq!(plan.actions.len(), plan.start_times.len());
+    assert!(plan.cost >= 6.0, "The optimal makespan bounds any plan from below");
+
+    // Replaying the plan reaches the goal
+    let state_space = StateSpace::new(task.clone());
+    let mut state = TemporalState {
+        classical_state: task.initial_state.clone(),
+        scheduled_effects: Vec::new(),
+        scheduled_numeric_effects: Vec::new(),
+        invariants: Vec::new(),
+        time: 0.0,
+    };
+    for &action_idx in &plan.actions {
+        state = state_space.apply_action_to_completion(&state, action_idx)
+            .expect("Plan steps should be executable");
+    }
+    assert!(state_space.is_goal(&state));
+}
+
+#[test]
+fn test_plans_carry_parallel_schedules() {
+    let domain_content = r#"
+(define (domain dock)
+  (:requirements :durative-actions)
+  (:predicates (docked) (charged) (free) (painted))
+  (:durative-action charge
+    :parameters ()
+    :duration (= ?duration 4)
+    :condition (over all (docked))
+    :effect (at end (charged)))
+  (:durative-action undock
+    :parameters ()
+    :duration (= ?duration 1)
+    :condition (at start (docked))
+    :effect (and (at start (not (docked))) (at end (free))))
+  (:durative-action paint
+    :parameters ()
+    :duration (= ?duration 3)
+    :condition (and)
+    :effect (at end (painted)))
+)
+"#;
+    let problem_content = r#"
+(define (problem dock-problem)
+  (:domain dock)
+  (:init (docked))
+  (:goal (and (charged) (free) (painted)))
+)
+"#;
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+
+    let step = |name: &str| {
+        let position = plan.actions.iter()
+            .position(|&idx| task.ground_actions[idx].name == name)
+            .expect("Every action is needed");
+        (plan.start_times[position], plan.end_times[position])
+    };
+    let (charge, undock, paint) = (step("charge"), step("undock"), step("paint"));
+    for ((start, end), duration) in [(charge, 4.0), (undock, 1.0), (paint, 3.0)] {
+        assert!((end - start - duration).abs() < 1e-9);
+    }
+    assert!(undock.0 >= charge.1, "Undocking must wait until charging, which needs the dock over all, ends");
+    assert!(paint.0 < charge.1, "Painting is independent and overlaps the charging");
+    assert!((plan.makespan() - 5.001).abs() < 1e-9, "Undocking follows charging after the default separation");
+}
+
+#[test]
+fn test_parallel_makespan_is_below_sequential_cost() {
+    let domain_content = r#"
+(define (domain errands)
+  (:requirements :durative-actions)
+  (:predicates (shopped) (cooked))
+  (:durative-action shop
+    :parameters ()
+    :duration (= ?duration 3)
+    :condition (and)
+    :effect (at end (shopped)))
+  (:durative-action cook
+    :parameters ()
+    :duration (= ?duration 4)
+    :condition (and)
+    :effect (at end (cooked)))
+)
+"#;
+    let problem_content = r#"
+(define (problem errands-problem)
+  (:domain errands)
+  (:init)
+  (:goal (and (shopped) (cooked)))
+)
+"#;
+    let task = TemporalTask::from_pddl(domain_content, problem_content);
+
+    let SearchResult::Solution(sequential) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(sequential.cost, 7.0, "Makespan cost runs one action at a time");
+    assert_eq!(sequential.sequential_cost, 7.0);
+    assert_eq!(sequential.makespan(), 4.0, "Both errands run side by side");
+
+    let mut search = TemporalAStarSearch::new().with_cost_model(CostModel::ParallelMakespan);
+    let SearchResult::Solution(parallel) = search.search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(parallel.cost, 4.0);
+    assert_eq!(parallel.makespan(), parallel.cost);
+    assert_eq!(parallel.sequential_cost, 7.0);
+}
+
+#[test]
+fn test_task_round_trips_through_binary_file() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    let path = std::env::temp_dir().join(format!("temporal_planner_task_{}.bin", std::process::id()));
+    task.save_binary(&path).expect("Saving the task should succeed");
+    let loaded = TemporalTask::load_binary(&path);
+    fs::remove_file(&path).ok();
+    let loaded = loaded.expect("Loading the saved task should succeed");
+
+    assert_eq!(loaded.actions, task.actions);
+    assert_eq!(loaded.ground_actions, task.ground_actions);
+    assert_eq!(loaded.initial_state, task.initial_state);
+    assert_eq!(loaded.goal_conditions, task.goal_conditions);
+    assert_eq!(loaded.fact_names, task.fact_names);
+
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&loaded) else {
+        panic!("The reloaded task should still be solvable");
+    };
+    assert_eq!(plan.cost, 6.0);
+}
+
+#[test]
+fn test_task_round_trips_through_json() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    let json = task.to_json().expect("Serializing the task should succeed");
+    let loaded = TemporalTask::from_json(&json).expect("Deserializing the task should succeed");
+    assert_eq!(loaded, task);
+}
+
+#[test]
+fn test_plans_report_optimality_guarantees() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/simple_robot.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/simple_delivery.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+    let mut admissible = TemporalAStarSearch::new()
+        .with_cost_model(CostModel::Makespan)
+        .with_heuristic(Box::new(TemporalAdmissibleHeuristic::new()));
+    let SearchResult::Solution(plan) = admissible.search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert!(plan.optimality.sequential_makespan_optimal);
+    assert!(!plan.optimality.cost_optimal, "Action costs were not minimized");
+
+    let SearchResult::Solution(plan) = TemporalAStarSearch::new().search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert_eq!(plan.optimality, Optimality::default(), "The FF heuristic is not admissible");
+
+    let mut weighted = TemporalAStarSearch::new()
+        .with_weight(2.0)
+        .with_heuristic(Box::new(TemporalAdmissibleHeuristic::new()));
+    let SearchResult::Solution(plan) = weighted.search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+    assert!(!plan.optimality.sequential_makespan_optimal);
+}
+
+#[test]
+fn test_add_goal_and_resume_reuses_the_frontier() {
+    let domain_content = fs::read_to_string("tests/fixtures/domains/blocks_world.pddl")
+        .expect("Failed to read domain file");
+    let problem_content = fs::read_to_string("tests/fixtures/problems/stack_blocks.pddl")
+        .expect("Failed to read problem file");
+    let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+    let mut first_goal = task.clone();
+    let second = first_goal.goal_conditions.remove(0);
+
+    let mut cold = TemporalAStarSearch::new();
+    let SearchResult::Solution(cold_plan) = cold.search(&task) else {
+        panic!("Planning should have found a solution");
+    };
+
+    let mut incremental = TemporalAStarSearch::new();
+    assert!(matches!(incremental.add_goal_and_resume(second.clone()), SearchResult::Failure), "Nothing to resume yet");
+    assert!(matches!(incremental.search(&first_goal), SearchResult::Solution(_)));
+    let first_expanded = incremental.stats().nodes_expanded;
+    let SearchResult::Solution(plan) = incremental.add_goal_and_resume(second) else {
+        panic!("Resuming should find a plan for both goals");
+    };
+    let resumed_expanded = incremental.stats().nodes_expanded;
+
+    let state_space = StateSpace::new(task.clone());
+    let mut state = TemporalState {
+        classical_state: task.initial_state.clone(),
+        scheduled_effects: Vec::new(),
+        scheduled_numeric_effects: Vec::new(),
+        invariants: Vec::new(),
+        time: 0.0,
+    };
+    for &action_idx in &plan.actions {
+        state = state_space.apply_action_to_completion(&state, action_idx)
+            .expect("Plan steps should be executable");
+    }
+    assert!(state_space.is_goal(&state), "Both goals hold at the end of the resumed plan");
+    assert_eq!(plan.cost, cold_plan.cost);
+    assert!(
+        first_expanded + resumed_expanded < cold.stats().nodes_expanded,
+        "{} + {} expansions should beat a cold solve with {}",
+        first_expanded,
+        resumed_expanded,
+        cold.stats().nodes_expanded
+    );
+}
+
+#[test]
+fn test_equal_states_hash_alike() {
+    let hash = |state: &State| {
+        let mut hasher = DefaultHasher::new();
+        state.hash(&mut hasher);
+        hasher.finish()
+    };
+    let fluents = ["fuel", "load", "speed", "battery", "distance", "cargo", "heat", "charge"];
+
+    let mut a = State { facts: FactSet::from(vec![true, false, true]), numeric_values: HashMap::new() };
+    let mut b = State { facts: FactSet::from(vec![true, false, true]), numeric_values: HashMap::new() };
+    // Same values, inserted in opposite orders and computed differently
+    for (i, name) in fluents.iter().enumerate() {
+        a.numeric_values.insert(name.to_string(), 0.1 + 0.2 + i as f64);
+    }
+    for (i, name) in fluents.iter().enumerate().rev() {
+        b.numeric_values.insert(name.to_string(), 0.3 + i as f64);
+    }
+    assert_ne!(a.numeric_values["fuel"], b.numeric_values["fuel"], "The values differ in their last bits");
+    assert_eq!(a, b);
+    assert_eq!(hash(&a), hash(&b));
+
+    b.numeric_values.insert("fuel".to_string(), 0.3001);
+    assert_ne!(a, b, "Values a tenth of a thousandth apart are different states");
+}
+
+/// Helper function to run all end-to-end tests and report results
+pub fn run_all_e2e_tests() {
+    println!("🚀 Running End-to-End Tests for Temporal Planner");
+    println!("{}", "=".repeat(50));
+    
+    test_simple_robot_domain_parsing();
+    test_blocks_world_domain_parsing();
+    test_factory_automation_complex_parsing();
+    test_action_temporal_properties();
+    test_error_handling_invalid_domain();
+    
+    println!("{}", "=".repeat(50));
+    println!("✅ All end-to-end tests completed successfully!");
+}
+
 #[cfg(test)]
 mod benchmarks {
     use super::*;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
     use std::time::Instant;
+    use temporal_planner::state_space::StateRegistry;
+
+    thread_local! {
+        static LIVE_BYTES: Cell<isize> = const { Cell::new(0) };
+    }
+
+    /// Heap bytes the current thread has allocated and not freed, for measuring what a data
+    /// structure really holds; other threads' tests do not disturb the count
+    fn live_bytes() -> isize {
+        LIVE_BYTES.with(Cell::get)
+    }
+
+    struct CountingAllocator;
+
+    fn count(bytes: isize) {
+        // Allocations while the thread shuts down are not counted
+        let _ = LIVE_BYTES.try_with(|live| live.set(live.get() + bytes));
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            count(layout.size() as isize);
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            count(-(layout.size() as isize));
+            System.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            count(new_size as isize - layout.size() as isize);
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
 
     #[test]
     fn benchmark_domain_parsing() {
@@ -218,19 +3592,114 @@ mod benchmarks {
         println!("✅ Benchmark: Domain parsing took {:?}", duration);
         assert!(duration.as_millis() < 1000, "Parsing should complete within 1 second");
     }
-}
 
-/// Helper function to run all end-to-end tests and report results
-pub fn run_all_e2e_tests() {
-    println!("🚀 Running End-to-End Tests for Temporal Planner");
-    println!("{}", "=".repeat(50));
-    
-    test_simple_robot_domain_parsing();
-    test_blocks_world_domain_parsing();
-    test_factory_automation_complex_parsing();
-    test_action_temporal_properties();
-    test_error_handling_invalid_domain();
-    
-    println!("{}", "=".repeat(50));
-    println!("✅ All end-to-end tests completed successfully!");
+    #[test]
+    fn benchmark_state_interning() {
+        let domain_content = fs::read_to_string("tests/fixtures/domains/factory_automation.pddl")
+            .expect("Failed to read domain file");
+        let problem_content = fs::read_to_string("tests/fixtures/problems/factory_production.pddl")
+            .expect("Failed to read problem file");
+        let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+
+        let mut search_engine = TemporalAStarSearch::new();
+        assert!(matches!(search_engine.search(&task), SearchResult::Solution(_)));
+        let stats = search_engine.stats();
+        assert!(stats.interned_states >= stats.distinct_states);
+        assert!(
+            stats.interned_states < stats.nodes_generated,
+            "Duplicate successors should share interned states"
+        );
+
+        // Every successor a search generates from a sample of reachable states, duplicates included
+        let state_space = StateSpace::new(task.clone());
+        let mut generated = vec![state_space.initial_state()];
+        let mut expanded = 0;
+        while expanded < generated.len() && expanded < 300 {
+            let state = generated[expanded].clone();
+            expanded += 1;
+            for (action_idx, _) in state_space.get_applicable_actions(&state) {
+                generated.extend(state_space.apply_action_to_completion(&state, action_idx));
+            }
+        }
+
+        // Nodes used to own a full state each; now they hold an id into the registry
+        let before = live_bytes();
+        let owned: Vec<TemporalState> = generated.to_vec();
+        let owned_bytes = live_bytes() - before;
+        let before = live_bytes();
+        let mut registry = StateRegistry::new();
+        let ids: Vec<StateId> = generated.iter().map(|state| registry.intern(state)).collect();
+        let interned_bytes = live_bytes() - before;
+        assert_eq!(ids.len(), owned.len());
+        assert!(registry.len() < generated.len(), "The sample revisits states");
+
+        println!(
+            "✅ Benchmark: {} generated states, {} distinct; {} bytes allocated owning them vs {} bytes interning them",
+            generated.len(),
+            registry.len(),
+            owned_bytes,
+            interned_bytes
+        );
+        assert!(interned_bytes < owned_bytes);
+    }
+
+    #[test]
+    fn benchmark_fact_set_hashing() {
+        let domain_content = fs::read_to_string("tests/fixtures/domains/factory_automation.pddl")
+            .expect("Failed to read domain file");
+        let problem_content = fs::read_to_string("tests/fixtures/problems/factory_production.pddl")
+            .expect("Failed to read problem file");
+        let task = TemporalTask::from_pddl(&domain_content, &problem_content);
+        let state_space = StateSpace::new(task.clone());
+
+        // Breadth-first sample of reachable states
+        let mut states = vec![TemporalState {
+            classical_state: task.initial_state.clone(),
+            scheduled_effects: Vec::new(),
+            scheduled_numeric_effects: Vec::new(),
+            invariants: Vec::new(),
+            time: 0.0,
+        }];
+        let mut seen: HashSet<_> = states.iter().map(|state| state.classical_state.clone()).collect();
+        let mut next = 0;
+        while next < states.len() && states.len() < 2000 {
+            let state = states[next].clone();
+            next += 1;
+            for (action_idx, _) in state_space.get_applicable_actions(&state) {
+                if let Some(successor) = state_space.apply_action_to_completion(&state, action_idx) {
+                    if seen.insert(successor.classical_state.clone()) {
+                        states.push(successor);
+                    }
+                }
+            }
+        }
+        let bitsets: Vec<_> = states.iter().map(|state| state.classical_state.facts.clone()).collect();
+        let bools: Vec<Vec<bool>> = bitsets.iter().map(|facts| facts.iter().collect()).collect();
+        for (facts, bools) in bitsets.iter().zip(&bools) {
+            assert_eq!(facts, &FactSet::from(bools.clone()));
+        }
+
+        const ROUNDS: usize = 20;
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            let set: HashSet<&Vec<bool>> = bools.iter().collect();
+            assert_eq!(set.len(), bools.len());
+        }
+        let bool_time = start.elapsed();
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            let set: HashSet<&FactSet> = bitsets.iter().collect();
+            assert_eq!(set.len(), bitsets.len());
+        }
+        let bitset_time = start.elapsed();
+
+        println!(
+            "✅ Benchmark: hashing {} factory_automation states of {} facts {} times: Vec<bool> {:?}, FactSet {:?}",
+            bitsets.len(),
+            task.fact_names.len(),
+            ROUNDS,
+            bool_time,
+            bitset_time
+        );
+    }
 }